@@ -0,0 +1,139 @@
+'use strict'
+
+let React
+let ReactDOM
+
+// lets the scheduler run the renders of the event handlers
+function waitForPaint() {
+    return new Promise((resolve) => setTimeout(resolve, 10))
+}
+
+describe('controlled components', () => {
+    let container
+    let root
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        document.body.appendChild(container)
+        root = ReactDOM.createRoot(container)
+    })
+
+    afterEach(() => {
+        document.body.removeChild(container)
+    })
+
+    function render(element) {
+        ReactDOM.flushSync(() => root.render(element))
+    }
+
+    // what typing into a text field does
+    function type(node, value) {
+        node.value = value
+        node.dispatchEvent(new Event('input', { bubbles: true }))
+    }
+
+    it('puts the value of a controlled input back when onChange ignores it', () => {
+        render(<input value="a" onChange={() => {}} />)
+        const input = container.firstChild
+        expect(input.value).toBe('a')
+
+        type(input, 'ab')
+        expect(input.value).toBe('a')
+    })
+
+    it('shows what the onChange of a controlled input set', async () => {
+        function App() {
+            const [value, setValue] = React.useState('')
+            return <input value={value} onChange={(e) => setValue(e.target.value.toUpperCase())} />
+        }
+        render(<App />)
+        const input = container.firstChild
+
+        type(input, 'ab')
+        await waitForPaint()
+        expect(input.value).toBe('AB')
+    })
+
+    it('leaves the value of an uncontrolled input to the user', () => {
+        render(<input defaultValue="a" />)
+        const input = container.firstChild
+        expect(input.value).toBe('a')
+
+        type(input, 'typed')
+        render(<input defaultValue="b" />)
+        expect(input.value).toBe('typed')
+        expect(input.defaultValue).toBe('b')
+    })
+
+    it('keeps a controlled checkbox as checked says', () => {
+        render(<input type="checkbox" checked={true} onChange={() => {}} />)
+        const checkbox = container.firstChild
+        expect(checkbox.checked).toBe(true)
+
+        checkbox.click()
+        expect(checkbox.checked).toBe(true)
+
+        render(<input type="checkbox" checked={false} onChange={() => {}} />)
+        expect(checkbox.checked).toBe(false)
+    })
+
+    it('calls onChange of a checkbox once per click', () => {
+        const onChange = jest.fn()
+        render(<input type="checkbox" checked={false} onChange={onChange} />)
+
+        container.firstChild.click()
+        expect(onChange).toHaveBeenCalledTimes(1)
+    })
+
+    it('controls the value of a textarea', () => {
+        render(<textarea value="a" onChange={() => {}} />)
+        const textarea = container.firstChild
+        expect(textarea.value).toBe('a')
+
+        type(textarea, 'ab')
+        expect(textarea.value).toBe('a')
+
+        render(<textarea value="c" onChange={() => {}} />)
+        expect(textarea.value).toBe('c')
+    })
+
+    it('selects the options of a controlled select', () => {
+        const options = [
+            <option key="a" value="a">
+                A
+            </option>,
+            <option key="b" value="b">
+                B
+            </option>,
+            <option key="c" value="c">
+                C
+            </option>,
+        ]
+        render(
+            <select value="b" onChange={() => {}}>
+                {options}
+            </select>
+        )
+        const select = container.firstChild
+        expect(select.value).toBe('b')
+
+        select.value = 'c'
+        select.dispatchEvent(new Event('change', { bubbles: true }))
+        expect(select.value).toBe('b')
+
+        render(
+            <select multiple={true} value={['a', 'c']} onChange={() => {}}>
+                {options}
+            </select>
+        )
+        expect(Array.from(select.selectedOptions).map((option) => option.value)).toEqual([
+            'a',
+            'c',
+        ])
+    })
+})
@@ -0,0 +1,97 @@
+'use strict'
+
+let React
+let ReactDOM
+
+const HTML_NAMESPACE = 'http://www.w3.org/1999/xhtml'
+const SVG_NAMESPACE = 'http://www.w3.org/2000/svg'
+const MATH_NAMESPACE = 'http://www.w3.org/1998/Math/MathML'
+const XLINK_NAMESPACE = 'http://www.w3.org/1999/xlink'
+
+describe('namespaces', () => {
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+    })
+
+    function render(element, container = document.createElement('div')) {
+        const root = ReactDOM.createRoot(container)
+        ReactDOM.flushSync(() => root.render(element))
+        return container
+    }
+
+    it('creates svg and its children in the svg namespace', () => {
+        const container = render(
+            <svg>
+                <g>
+                    <circle />
+                </g>
+            </svg>
+        )
+        const svg = container.firstChild
+        expect(svg.namespaceURI).toBe(SVG_NAMESPACE)
+        expect(svg.querySelector('g').namespaceURI).toBe(SVG_NAMESPACE)
+        expect(svg.querySelector('circle').namespaceURI).toBe(SVG_NAMESPACE)
+    })
+
+    it('goes back to html inside foreignObject', () => {
+        const container = render(
+            <svg>
+                <foreignObject>
+                    <div>
+                        <svg />
+                    </div>
+                </foreignObject>
+            </svg>
+        )
+        const foreignObject = container.querySelector('foreignObject')
+        expect(foreignObject.namespaceURI).toBe(SVG_NAMESPACE)
+        expect(foreignObject.firstChild.namespaceURI).toBe(HTML_NAMESPACE)
+        expect(foreignObject.firstChild.firstChild.namespaceURI).toBe(SVG_NAMESPACE)
+    })
+
+    it('creates math in the mathml namespace', () => {
+        const container = render(
+            <math>
+                <mi>x</mi>
+            </math>
+        )
+        expect(container.firstChild.namespaceURI).toBe(MATH_NAMESPACE)
+        expect(container.firstChild.firstChild.namespaceURI).toBe(MATH_NAMESPACE)
+    })
+
+    it('renders into an svg container in the svg namespace', () => {
+        const svg = document.createElementNS(SVG_NAMESPACE, 'svg')
+        render(<rect />, svg)
+        expect(svg.firstChild.namespaceURI).toBe(SVG_NAMESPACE)
+    })
+
+    it('writes xlink and svg attributes', () => {
+        const container = document.createElement('div')
+        const root = ReactDOM.createRoot(container)
+        ReactDOM.flushSync(() =>
+            root.render(
+                <svg className="icon">
+                    <use xlinkHref="#a" strokeWidth={2} />
+                </svg>
+            )
+        )
+        const svg = container.firstChild
+        const use = svg.firstChild
+        expect(svg.getAttribute('class')).toBe('icon')
+        expect(use.getAttributeNS(XLINK_NAMESPACE, 'href')).toBe('#a')
+        expect(use.getAttribute('stroke-width')).toBe('2')
+
+        ReactDOM.flushSync(() =>
+            root.render(
+                <svg className="icon">
+                    <use />
+                </svg>
+            )
+        )
+        expect(use.hasAttributeNS(XLINK_NAMESPACE, 'href')).toBe(false)
+        expect(use.hasAttribute('stroke-width')).toBe(false)
+    })
+})
@@ -0,0 +1,70 @@
+'use strict'
+
+let React
+let ReactDOM
+
+describe('ReactDOM.createPortal', () => {
+    let container
+    let root
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        root = ReactDOM.createRoot(container)
+    })
+
+    function render(element) {
+        ReactDOM.flushSync(() => root.render(element))
+    }
+
+    it('throws a TypeError for a container that is not a DOM node', () => {
+        expect(() => ReactDOM.createPortal(<span />, {})).toThrow(TypeError)
+        expect(() => ReactDOM.createPortal(<span />, null)).toThrow(
+            'Target container is not a DOM element.'
+        )
+    })
+
+    it('updates the children of a portal in place', () => {
+        const modal = document.createElement('div')
+        render(<div>{ReactDOM.createPortal(<p title="a">a</p>, modal)}</div>)
+        const p = modal.firstChild
+
+        render(<div>{ReactDOM.createPortal(<p title="b">b</p>, modal)}</div>)
+        expect(modal.innerHTML).toBe('<p title="b">b</p>')
+        expect(modal.firstChild).toBe(p)
+        expect(container.innerHTML).toBe('<div></div>')
+    })
+
+    it('moves the children to a new container', () => {
+        const first = document.createElement('div')
+        const second = document.createElement('div')
+        render(<div>{ReactDOM.createPortal(<p>moved</p>, first)}</div>)
+        expect(first.innerHTML).toBe('<p>moved</p>')
+
+        render(<div>{ReactDOM.createPortal(<p>moved</p>, second)}</div>)
+        expect(first.innerHTML).toBe('')
+        expect(second.innerHTML).toBe('<p>moved</p>')
+    })
+
+    it('bubbles events through the react tree, not the dom tree', () => {
+        const modal = document.createElement('div')
+        document.body.appendChild(modal)
+        const calls = []
+        render(
+            <div onClick={() => calls.push('outer')}>
+                {ReactDOM.createPortal(
+                    <button onClick={() => calls.push('button')}>open</button>,
+                    modal
+                )}
+            </div>
+        )
+
+        modal.firstChild.click()
+        expect(calls).toEqual(['button', 'outer'])
+        document.body.removeChild(modal)
+    })
+})
@@ -0,0 +1,79 @@
+'use strict'
+
+let React
+let ReactDOM
+
+describe('style and dangerouslySetInnerHTML', () => {
+    let container
+    let root
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        root = ReactDOM.createRoot(container)
+    })
+
+    function render(element) {
+        ReactDOM.flushSync(() => root.render(element))
+        return container.firstChild
+    }
+
+    it('sets a style object, with px for numbers that are not unitless', () => {
+        const div = render(
+            <div style={{ backgroundColor: 'red', width: 10, opacity: 0.5, margin: 0 }} />
+        )
+        expect(div.style.backgroundColor).toBe('red')
+        expect(div.style.width).toBe('10px')
+        expect(div.style.opacity).toBe('0.5')
+        expect(div.style.margin).toBe('0px')
+    })
+
+    it('sets custom properties as they are', () => {
+        const div = render(<div style={{ '--gap': 4 }} />)
+        expect(div.style.getPropertyValue('--gap')).toBe('4')
+    })
+
+    it('only touches the style properties that changed', () => {
+        const div = render(<div style={{ color: 'red', width: 10 }} />)
+        div.style.height = '5px'
+
+        render(<div style={{ color: 'blue' }} />)
+        expect(div.style.color).toBe('blue')
+        expect(div.style.width).toBe('')
+        // set outside of react, so left alone
+        expect(div.style.height).toBe('5px')
+    })
+
+    it('removes properties that become null or boolean', () => {
+        const div = render(<div style={{ color: 'red', width: 10 }} />)
+        render(<div style={{ color: null, width: false }} />)
+        expect(div.getAttribute('style')).toBeFalsy()
+    })
+
+    it('sets and replaces dangerouslySetInnerHTML', () => {
+        const div = render(<div dangerouslySetInnerHTML={{ __html: '<b>bold</b>' }} />)
+        expect(div.innerHTML).toBe('<b>bold</b>')
+
+        render(<div dangerouslySetInnerHTML={{ __html: '<i>italic</i>' }} />)
+        expect(div.innerHTML).toBe('<i>italic</i>')
+        expect(div.hasAttribute('dangerouslySetInnerHTML')).toBe(false)
+    })
+
+    it('switches between dangerouslySetInnerHTML and children', () => {
+        const div = render(<div dangerouslySetInnerHTML={{ __html: '<b>html</b>' }} />)
+
+        render(
+            <div>
+                <span>child</span>
+            </div>
+        )
+        expect(div.innerHTML).toBe('<span>child</span>')
+
+        render(<div dangerouslySetInnerHTML={{ __html: '<b>html</b>' }} />)
+        expect(div.innerHTML).toBe('<b>html</b>')
+    })
+})
@@ -0,0 +1,77 @@
+'use strict'
+
+let React
+let ReactDOM
+
+// lets the scheduler run the tasks of non-urgent lanes
+function waitForPaint() {
+    return new Promise((resolve) => setTimeout(resolve, 10))
+}
+
+describe('useDeferredValue', () => {
+    let container
+    let root
+    let log
+    let setValue
+
+    function App({ initialValue }) {
+        const [value, _setValue] = React.useState('a')
+        setValue = _setValue
+        const deferred = React.useDeferredValue(value, initialValue)
+        log.push(`${value} ${deferred}`)
+        return <span>{deferred}</span>
+    }
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        root = ReactDOM.createRoot(container)
+        log = []
+    })
+
+    it('keeps the previous value for an urgent update and catches up later', async () => {
+        root.render(<App />)
+        await waitForPaint()
+        expect(log).toEqual(['a a'])
+
+        ReactDOM.flushSync(() => setValue('b'))
+        expect(log).toEqual(['a a', 'b a'])
+        expect(container.innerHTML).toBe('<span>a</span>')
+
+        await waitForPaint()
+        expect(log).toEqual(['a a', 'b a', 'b b'])
+        expect(container.innerHTML).toBe('<span>b</span>')
+    })
+
+    it('shows the initial value on mount', async () => {
+        ReactDOM.flushSync(() => root.render(<App initialValue="initial" />))
+        expect(container.innerHTML).toBe('<span>initial</span>')
+
+        await waitForPaint()
+        expect(log).toEqual(['a initial', 'a a'])
+        expect(container.innerHTML).toBe('<span>a</span>')
+    })
+
+    it('returns the new value right away inside a transition', async () => {
+        root.render(<App />)
+        await waitForPaint()
+
+        React.startTransition(() => setValue('b'))
+        await waitForPaint()
+        expect(log).toEqual(['a a', 'b b'])
+    })
+
+    it('does not render again when the value did not change', async () => {
+        root.render(<App />)
+        await waitForPaint()
+
+        ReactDOM.flushSync(() => setValue('a'))
+        await waitForPaint()
+        expect(log.every((entry) => entry === 'a a')).toBe(true)
+        expect(container.innerHTML).toBe('<span>a</span>')
+    })
+})
@@ -0,0 +1,182 @@
+'use strict'
+
+let React
+let ReactDOM
+
+describe('DevTools', () => {
+    let container
+    let root
+    let messages
+    let handler
+    let backend
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        root = ReactDOM.createRoot(container)
+        messages = []
+        handler = null
+        backend = ReactDOM.connectDevTools({
+            send: (json) => messages.push(JSON.parse(json)),
+            listen: (h) => (handler = h),
+        })
+    })
+
+    afterEach(() => {
+        backend.disconnect()
+    })
+
+    function takeMessages() {
+        const taken = messages
+        messages = []
+        return taken
+    }
+
+    function findAdd(displayName) {
+        for (const message of messages) {
+            if (message.type !== 'operations') continue
+            const add = message.operations.find(
+                (operation) => operation.op === 'add' && operation.displayName === displayName
+            )
+            if (add) return add
+        }
+        return null
+    }
+
+    function inspect(id, requestId) {
+        handler(JSON.stringify({ type: 'inspectElement', id, requestId }))
+        const response = takeMessages().find((message) => message.type === 'inspectedElement')
+        expect(response.id).toBe(id)
+        expect(response.requestId).toBe(requestId)
+        return response.element
+    }
+
+    it('sends add operations for a new tree', () => {
+        function App() {
+            return <span>hi</span>
+        }
+        root.render(<App />)
+
+        const app = findAdd('App')
+        expect(app).not.toBe(null)
+        const span = messages
+            .flatMap((message) => message.operations || [])
+            .find((operation) => operation.op === 'add' && operation.parentId === app.id)
+        expect(span.tag).toBe('HostComponent')
+    })
+
+    it('overrides hook state, inspects it and schedules an update', () => {
+        let renders = 0
+        function App() {
+            renders++
+            const [count] = React.useState(0)
+            return <span>{count}</span>
+        }
+        root.render(<App />)
+        expect(container.innerHTML).toBe('<span>0</span>')
+        const { id } = findAdd('App')
+        takeMessages()
+
+        const before = inspect(id, 1)
+        expect(before.hooks).toEqual([{ id: 0, kind: 'State', value: 0 }])
+
+        ReactDOM.flushSync(() => {
+            handler(JSON.stringify({ type: 'overrideHookState', id, hookId: 0, value: 5 }))
+        })
+        expect(container.innerHTML).toBe('<span>5</span>')
+        expect(takeMessages().every((message) => message.type !== 'error')).toBe(true)
+
+        const after = inspect(id, 2)
+        expect(after.hooks).toEqual([{ id: 0, kind: 'State', value: 5 }])
+
+        const rendered = renders
+        ReactDOM.flushSync(() => {
+            handler(JSON.stringify({ type: 'scheduleUpdate', id }))
+        })
+        expect(renders).toBe(rendered + 1)
+        expect(container.innerHTML).toBe('<span>5</span>')
+    })
+
+    it('answers an inspectElement of an unknown id with a null element', () => {
+        root.render(<div />)
+        takeMessages()
+        expect(inspect(123456, 7)).toBe(null)
+    })
+
+    it('answers messages it can not handle with an error', () => {
+        function App() {
+            React.useMemo(() => 1, [])
+            return <div />
+        }
+        root.render(<App />)
+        const { id } = findAdd('App')
+        takeMessages()
+
+        handler('{ not json')
+        handler(JSON.stringify({ type: 'unknown' }))
+        handler(JSON.stringify({ type: 'overrideHookState', id: 123456, hookId: 0, value: 1 }))
+        handler(JSON.stringify({ type: 'overrideHookState', id, hookId: 0, value: 1 }))
+        handler(JSON.stringify({ type: 'scheduleUpdate', id: 123456 }))
+        expect(takeMessages()).toEqual([
+            { type: 'error', message: 'message is not valid JSON' },
+            { type: 'error', message: 'unknown message type unknown' },
+            { type: 'error', message: 'no element with this id' },
+            { type: 'error', message: 'hookId is not a state hook' },
+            { type: 'error', message: 'no element with this id' },
+        ])
+    })
+
+    it('throws a TypeError for a transport without send', () => {
+        expect(() => ReactDOM.connectDevTools({})).toThrow(TypeError)
+        expect(() => ReactDOM.connectDevTools({ send: 1 })).toThrow(
+            'transport.send should be a function'
+        )
+    })
+
+    it('keeps the subtree of a component that did not render', () => {
+        let setCount
+        function Static() {
+            return (
+                <ul>
+                    <li>a</li>
+                </ul>
+            )
+        }
+        // the same element every render, Static bails out
+        const staticElement = <Static />
+        function App() {
+            const [count, _setCount] = React.useState(0)
+            setCount = _setCount
+            return (
+                <div>
+                    <span>{count}</span>
+                    {staticElement}
+                </div>
+            )
+        }
+        root.render(<App />)
+        const staticId = findAdd('Static').id
+        takeMessages()
+
+        ReactDOM.flushSync(() => setCount(1))
+        const operations = takeMessages().flatMap((message) => message.operations || [])
+        expect(operations.some((operation) => operation.op === 'update')).toBe(true)
+        expect(operations.filter((operation) => operation.op !== 'update')).toEqual([])
+        expect(operations.some((operation) => operation.id === staticId)).toBe(false)
+        expect(inspect(staticId, 1).children.length).toBe(1)
+    })
+
+    it('stops handling messages once disconnected', () => {
+        root.render(<div />)
+        backend.disconnect()
+        takeMessages()
+
+        handler(JSON.stringify({ type: 'unknown' }))
+        root.render(<span />)
+        expect(messages).toEqual([])
+    })
+})
@@ -0,0 +1,126 @@
+'use strict'
+
+let React
+let ReactDOM
+
+describe('development warnings', () => {
+    let container
+    let root
+    let errors
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        root = ReactDOM.createRoot(container)
+        errors = []
+        ReactDOM.configureLogging({
+            sink: ({ level, message }) => level === 'error' && errors.push(message),
+        })
+    })
+
+    afterEach(() => {
+        ReactDOM.configureLogging({ sink: null })
+    })
+
+    function render(element) {
+        ReactDOM.flushSync(() => root.render(element))
+    }
+
+    it('throws when a hook is called outside of a component', () => {
+        expect(() => React.useState(0)).toThrow('Invalid hook call.')
+    })
+
+    it('warns when the order of hooks changes, with the component stack', () => {
+        let id
+        // both hooks keep the id, so only the order differs
+        function App({ swap }) {
+            if (swap) {
+                React.useDeferredValue(id)
+                React.useId()
+            } else {
+                id = React.useId()
+                React.useDeferredValue(id)
+            }
+            return <span />
+        }
+        render(
+            <div>
+                <App swap={false} />
+            </div>
+        )
+        expect(errors).toEqual([])
+
+        render(
+            <div>
+                <App swap={true} />
+            </div>
+        )
+        expect(errors.length).toBe(1)
+        expect(errors[0]).toContain(
+            'React has detected a change in the order of Hooks called by App.'
+        )
+        expect(errors[0]).toContain('1. useId')
+        expect(errors[0]).toMatch(/\n {4}in App\n {4}in div$/)
+    })
+
+    it('does not commit a render with more hooks than the previous one', () => {
+        function App({ more }) {
+            const [a] = React.useState('a')
+            if (more) {
+                React.useState('b')
+            }
+            return <span>{a}</span>
+        }
+        render(<App more={false} />)
+        expect(container.innerHTML).toBe('<span>a</span>')
+
+        render(<App more={true} />)
+        expect(container.innerHTML).toBe('<span>a</span>')
+        expect(
+            errors.some((message) =>
+                message.includes('Rendered more hooks than during the previous render.')
+            )
+        ).toBe(true)
+    })
+
+    it('does not commit a render with fewer hooks than the previous one', () => {
+        function App({ early }) {
+            const [a] = React.useState('a')
+            if (early) {
+                return <b>early</b>
+            }
+            React.useState('b')
+            return <span>{a}</span>
+        }
+        render(<App early={false} />)
+        render(<App early={true} />)
+        expect(container.innerHTML).toBe('<span>a</span>')
+        expect(
+            errors.some((message) => message.includes('Rendered fewer hooks than expected.'))
+        ).toBe(true)
+    })
+
+    it('warns about setState on an unmounted component once', () => {
+        let setState
+        function App() {
+            const [value, _setState] = React.useState(0)
+            setState = _setState
+            return <span>{value}</span>
+        }
+        render(<App />)
+        render(null)
+        expect(container.innerHTML).toBe('')
+
+        setState(1)
+        setState(2)
+        expect(errors.length).toBe(1)
+        expect(errors[0]).toContain(
+            "Can't perform a React state update on a component that is not mounted."
+        )
+        expect(errors[0]).toMatch(/\n {4}in App$/)
+    })
+})
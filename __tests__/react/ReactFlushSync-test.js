@@ -0,0 +1,74 @@
+'use strict'
+
+let React
+let ReactDOM
+
+describe('flushSync', () => {
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+    })
+
+    function createApp() {
+        const container = document.createElement('div')
+        const root = ReactDOM.createRoot(container)
+        const app = { container, root }
+        app.App = function App() {
+            const [value, setValue] = React.useState('a')
+            app.setValue = setValue
+            return <span>{value}</span>
+        }
+        return app
+    }
+
+    it('returns the value of the callback, also before a root is created', () => {
+        expect(ReactDOM.flushSync(() => 42)).toBe(42)
+    })
+
+    it('commits the render before it returns', () => {
+        const { container, root, App } = createApp()
+        ReactDOM.flushSync(() => root.render(<App />))
+        expect(container.innerHTML).toBe('<span>a</span>')
+    })
+
+    it('renders the updates of a transition inside it synchronously', () => {
+        const app = createApp()
+        ReactDOM.flushSync(() => app.root.render(<app.App />))
+
+        React.startTransition(() => {
+            ReactDOM.flushSync(() => app.setValue('b'))
+        })
+        expect(app.container.innerHTML).toBe('<span>b</span>')
+    })
+
+    it('flushes every root', () => {
+        const first = createApp()
+        const second = createApp()
+        ReactDOM.flushSync(() => {
+            first.root.render(<first.App />)
+            second.root.render(<second.App />)
+        })
+
+        ReactDOM.flushSync(() => {
+            first.setValue('b')
+            second.setValue('c')
+        })
+        expect(first.container.innerHTML).toBe('<span>b</span>')
+        expect(second.container.innerHTML).toBe('<span>c</span>')
+    })
+
+    it('rethrows what the callback throws after flushing its updates', () => {
+        const app = createApp()
+        ReactDOM.flushSync(() => app.root.render(<app.App />))
+
+        expect(() =>
+            ReactDOM.flushSync(() => {
+                app.setValue('b')
+                throw new Error('oops')
+            })
+        ).toThrow('oops')
+        expect(app.container.innerHTML).toBe('<span>b</span>')
+    })
+})
@@ -0,0 +1,85 @@
+'use strict'
+
+let React
+let ReactDOM
+
+describe('useId', () => {
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+    })
+
+    function render(element) {
+        const container = document.createElement('div')
+        const root = ReactDOM.createRoot(container)
+        ReactDOM.flushSync(() => root.render(element))
+        return { container, root }
+    }
+
+    function Field() {
+        const id = React.useId()
+        return <input id={id} />
+    }
+
+    function ids(container) {
+        return Array.from(container.querySelectorAll('[id]')).map((node) => node.id)
+    }
+
+    it('gives siblings different ids in the :R<id>: format', () => {
+        const { container } = render(
+            <div>
+                <Field />
+                <Field />
+                <Field />
+            </div>
+        )
+        const [a, b, c] = ids(container)
+        expect(a).toMatch(/^:R[0-9a-v]*:$/)
+        expect(new Set([a, b, c]).size).toBe(3)
+    })
+
+    it('keeps the id across renders', () => {
+        const seen = []
+        let setCount
+        function App() {
+            const [count, _setCount] = React.useState(0)
+            setCount = _setCount
+            seen.push(React.useId())
+            return count
+        }
+        render(<App />)
+        ReactDOM.flushSync(() => setCount(1))
+        expect(seen.length).toBe(2)
+        expect(seen[0]).toBe(seen[1])
+    })
+
+    it('derives the ids from the position in the tree, the same in every root', () => {
+        const app = (
+            <section>
+                <p>
+                    <Field />
+                </p>
+                <Field />
+            </section>
+        )
+        expect(ids(render(app).container)).toEqual(ids(render(app).container))
+    })
+
+    it('tells apart more than one useId in a component', () => {
+        function Form() {
+            const name = React.useId()
+            const email = React.useId()
+            return (
+                <form>
+                    <input id={name} />
+                    <input id={email} />
+                </form>
+            )
+        }
+        const [name, email] = ids(render(<Form />).container)
+        expect(name).not.toBe(email)
+        expect(email).toBe(name.slice(0, -1) + 'H1:')
+    })
+})
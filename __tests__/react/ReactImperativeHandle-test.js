@@ -0,0 +1,70 @@
+'use strict'
+
+let React
+let ReactDOM
+
+describe('useImperativeHandle', () => {
+    let container
+    let root
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        root = ReactDOM.createRoot(container)
+    })
+
+    function render(element) {
+        ReactDOM.flushSync(() => root.render(element))
+    }
+
+    function createInput() {
+        return React.forwardRef(function Input({ label }, ref) {
+            const inputRef = { current: null }
+            React.useImperativeHandle(ref, () => ({ label, focus: () => inputRef.current }), [label])
+            return <input ref={inputRef} />
+        })
+    }
+
+    it('gives the ref the handle instead of the node', () => {
+        const Input = createInput()
+        const ref = { current: null }
+        render(<Input ref={ref} label="a" />)
+        expect(ref.current.label).toBe('a')
+        expect(ref.current.focus()).toBe(container.firstChild)
+    })
+
+    it('creates a new handle only when the deps change', () => {
+        const Input = createInput()
+        const ref = { current: null }
+        render(<Input ref={ref} label="a" />)
+        const first = ref.current
+
+        render(<Input ref={ref} label="a" />)
+        expect(ref.current).toBe(first)
+
+        render(<Input ref={ref} label="b" />)
+        expect(ref.current).not.toBe(first)
+        expect(ref.current.label).toBe('b')
+    })
+
+    it('calls a ref callback with the handle and null on unmount', () => {
+        const Input = createInput()
+        const calls = []
+        const ref = (handle) => calls.push(handle && handle.label)
+        render(<Input ref={ref} label="a" />)
+        render(null)
+        expect(calls).toEqual(['a', null])
+    })
+
+    it('passes the ref of a forwardRef component through to a node', () => {
+        const Button = React.forwardRef((props, ref) => <button ref={ref}>{props.children}</button>)
+        const ref = { current: null }
+        render(<Button ref={ref}>go</Button>)
+        expect(ref.current).toBe(container.firstChild)
+        expect(ref.current.textContent).toBe('go')
+    })
+})
@@ -0,0 +1,81 @@
+'use strict'
+
+let React
+let ReactDOM
+
+describe('useInsertionEffect', () => {
+    let container
+    let root
+    let log
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        root = ReactDOM.createRoot(container)
+        log = []
+    })
+
+    function render(element) {
+        ReactDOM.flushSync(() => root.render(element))
+    }
+
+    // a ref callback is attached in the layout phase, after every insertion effect
+    const attach = (node) => log.push(node ? 'attach' : 'detach')
+
+    function Styled({ color }) {
+        React.useInsertionEffect(() => {
+            log.push(`insert ${color}`)
+            return () => log.push(`remove ${color}`)
+        }, [color])
+        return <span ref={attach}>{color}</span>
+    }
+
+    it('runs before refs are attached', () => {
+        render(<Styled color="red" />)
+        expect(log).toEqual(['insert red', 'attach'])
+        expect(container.innerHTML).toBe('<span>red</span>')
+    })
+
+    it('cleans up before running again when the deps change', () => {
+        render(<Styled color="red" />)
+        log = []
+
+        render(<Styled color="red" />)
+        expect(log).toEqual([])
+
+        render(<Styled color="blue" />)
+        expect(log).toEqual(['remove red', 'insert blue'])
+    })
+
+    it('cleans up on unmount', () => {
+        render(
+            <div>
+                <Styled color="red" />
+            </div>
+        )
+        log = []
+
+        render(<div />)
+        expect(log).toEqual(['remove red', 'detach'])
+        expect(container.innerHTML).toBe('<div></div>')
+    })
+
+    it('runs the insertion effects of children before the refs of their parents', () => {
+        function Parent() {
+            React.useInsertionEffect(() => {
+                log.push('insert parent')
+            }, [])
+            return (
+                <div ref={(node) => node && log.push('attach parent')}>
+                    <Styled color="red" />
+                </div>
+            )
+        }
+        render(<Parent />)
+        expect(log).toEqual(['insert red', 'insert parent', 'attach', 'attach parent'])
+    })
+})
@@ -0,0 +1,97 @@
+'use strict'
+
+let React
+let ReactDOM
+
+// lets the scheduler and the thenable callbacks run
+function waitForPaint() {
+    return new Promise((resolve) => setTimeout(resolve, 10))
+}
+
+describe('lazy', () => {
+    let container
+    let root
+    let errors
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        root = ReactDOM.createRoot(container)
+        errors = []
+        ReactDOM.configureLogging({
+            sink: ({ level, message }) => level === 'error' && errors.push(message),
+        })
+    })
+
+    afterEach(() => {
+        ReactDOM.configureLogging({ sink: null })
+    })
+
+    it('shows the fallback until the module loads and calls the loader once', async () => {
+        let resolve
+        const load = jest.fn(() => new Promise((r) => (resolve = r)))
+        const Text = React.lazy(load)
+
+        root.render(
+            <React.Suspense fallback="loading">
+                <Text text="a" />
+            </React.Suspense>
+        )
+        await waitForPaint()
+        expect(container.innerHTML).toBe('loading')
+
+        resolve({ default: ({ text }) => <span>{text}</span> })
+        await waitForPaint()
+        expect(container.innerHTML).toBe('<span>a</span>')
+
+        root.render(
+            <React.Suspense fallback="loading">
+                <Text text="b" />
+            </React.Suspense>
+        )
+        await waitForPaint()
+        expect(container.innerHTML).toBe('<span>b</span>')
+        expect(load).toHaveBeenCalledTimes(1)
+    })
+
+    it('does not commit a module that failed to load', async () => {
+        let reject
+        const Text = React.lazy(() => new Promise((_, r) => (reject = r)))
+
+        root.render(
+            <React.Suspense fallback="loading">
+                <Text />
+            </React.Suspense>
+        )
+        await waitForPaint()
+        expect(container.innerHTML).toBe('loading')
+
+        reject('network error')
+        await waitForPaint()
+        expect(container.innerHTML).toBe('loading')
+        expect(errors.length).toBe(1)
+        expect(errors[0]).toContain('network error')
+    })
+
+    it('rejects a class component', async () => {
+        class Legacy {}
+        Legacy.prototype.isReactComponent = {}
+        const Text = React.lazy(() => Promise.resolve({ default: Legacy }))
+
+        root.render(
+            <React.Suspense fallback="loading">
+                <Text />
+            </React.Suspense>
+        )
+        await waitForPaint()
+        await waitForPaint()
+        expect(container.innerHTML).toBe('loading')
+        expect(errors.some((message) => message.includes('class components are not supported'))).toBe(
+            true
+        )
+    })
+})
@@ -0,0 +1,58 @@
+'use strict'
+
+let React
+let ReactDOM
+
+describe('Profiler', () => {
+    let container
+    let root
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        root = ReactDOM.createRoot(container)
+    })
+
+    it('reports the durations of the clock set for tests', () => {
+        let time = 0
+        ReactDOM.unstable_setProfilerClock(() => (time += 5))
+        const onRender = jest.fn()
+        ReactDOM.flushSync(() =>
+            root.render(
+                <React.Profiler id="app" onRender={onRender}>
+                    <div />
+                </React.Profiler>
+            )
+        )
+        expect(onRender).toHaveBeenCalledTimes(1)
+        const [id, phase, actualDuration] = onRender.mock.calls[0]
+        expect(id).toBe('app')
+        expect(phase).toBe('mount')
+        expect(actualDuration).toBeGreaterThan(0)
+    })
+
+    it('falls back to Date.now when the clock throws', () => {
+        ReactDOM.unstable_setProfilerClock(() => {
+            throw new Error('broken clock')
+        })
+        const warn = jest.spyOn(console, 'warn').mockImplementation(() => {})
+        const onRender = jest.fn()
+        ReactDOM.flushSync(() =>
+            root.render(
+                <React.Profiler id="app" onRender={onRender}>
+                    <div />
+                </React.Profiler>
+            )
+        )
+        expect(container.innerHTML).toBe('<div></div>')
+        expect(onRender).toHaveBeenCalledTimes(1)
+        expect(typeof onRender.mock.calls[0][2]).toBe('number')
+        // once, not for every reading of the clock
+        expect(warn).toHaveBeenCalledTimes(1)
+        warn.mockRestore()
+    })
+})
@@ -0,0 +1,106 @@
+'use strict'
+
+let React
+let ReactDOM
+
+describe('getSnapshot', () => {
+    let container
+    let root
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        root = ReactDOM.createRoot(container)
+    })
+
+    function render(element) {
+        ReactDOM.flushSync(() => root.render(element))
+        return root.getSnapshot()
+    }
+
+    it('describes the committed fiber tree', () => {
+        function App({ title }) {
+            const [count] = React.useState(1)
+            return <div title={title}>{count}</div>
+        }
+        const snapshot = render(<App title="t" />)
+
+        expect(snapshot.tag).toBe('HostRoot')
+        const app = snapshot.children[0]
+        expect(app).toMatchObject({
+            tag: 'FunctionComponent',
+            type: 'App',
+            key: null,
+            props: { title: 't' },
+            hooks: [{ id: 0, kind: 'State', value: 1 }],
+            hostNodeId: null,
+        })
+        const div = app.children[0]
+        expect(div).toMatchObject({ tag: 'HostComponent', type: 'div', hooks: [] })
+        expect(typeof div.hostNodeId).toBe('number')
+        expect(Array.isArray(div.flags)).toBe(true)
+    })
+
+    it('keeps the ids of fibers and host nodes across renders', () => {
+        function App({ label }) {
+            return <span>{label}</span>
+        }
+        const first = render(<App label="a" />)
+        const second = render(<App label="b" />)
+
+        const ids = (node) => [node.id, node.hostNodeId, ...node.children.flatMap(ids)]
+        expect(ids(second)).toEqual(ids(first))
+        expect(second.children[0].props).toEqual({ label: 'b' })
+    })
+
+    it('gives new fibers new ids', () => {
+        const first = render(<p key="a" />)
+        const second = render(<p key="b" />)
+        expect(second.children[0].key).toBe('b')
+        expect(second.children[0].id).not.toBe(first.children[0].id)
+    })
+
+    it('turns props into values JSON can hold', () => {
+        function handleClick() {}
+        function Item() {
+            return null
+        }
+        function App() {
+            return null
+        }
+        const snapshot = render(
+            <App
+                onClick={handleClick}
+                item={<Item />}
+                missing={undefined}
+                deep={{ a: { b: { c: { d: 1 } } } }}
+                list={[1, [2, [3, [4]]]]}
+            />
+        )
+
+        const props = snapshot.children[0].props
+        expect(props).toEqual({
+            onClick: 'ƒ handleClick()',
+            item: '<Item />',
+            missing: null,
+            deep: { a: { b: { c: '{…}' } } },
+            list: [1, [2, [3, '[…]']]],
+        })
+        expect(JSON.parse(JSON.stringify(snapshot))).toEqual(snapshot)
+    })
+
+    it('leaves element children to the child fibers', () => {
+        const snapshot = render(
+            <section>
+                <b>bold</b>
+            </section>
+        )
+        const section = snapshot.children[0]
+        expect(section.props).toEqual({})
+        expect(section.children[0].type).toBe('b')
+    })
+})
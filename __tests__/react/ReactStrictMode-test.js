@@ -0,0 +1,105 @@
+'use strict'
+
+let React
+let ReactDOM
+
+describe('StrictMode', () => {
+    let container
+    let root
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        root = ReactDOM.createRoot(container)
+    })
+
+    it('renders components twice', () => {
+        let renders = 0
+        function App() {
+            renders++
+            return <div />
+        }
+
+        root.render(
+            <React.StrictMode>
+                <App />
+            </React.StrictMode>
+        )
+        expect(renders).toBe(2)
+
+        root.render(<App />)
+        expect(renders).toBe(3)
+    })
+
+    it('calls useMemo initializers twice and keeps the memoized value', () => {
+        const created = []
+        function App({ n }) {
+            const value = React.useMemo(() => {
+                created.push(n)
+                return { n }
+            }, [n])
+            return <span>{value.n}</span>
+        }
+
+        root.render(
+            <React.StrictMode>
+                <App n={1} />
+            </React.StrictMode>
+        )
+        expect(created).toEqual([1, 1])
+        expect(container.textContent).toBe('1')
+
+        root.render(
+            <React.StrictMode>
+                <App n={1} />
+            </React.StrictMode>
+        )
+        expect(created).toEqual([1, 1])
+
+        root.render(
+            <React.StrictMode>
+                <App n={2} />
+            </React.StrictMode>
+        )
+        expect(created).toEqual([1, 1, 2, 2])
+        expect(container.textContent).toBe('2')
+    })
+
+    it('mounts the layout effects of new components a second time', () => {
+        const log = []
+        // the ref is one of the deps, so it has to stay the same between renders
+        const refs = {}
+        const refFor = (name) =>
+            (refs[name] ||= (handle) => log.push(handle === null ? `detach ${name}` : `attach ${name}`))
+        function Child({ name }) {
+            React.useImperativeHandle(refFor(name), () => name, [])
+            return null
+        }
+
+        root.render(
+            <div>
+                <Child name="loose" />
+                <React.StrictMode>
+                    <Child name="strict" />
+                </React.StrictMode>
+            </div>
+        )
+        expect(log).toEqual(['attach loose', 'attach strict', 'detach strict', 'attach strict'])
+
+        // only components placed by a commit are replayed
+        log.length = 0
+        root.render(
+            <div>
+                <Child name="loose" />
+                <React.StrictMode>
+                    <Child name="strict" />
+                </React.StrictMode>
+            </div>
+        )
+        expect(log).toEqual([])
+    })
+})
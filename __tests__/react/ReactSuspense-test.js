@@ -0,0 +1,118 @@
+'use strict'
+
+let React
+let ReactDOM
+
+// lets the scheduler and the thenable callbacks run
+function waitForPaint() {
+    return new Promise((resolve) => setTimeout(resolve, 10))
+}
+
+// read throws the pending promise, like a data library would, until resolve is called
+function createResource() {
+    let status = 'pending'
+    let value
+    let resolve
+    const promise = new Promise((r) => (resolve = r)).then((resolved) => {
+        status = 'fulfilled'
+        value = resolved
+    })
+    return {
+        read() {
+            if (status === 'pending') throw promise
+            return value
+        },
+        resolve: (resolved) => resolve(resolved),
+    }
+}
+
+describe('Suspense', () => {
+    let container
+    let root
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        root = ReactDOM.createRoot(container)
+    })
+
+    function render(element) {
+        ReactDOM.flushSync(() => root.render(element))
+    }
+
+    it('shows the fallback until the thrown promise resolves', async () => {
+        const resource = createResource()
+        function Data() {
+            return <span>{resource.read()}</span>
+        }
+        render(
+            <React.Suspense fallback="loading">
+                <Data />
+            </React.Suspense>
+        )
+        expect(container.innerHTML).toBe('loading')
+
+        resource.resolve('data')
+        await waitForPaint()
+        expect(container.innerHTML).toBe('<span>data</span>')
+    })
+
+    it('hides the shown children when they suspend again and keeps their state', async () => {
+        let setCount
+        function Counter() {
+            const [count, _setCount] = React.useState(0)
+            setCount = _setCount
+            return <b>{count}</b>
+        }
+        function Data({ resource }) {
+            return <span>{resource ? resource.read() : 'ready'}</span>
+        }
+        function App({ resource }) {
+            return (
+                <React.Suspense fallback={<i>loading</i>}>
+                    <Counter />
+                    <Data resource={resource} />
+                </React.Suspense>
+            )
+        }
+        render(<App resource={null} />)
+        ReactDOM.flushSync(() => setCount(1))
+        const counter = container.querySelector('b')
+        const data = container.querySelector('span')
+        expect(counter.textContent).toBe('1')
+
+        const resource = createResource()
+        render(<App resource={resource} />)
+        // still mounted, hidden next to the fallback
+        expect(counter.style.display).toBe('none')
+        expect(data.style.display).toBe('none')
+        expect(container.querySelector('i').textContent).toBe('loading')
+
+        resource.resolve('data')
+        await waitForPaint()
+        expect(container.querySelector('i')).toBe(null)
+        expect(container.querySelector('b')).toBe(counter)
+        expect(counter.style.display).toBe('')
+        expect(counter.textContent).toBe('1')
+        expect(container.querySelector('span').textContent).toBe('data')
+    })
+
+    it('retries a render that suspended outside of any boundary once pinged', async () => {
+        render(<p>before</p>)
+        const resource = createResource()
+        function Data() {
+            return <p>{resource.read()}</p>
+        }
+        render(<Data />)
+        // nothing to show a fallback, the current tree stays
+        expect(container.innerHTML).toBe('<p>before</p>')
+
+        resource.resolve('data')
+        await waitForPaint()
+        expect(container.innerHTML).toBe('<p>data</p>')
+    })
+})
@@ -0,0 +1,128 @@
+'use strict'
+
+let React
+let ReactDOM
+
+// lets the scheduler run passive effects and non-urgent lanes
+function waitForPaint() {
+    return new Promise((resolve) => setTimeout(resolve, 10))
+}
+
+function createStore(state) {
+    const listeners = new Set()
+    return {
+        listeners,
+        subscribe(listener) {
+            listeners.add(listener)
+            return () => listeners.delete(listener)
+        },
+        getSnapshot() {
+            return state
+        },
+        set(next) {
+            state = next
+            listeners.forEach((listener) => listener())
+        },
+    }
+}
+
+describe('useSyncExternalStore', () => {
+    let container
+    let root
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        root = ReactDOM.createRoot(container)
+    })
+
+    it('renders the snapshot and renders again when the store changes', async () => {
+        const store = createStore(0)
+        function App() {
+            return <span>{React.useSyncExternalStore(store.subscribe, store.getSnapshot)}</span>
+        }
+        root.render(<App />)
+        await waitForPaint()
+        expect(container.innerHTML).toBe('<span>0</span>')
+        expect(store.listeners.size).toBe(1)
+
+        store.set(1)
+        await waitForPaint()
+        expect(container.innerHTML).toBe('<span>1</span>')
+    })
+
+    it('catches a change between the render and the subscription', async () => {
+        const store = createStore('a')
+        function App() {
+            return <span>{React.useSyncExternalStore(store.subscribe, store.getSnapshot)}</span>
+        }
+        ReactDOM.flushSync(() => root.render(<App />))
+        expect(container.innerHTML).toBe('<span>a</span>')
+        expect(store.listeners.size).toBe(0)
+
+        store.set('b')
+        await waitForPaint()
+        expect(container.innerHTML).toBe('<span>b</span>')
+    })
+
+    it('unsubscribes on unmount and when subscribe changes', async () => {
+        const first = createStore(1)
+        const second = createStore(2)
+        function App({ store }) {
+            return <span>{React.useSyncExternalStore(store.subscribe, store.getSnapshot)}</span>
+        }
+        root.render(<App store={first} />)
+        await waitForPaint()
+        expect(first.listeners.size).toBe(1)
+
+        root.render(<App store={second} />)
+        await waitForPaint()
+        expect(container.innerHTML).toBe('<span>2</span>')
+        expect(first.listeners.size).toBe(0)
+        expect(second.listeners.size).toBe(1)
+
+        root.render(null)
+        await waitForPaint()
+        expect(second.listeners.size).toBe(0)
+    })
+
+    it('does not render again for a snapshot that did not change', async () => {
+        const store = createStore(0)
+        let renders = 0
+        function App() {
+            renders++
+            return React.useSyncExternalStore(store.subscribe, store.getSnapshot)
+        }
+        root.render(<App />)
+        await waitForPaint()
+        const rendered = renders
+
+        store.set(0)
+        await waitForPaint()
+        expect(renders).toBe(rendered)
+    })
+
+    it('accepts a getServerSnapshot and warns when its result is not cached', () => {
+        const store = createStore(0)
+        const errors = []
+        ReactDOM.configureLogging({
+            sink: ({ level, message }) => level === 'error' && errors.push(message),
+        })
+        function App({ getServerSnapshot }) {
+            return React.useSyncExternalStore(store.subscribe, store.getSnapshot, getServerSnapshot)
+        }
+        ReactDOM.flushSync(() => root.render(<App getServerSnapshot={() => 0} />))
+        expect(container.innerHTML).toBe('0')
+        expect(errors).toEqual([])
+
+        ReactDOM.flushSync(() => root.render(<App getServerSnapshot={() => ({})} />))
+        expect(errors).toEqual([
+            'The result of getServerSnapshot should be cached to avoid an infinite loop',
+        ])
+        ReactDOM.configureLogging({ sink: null })
+    })
+})
@@ -0,0 +1,102 @@
+'use strict'
+
+let React
+let ReactDOM
+
+// lets the scheduler run the tasks of non-urgent lanes
+function waitForPaint() {
+    return new Promise((resolve) => setTimeout(resolve, 10))
+}
+
+describe('transitions', () => {
+    let container
+    let root
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        root = ReactDOM.createRoot(container)
+    })
+
+    it('renders isPending urgently and the transition after it', async () => {
+        const log = []
+        let startTransition
+        let setValue
+        function App() {
+            const [isPending, _startTransition] = React.useTransition()
+            const [value, _setValue] = React.useState('a')
+            startTransition = _startTransition
+            setValue = _setValue
+            log.push(`${isPending} ${value}`)
+            return <span>{value}</span>
+        }
+        root.render(<App />)
+        await waitForPaint()
+        expect(log).toEqual(['false a'])
+
+        startTransition(() => setValue('b'))
+        await waitForPaint()
+        expect(log).toEqual(['false a', 'true a', 'false b'])
+        expect(container.innerHTML).toBe('<span>b</span>')
+    })
+
+    it('keeps the same startTransition across renders', async () => {
+        const starts = []
+        let setValue
+        function App() {
+            const [, startTransition] = React.useTransition()
+            const [value, _setValue] = React.useState(0)
+            setValue = _setValue
+            starts.push(startTransition)
+            return value
+        }
+        root.render(<App />)
+        await waitForPaint()
+        setValue(1)
+        await waitForPaint()
+        expect(starts.length).toBe(2)
+        expect(starts[0]).toBe(starts[1])
+    })
+
+    it('does not render an update of startTransition synchronously', async () => {
+        let setValue
+        function App() {
+            const [value, _setValue] = React.useState('a')
+            setValue = _setValue
+            return <span>{value}</span>
+        }
+        root.render(<App />)
+        await waitForPaint()
+
+        React.startTransition(() => setValue('b'))
+        expect(container.innerHTML).toBe('<span>a</span>')
+        await waitForPaint()
+        expect(container.innerHTML).toBe('<span>b</span>')
+    })
+
+    it('renders an urgent update before a pending transition', async () => {
+        const log = []
+        let setSlow
+        let setFast
+        function App() {
+            const [slow, _setSlow] = React.useState('a')
+            const [fast, _setFast] = React.useState(0)
+            setSlow = _setSlow
+            setFast = _setFast
+            log.push(`${slow} ${fast}`)
+            return null
+        }
+        root.render(<App />)
+        await waitForPaint()
+
+        React.startTransition(() => setSlow('b'))
+        ReactDOM.flushSync(() => setFast(1))
+        expect(log).toEqual(['a 0', 'a 1'])
+        await waitForPaint()
+        expect(log).toEqual(['a 0', 'a 1', 'b 1'])
+    })
+})
@@ -0,0 +1,107 @@
+'use strict'
+
+let React
+let ReactDOM
+
+// lets the scheduler and the thenable callbacks run
+function waitForPaint() {
+    return new Promise((resolve) => setTimeout(resolve, 10))
+}
+
+describe('use', () => {
+    let container
+    let root
+    let errors
+
+    beforeEach(() => {
+        jest.resetModules()
+
+        React = require('../../dist/react')
+        ReactDOM = require('../../dist/react-dom')
+
+        container = document.createElement('div')
+        root = ReactDOM.createRoot(container)
+        errors = []
+        ReactDOM.configureLogging({
+            sink: ({ level, message }) => level === 'error' && errors.push(message),
+        })
+    })
+
+    afterEach(() => {
+        ReactDOM.configureLogging({ sink: null })
+    })
+
+    it('reads a thenable that is fulfilled already without suspending', () => {
+        const thenable = { status: 'fulfilled', value: 'data', then() {} }
+        function App() {
+            return <span>{React.use(thenable)}</span>
+        }
+        root.render(
+            <React.Suspense fallback="loading">
+                <App />
+            </React.Suspense>
+        )
+        expect(container.innerHTML).toBe('<span>data</span>')
+    })
+
+    it('suspends on a pending promise and renders its value once it resolves', async () => {
+        let resolve
+        const promise = new Promise((r) => (resolve = r))
+        let renders = 0
+        function App() {
+            renders++
+            return <span>{React.use(promise)}</span>
+        }
+        root.render(
+            <React.Suspense fallback="loading">
+                <App />
+            </React.Suspense>
+        )
+        await waitForPaint()
+        expect(container.innerHTML).toBe('loading')
+
+        resolve('data')
+        await waitForPaint()
+        expect(container.innerHTML).toBe('<span>data</span>')
+        // the resolved value is read from the promise, it is not awaited again
+        expect(renders).toBe(2)
+    })
+
+    it('does not commit a render that reads a rejected thenable', async () => {
+        root.render(<div>a</div>)
+        await waitForPaint()
+        expect(container.innerHTML).toBe('<div>a</div>')
+
+        const thenable = { status: 'rejected', reason: 'boom', then() {} }
+        function App() {
+            return <span>{React.use(thenable)}</span>
+        }
+        root.render(
+            <div>
+                b<App />
+            </div>
+        )
+        await waitForPaint()
+        expect(container.innerHTML).toBe('<div>a</div>')
+        expect(errors.length).toBe(1)
+        expect(errors[0]).toContain('Uncaught error, the update was not committed')
+        expect(errors[0]).toContain('boom')
+    })
+
+    it('reads a context like useContext', async () => {
+        const Theme = React.createContext('light')
+        function Label() {
+            return <span>{React.use(Theme)}</span>
+        }
+        root.render(
+            <div>
+                <Label />
+                <Theme.Provider value="dark">
+                    <Label />
+                </Theme.Provider>
+            </div>
+        )
+        await waitForPaint()
+        expect(container.innerHTML).toBe('<div><span>light</span><span>dark</span></div>')
+    })
+})
@@ -0,0 +1,96 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    // 0.0 - 1.0
+    pub a: f64,
+}
+
+impl Color {
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0.0);
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: f64) -> Self {
+        Color { r, g, b, a }
+    }
+
+    pub fn with_alpha(&self, alpha: f64) -> Self {
+        Color {
+            a: self.a * alpha,
+            ..*self
+        }
+    }
+}
+
+static NAMED_COLORS: [(&str, Color); 12] = [
+    ("transparent", Color::TRANSPARENT),
+    ("black", Color::rgba(0, 0, 0, 1.0)),
+    ("white", Color::rgba(255, 255, 255, 1.0)),
+    ("red", Color::rgba(255, 0, 0, 1.0)),
+    ("green", Color::rgba(0, 128, 0, 1.0)),
+    ("blue", Color::rgba(0, 0, 255, 1.0)),
+    ("yellow", Color::rgba(255, 255, 0, 1.0)),
+    ("orange", Color::rgba(255, 165, 0, 1.0)),
+    ("purple", Color::rgba(128, 0, 128, 1.0)),
+    ("cyan", Color::rgba(0, 255, 255, 1.0)),
+    ("magenta", Color::rgba(255, 0, 255, 1.0)),
+    ("gray", Color::rgba(128, 128, 128, 1.0)),
+];
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+    let short = |i: usize| channel(&hex[i..i + 1].repeat(2));
+    match hex.len() {
+        3 => Some(Color::rgba(short(0)?, short(1)?, short(2)?, 1.0)),
+        6 | 8 => {
+            let alpha = if hex.len() == 8 {
+                channel(&hex[6..8])? as f64 / 255.0
+            } else {
+                1.0
+            };
+            Some(Color::rgba(
+                channel(&hex[0..2])?,
+                channel(&hex[2..4])?,
+                channel(&hex[4..6])?,
+                alpha,
+            ))
+        }
+        _ => None,
+    }
+}
+
+// rgb(r, g, b) or rgba(r, g, b, a)
+fn parse_rgb(args: &str) -> Option<Color> {
+    let parts: Vec<&str> = args.split(',').map(|part| part.trim()).collect();
+    if parts.len() != 3 && parts.len() != 4 {
+        return None;
+    }
+    let channel = |s: &str| s.parse::<f64>().ok().map(|v| v.clamp(0.0, 255.0) as u8);
+    let alpha = match parts.get(3) {
+        Some(a) => a.parse::<f64>().ok()?.clamp(0.0, 1.0),
+        None => 1.0,
+    };
+    Some(Color::rgba(
+        channel(parts[0])?,
+        channel(parts[1])?,
+        channel(parts[2])?,
+        alpha,
+    ))
+}
+
+pub fn parse_color(color: &str) -> Option<Color> {
+    let color = color.trim();
+    if let Some(hex) = color.strip_prefix('#') {
+        return parse_hex(hex);
+    }
+    if let Some(args) = color
+        .strip_prefix("rgba(")
+        .or_else(|| color.strip_prefix("rgb("))
+    {
+        return parse_rgb(args.strip_suffix(')')?);
+    }
+    NAMED_COLORS
+        .iter()
+        .find(|(name, _)| *name == color)
+        .map(|(_, color)| *color)
+}
@@ -0,0 +1,120 @@
+use std::{any::Any, cell::RefCell, rc::Rc};
+
+use react_reconciler::{react_value::Value, HostConfig};
+use shared::warn;
+
+use crate::scene_node::{SceneNode, SceneNodeKind};
+
+pub struct CanvasHostConfig;
+
+impl HostConfig for CanvasHostConfig {
+    fn create_text_instance(&self, content: String) -> Rc<dyn Any> {
+        Rc::new(RefCell::new(SceneNode::new(SceneNodeKind::RawText(
+            content,
+        ))))
+    }
+
+    fn create_instance(&self, _type: String, _host_context: &str) -> Rc<dyn Any> {
+        let node = SceneNode::from_type(&_type).unwrap_or_else(|| {
+            warn!(
+                Host,
+                "unknown canvas element <{}>, rendering it as <group>", _type
+            );
+            SceneNode::from_type("group").unwrap()
+        });
+        Rc::new(RefCell::new(node))
+    }
+
+    fn get_root_host_context(&self, _container: Rc<dyn Any>) -> String {
+        "".to_string()
+    }
+
+    fn get_child_host_context(&self, parent_host_context: &str, _type: &str) -> String {
+        parent_host_context.to_string()
+    }
+
+    // scene nodes are not exposed to js
+    fn get_public_instance(&self, _instance: Rc<dyn Any>) -> Value {
+        Value::null()
+    }
+
+    fn get_portal_container(&self, _container_info: &Value) -> Rc<dyn Any> {
+        panic!("react-canvas does not support portals")
+    }
+
+    fn prepare_portal_mount(&self, _portal_container: Rc<dyn Any>, _parent_container: Rc<dyn Any>) {
+    }
+
+    fn append_initial_child(&self, parent: Rc<dyn Any>, child: Rc<dyn Any>) {
+        let parent = SceneNode::from_instance(&parent);
+        let child = SceneNode::from_instance(&child);
+        parent.borrow_mut().append_child(child);
+    }
+
+    fn finalize_initial_children(&self, instance: Rc<dyn Any>, _type: String, props: &Value) {
+        SceneNode::from_instance(&instance)
+            .borrow_mut()
+            .apply_props(&props.to_js());
+    }
+
+    fn commit_update(
+        &self,
+        instance: Rc<dyn Any>,
+        _type: String,
+        _old_props: &Value,
+        new_props: &Value,
+    ) {
+        SceneNode::from_instance(&instance)
+            .borrow_mut()
+            .apply_props(&new_props.to_js());
+    }
+
+    fn append_child_to_container(&self, child: Rc<dyn Any>, parent: Rc<dyn Any>) {
+        self.append_initial_child(parent, child)
+    }
+
+    fn insert_child_to_container(
+        &self,
+        child: Rc<dyn Any>,
+        container: Rc<dyn Any>,
+        before: Rc<dyn Any>,
+    ) {
+        let parent = SceneNode::from_instance(&container);
+        let child = SceneNode::from_instance(&child);
+        let before = SceneNode::from_instance(&before);
+        parent.borrow_mut().insert_before(child, &before);
+    }
+
+    fn remove_child(&self, child: Rc<dyn Any>, container: Rc<dyn Any>) {
+        let parent = SceneNode::from_instance(&container);
+        let child = SceneNode::from_instance(&child);
+        parent.borrow_mut().remove_child(&child);
+    }
+
+    // the scene is rasterized when its pixels are asked for, see Renderer
+    fn reset_after_commit(&self, _container: Rc<dyn Any>) {}
+
+    fn reset_text_content(&self, _instance: Rc<dyn Any>) {}
+
+    fn commit_text_update(&self, text_instance: Rc<dyn Any>, content: String) {
+        let text_instance = SceneNode::from_instance(&text_instance);
+        text_instance.borrow_mut().kind = SceneNodeKind::RawText(content);
+    }
+
+    fn hide_instance(&self, instance: Rc<dyn Any>) {
+        SceneNode::from_instance(&instance).borrow_mut().hidden = true;
+    }
+
+    fn unhide_instance(&self, instance: Rc<dyn Any>) {
+        SceneNode::from_instance(&instance).borrow_mut().hidden = false;
+    }
+
+    fn hide_text_instance(&self, text_instance: Rc<dyn Any>) {
+        self.hide_instance(text_instance);
+    }
+
+    fn unhide_text_instance(&self, text_instance: Rc<dyn Any>, content: String) {
+        self.commit_text_update(text_instance.clone(), content);
+        self.unhide_instance(text_instance);
+    }
+}
@@ -0,0 +1,43 @@
+use std::{cell::RefCell, rc::Rc};
+
+use color::parse_color;
+use host_config::CanvasHostConfig;
+use react_reconciler::Reconciler;
+use renderer::Renderer;
+use scene_node::{SceneNode, SceneNodeKind};
+use shared::derive_from_js_value;
+use wasm_bindgen::prelude::*;
+
+mod color;
+mod font;
+mod host_config;
+mod png;
+mod raster;
+mod renderer;
+mod scene_node;
+mod transform;
+
+// options is { width, height, background }, background is a css color and transparent by default
+#[wasm_bindgen(js_name = createRoot)]
+pub fn create_root(options: &JsValue) -> Renderer {
+    set_panic_hook();
+    let width = derive_from_js_value(options, "width")
+        .as_f64()
+        .unwrap_or(300.0) as u32;
+    let height = derive_from_js_value(options, "height")
+        .as_f64()
+        .unwrap_or(150.0) as u32;
+    let background = derive_from_js_value(options, "background")
+        .as_string()
+        .and_then(|color| parse_color(&color));
+    let container = Rc::new(RefCell::new(SceneNode::new(SceneNodeKind::Root {
+        width,
+        height,
+        background,
+    })));
+    let reconciler = Reconciler::new(Rc::new(CanvasHostConfig));
+    let root = reconciler.create_container(container.clone());
+    Renderer::new(root, reconciler, container)
+}
+
+shared::renderer_exports!(Renderer, react_devtools::connect);
@@ -0,0 +1,164 @@
+// a png encoder without dependencies, the image data is stored uncompressed in the zlib stream
+
+static SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+// the most a stored deflate block can hold
+static MAX_STORED_BLOCK: usize = 65535;
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let mut a = 1u32;
+    let mut b = 0u32;
+    for byte in bytes {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // deflate, 32k window, no preset dictionary
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[0x01, 0x00, 0x00, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        out.push(if is_final { 0x01 } else { 0x00 });
+        let len = block.len() as u16;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+// rgba is width * height * 4 bytes of non-premultiplied rgba, row by row
+pub fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    let mut out = SIGNATURE.to_vec();
+
+    let mut header = vec![];
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bit depth, truecolor with alpha, deflate, adaptive filtering, no interlace
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+    write_chunk(&mut out, b"IHDR", &header);
+
+    // every scanline starts with its filter type, 0 is none
+    let stride = width as usize * 4;
+    let mut scanlines = Vec::with_capacity((stride + 1) * height as usize);
+    for row in rgba.chunks(stride.max(1)).take(height as usize) {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+    write_chunk(&mut out, b"IDAT", &zlib_stored(&scanlines));
+    write_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the chunks of a png as (kind, data), after checking the signature and every crc
+    fn read_chunks(png: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+        assert_eq!(png[..8], SIGNATURE);
+        let mut chunks = vec![];
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let kind: [u8; 4] = rest[4..8].try_into().unwrap();
+            let data = rest[8..8 + len].to_vec();
+            let crc = u32::from_be_bytes(rest[8 + len..12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32(&rest[4..8 + len]));
+            chunks.push((kind, data));
+            rest = &rest[12 + len..];
+        }
+        chunks
+    }
+
+    // the data of a zlib stream made of stored blocks, after checking its adler32
+    fn inflate_stored(zlib: &[u8]) -> (Vec<u8>, usize) {
+        assert_eq!(zlib[..2], [0x78, 0x01]);
+        let mut data = vec![];
+        let mut blocks = 0;
+        let mut rest = &zlib[2..];
+        loop {
+            let is_final = rest[0] == 0x01;
+            let len = u16::from_le_bytes([rest[1], rest[2]]);
+            let nlen = u16::from_le_bytes([rest[3], rest[4]]);
+            assert_eq!(nlen, !len);
+            data.extend_from_slice(&rest[5..5 + len as usize]);
+            rest = &rest[5 + len as usize..];
+            blocks += 1;
+            if is_final {
+                break;
+            }
+        }
+        assert_eq!(rest, adler32(&data).to_be_bytes());
+        (data, blocks)
+    }
+
+    #[test]
+    fn checksums_match_known_values() {
+        assert_eq!(crc32(b"IEND"), 0xae426082);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+
+    #[test]
+    fn encodes_an_empty_image() {
+        let chunks = read_chunks(&encode_png(0, 0, &[]));
+        let kinds: Vec<&[u8; 4]> = chunks.iter().map(|(kind, _)| kind).collect();
+        assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0, 0, 0, 0, 0, 0, 8, 6, 0, 0, 0]);
+        // a single empty final block
+        assert_eq!(
+            chunks[1].1,
+            [0x78, 0x01, 0x01, 0x00, 0x00, 0xff, 0xff, 0, 0, 0, 1]
+        );
+        assert!(chunks[2].1.is_empty());
+    }
+
+    #[test]
+    fn splits_large_images_into_stored_blocks() {
+        let (width, height) = (200u32, 100u32);
+        let rgba: Vec<u8> = (0..width * height * 4).map(|i| (i % 251) as u8).collect();
+        let chunks = read_chunks(&encode_png(width, height, &rgba));
+        assert_eq!(chunks[0].1[..8], [0, 0, 0, 200, 0, 0, 0, 100]);
+
+        let (scanlines, blocks) = inflate_stored(&chunks[1].1);
+        // 100 rows of a filter byte and 800 bytes of pixels
+        assert_eq!(scanlines.len(), 80100);
+        assert_eq!(blocks, 2);
+        for (row, line) in scanlines.chunks(801).enumerate() {
+            assert_eq!(line[0], 0);
+            assert_eq!(line[1..], rgba[row * 800..(row + 1) * 800]);
+        }
+    }
+}
@@ -0,0 +1,469 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::color::Color;
+use crate::font::{glyph_contains, GLYPH_ADVANCE, GLYPH_HEIGHT};
+use crate::png::encode_png;
+use crate::scene_node::{Paint, SceneNode, SceneNodeKind};
+use crate::transform::Transform;
+
+// samples per pixel along each axis, for anti-aliased edges
+static SUPERSAMPLE: usize = 4;
+// a line of text and the space below it, in glyph pixels
+static LINE_HEIGHT: f64 = 9.0;
+
+pub struct PixelBuffer {
+    pub width: u32,
+    pub height: u32,
+    // non-premultiplied rgba, row by row
+    pub data: Vec<u8>,
+}
+
+impl PixelBuffer {
+    pub fn new(width: u32, height: u32, background: Option<Color>) -> Self {
+        let background = background.unwrap_or(Color::TRANSPARENT);
+        let pixel = [
+            background.r,
+            background.g,
+            background.b,
+            (background.a * 255.0).round() as u8,
+        ];
+        PixelBuffer {
+            width,
+            height,
+            data: pixel.repeat((width * height) as usize),
+        }
+    }
+
+    // source-over compositing of color, covering coverage (0.0 - 1.0) of the pixel
+    fn blend(&mut self, x: u32, y: u32, color: &Color, coverage: f64) {
+        let alpha = color.a * coverage;
+        if alpha <= 0.0 {
+            return;
+        }
+        let index = ((y * self.width + x) * 4) as usize;
+        let pixel = &mut self.data[index..index + 4];
+        let dst_alpha = pixel[3] as f64 / 255.0;
+        let out_alpha = alpha + dst_alpha * (1.0 - alpha);
+        let mix = |src: u8, dst: u8| {
+            ((src as f64 * alpha + dst as f64 * dst_alpha * (1.0 - alpha)) / out_alpha).round()
+                as u8
+        };
+        pixel[0] = mix(color.r, pixel[0]);
+        pixel[1] = mix(color.g, pixel[1]);
+        pixel[2] = mix(color.b, pixel[2]);
+        pixel[3] = (out_alpha * 255.0).round() as u8;
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        encode_png(self.width, self.height, &self.data)
+    }
+}
+
+enum Shape<'a> {
+    Rect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+    Circle {
+        cx: f64,
+        cy: f64,
+        r: f64,
+    },
+    Line {
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    },
+    Text {
+        x: f64,
+        y: f64,
+        scale: f64,
+        lines: Vec<&'a str>,
+    },
+}
+
+impl<'a> Shape<'a> {
+    // in local coordinates, large enough for the stroke
+    fn bounds(&self, stroke_width: f64) -> (f64, f64, f64, f64) {
+        let half = stroke_width / 2.0;
+        match self {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+            } => (x - half, y - half, x + width + half, y + height + half),
+            Shape::Circle { cx, cy, r } => {
+                (cx - r - half, cy - r - half, cx + r + half, cy + r + half)
+            }
+            Shape::Line { x1, y1, x2, y2 } => (
+                x1.min(*x2) - half,
+                y1.min(*y2) - half,
+                x1.max(*x2) + half,
+                y1.max(*y2) + half,
+            ),
+            Shape::Text { x, y, scale, lines } => {
+                let columns = lines
+                    .iter()
+                    .map(|line| line.chars().count())
+                    .max()
+                    .unwrap_or(0);
+                (
+                    *x,
+                    *y,
+                    x + columns as f64 * GLYPH_ADVANCE * scale,
+                    y + lines.len() as f64 * LINE_HEIGHT * scale,
+                )
+            }
+        }
+    }
+
+    fn fill_contains(&self, px: f64, py: f64) -> bool {
+        match self {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+            } => px >= *x && py >= *y && px < x + width && py < y + height,
+            Shape::Circle { cx, cy, r } => (px - cx).powi(2) + (py - cy).powi(2) <= r * r,
+            Shape::Line { .. } => false,
+            Shape::Text { x, y, scale, lines } => {
+                let gx = (px - x) / scale;
+                let gy = (py - y) / scale;
+                if gx < 0.0 || gy < 0.0 {
+                    return false;
+                }
+                let line = match lines.get((gy / LINE_HEIGHT) as usize) {
+                    Some(line) => line,
+                    None => return false,
+                };
+                match line.chars().nth((gx / GLYPH_ADVANCE) as usize) {
+                    Some(ch) => glyph_contains(ch, gx % GLYPH_ADVANCE, gy % LINE_HEIGHT),
+                    None => false,
+                }
+            }
+        }
+    }
+
+    // strokes are centered on the outline
+    fn stroke_contains(&self, px: f64, py: f64, stroke_width: f64) -> bool {
+        let half = stroke_width / 2.0;
+        match self {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                let outer = px >= x - half
+                    && py >= y - half
+                    && px < x + width + half
+                    && py < y + height + half;
+                let inner = px >= x + half
+                    && py >= y + half
+                    && px < x + width - half
+                    && py < y + height - half;
+                outer && !inner
+            }
+            Shape::Circle { cx, cy, r } => {
+                let distance = ((px - cx).powi(2) + (py - cy).powi(2)).sqrt();
+                (distance - r).abs() <= half
+            }
+            Shape::Line { x1, y1, x2, y2 } => {
+                let (dx, dy) = (x2 - x1, y2 - y1);
+                let length_squared = dx * dx + dy * dy;
+                let t = if length_squared == 0.0 {
+                    0.0
+                } else {
+                    (((px - x1) * dx + (py - y1) * dy) / length_squared).clamp(0.0, 1.0)
+                };
+                let (nx, ny) = (x1 + t * dx, y1 + t * dy);
+                (px - nx).powi(2) + (py - ny).powi(2) <= half * half
+            }
+            Shape::Text { .. } => false,
+        }
+    }
+}
+
+fn rasterize_shape(
+    buffer: &mut PixelBuffer,
+    shape: &Shape,
+    paint: &Paint,
+    transform: &Transform,
+    opacity: f64,
+) {
+    let inverse = match transform.invert() {
+        Some(inverse) => inverse,
+        // scaled down to nothing
+        None => return,
+    };
+    let stroke_width = if paint.stroke.is_some() {
+        paint.stroke_width
+    } else {
+        0.0
+    };
+
+    // the pixels the transformed bounds cover
+    let (x0, y0, x1, y1) = shape.bounds(stroke_width);
+    let corners = [
+        transform.apply(x0, y0),
+        transform.apply(x1, y0),
+        transform.apply(x0, y1),
+        transform.apply(x1, y1),
+    ];
+    let min_x = corners.iter().map(|c| c.0).fold(f64::INFINITY, f64::min);
+    let max_x = corners
+        .iter()
+        .map(|c| c.0)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let min_y = corners.iter().map(|c| c.1).fold(f64::INFINITY, f64::min);
+    let max_y = corners
+        .iter()
+        .map(|c| c.1)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let start_x = min_x.floor().max(0.0) as u32;
+    let start_y = min_y.floor().max(0.0) as u32;
+    let end_x = (max_x.ceil().max(0.0) as u32).min(buffer.width);
+    let end_y = (max_y.ceil().max(0.0) as u32).min(buffer.height);
+
+    let fill = paint
+        .fill
+        .map(|color| color.with_alpha(paint.opacity * opacity));
+    let stroke = paint
+        .stroke
+        .map(|color| color.with_alpha(paint.opacity * opacity));
+    let samples = (SUPERSAMPLE * SUPERSAMPLE) as f64;
+
+    for y in start_y..end_y {
+        for x in start_x..end_x {
+            let mut fill_hits = 0;
+            let mut stroke_hits = 0;
+            for sy in 0..SUPERSAMPLE {
+                for sx in 0..SUPERSAMPLE {
+                    let dx = x as f64 + (sx as f64 + 0.5) / SUPERSAMPLE as f64;
+                    let dy = y as f64 + (sy as f64 + 0.5) / SUPERSAMPLE as f64;
+                    let (lx, ly) = inverse.apply(dx, dy);
+                    if fill.is_some() && shape.fill_contains(lx, ly) {
+                        fill_hits += 1;
+                    }
+                    if stroke.is_some() && shape.stroke_contains(lx, ly, stroke_width) {
+                        stroke_hits += 1;
+                    }
+                }
+            }
+            if let Some(fill) = &fill {
+                buffer.blend(x, y, fill, fill_hits as f64 / samples);
+            }
+            if let Some(stroke) = &stroke {
+                buffer.blend(x, y, stroke, stroke_hits as f64 / samples);
+            }
+        }
+    }
+}
+
+fn rasterize_node(buffer: &mut PixelBuffer, node: &SceneNode, transform: &Transform, opacity: f64) {
+    if node.hidden {
+        return;
+    }
+    match &node.kind {
+        SceneNodeKind::Root { .. } => {
+            for child in node.children.iter() {
+                rasterize_node(buffer, &child.borrow(), transform, opacity);
+            }
+        }
+        SceneNodeKind::Group {
+            transform: own,
+            opacity: own_opacity,
+        } => {
+            let transform = transform.multiply(own);
+            for child in node.children.iter() {
+                rasterize_node(buffer, &child.borrow(), &transform, opacity * own_opacity);
+            }
+        }
+        SceneNodeKind::Rect {
+            x,
+            y,
+            width,
+            height,
+            paint,
+        } => {
+            let shape = Shape::Rect {
+                x: *x,
+                y: *y,
+                width: *width,
+                height: *height,
+            };
+            rasterize_shape(buffer, &shape, paint, transform, opacity);
+        }
+        SceneNodeKind::Circle { cx, cy, r, paint } => {
+            let shape = Shape::Circle {
+                cx: *cx,
+                cy: *cy,
+                r: *r,
+            };
+            rasterize_shape(buffer, &shape, paint, transform, opacity);
+        }
+        SceneNodeKind::Line {
+            x1,
+            y1,
+            x2,
+            y2,
+            paint,
+        } => {
+            let shape = Shape::Line {
+                x1: *x1,
+                y1: *y1,
+                x2: *x2,
+                y2: *y2,
+            };
+            rasterize_shape(buffer, &shape, paint, transform, opacity);
+        }
+        SceneNodeKind::Text {
+            x,
+            y,
+            font_size,
+            paint,
+        } => {
+            let content = node.text_content();
+            let shape = Shape::Text {
+                x: *x,
+                y: *y,
+                scale: font_size / GLYPH_HEIGHT,
+                lines: content.split('\n').collect(),
+            };
+            // glyphs are filled, a stroke would outline every glyph pixel
+            let paint = Paint {
+                stroke: None,
+                ..paint.clone()
+            };
+            rasterize_shape(buffer, &shape, &paint, transform, opacity);
+        }
+        // text outside of <text> is not drawn
+        SceneNodeKind::RawText(_) => {}
+    }
+}
+
+// draws the scene of a root into a new buffer the size of the root
+pub fn rasterize(root: &Rc<RefCell<SceneNode>>) -> PixelBuffer {
+    let root = root.borrow();
+    let mut buffer = match &root.kind {
+        SceneNodeKind::Root {
+            width,
+            height,
+            background,
+        } => PixelBuffer::new(*width, *height, *background),
+        _ => panic!("rasterize expects a root"),
+    };
+    rasterize_node(&mut buffer, &root, &Transform::IDENTITY, 1.0);
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(kind: SceneNodeKind) -> Rc<RefCell<SceneNode>> {
+        Rc::new(RefCell::new(SceneNode::new(kind)))
+    }
+
+    fn filled(color: Color) -> Paint {
+        Paint {
+            fill: Some(color),
+            ..Paint::default()
+        }
+    }
+
+    fn pixel(buffer: &PixelBuffer, x: u32, y: u32) -> [u8; 4] {
+        let index = ((y * buffer.width + x) * 4) as usize;
+        buffer.data[index..index + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn rasterizes_a_small_scene() {
+        let white = Color::rgba(255, 255, 255, 1.0);
+        let red = Color::rgba(255, 0, 0, 1.0);
+        let blue = Color::rgba(0, 0, 255, 1.0);
+        let root = shape(SceneNodeKind::Root {
+            width: 4,
+            height: 3,
+            background: Some(white),
+        });
+        root.borrow_mut().children = vec![
+            shape(SceneNodeKind::Rect {
+                x: 0.0,
+                y: 0.0,
+                width: 2.0,
+                height: 2.0,
+                paint: filled(red),
+            }),
+            // covers half of the pixels (2, 0) and (3, 0)
+            shape(SceneNodeKind::Rect {
+                x: 2.0,
+                y: 0.5,
+                width: 2.0,
+                height: 0.5,
+                paint: filled(blue),
+            }),
+            // a group at half opacity, with a rect over pixel (3, 2)
+            {
+                let group = shape(SceneNodeKind::Group {
+                    transform: Transform::translate(3.0, 2.0),
+                    opacity: 0.5,
+                });
+                group.borrow_mut().children = vec![shape(SceneNodeKind::Rect {
+                    x: 0.0,
+                    y: 0.0,
+                    width: 1.0,
+                    height: 1.0,
+                    paint: filled(red),
+                })];
+                group
+            },
+        ];
+
+        let buffer = rasterize(&root);
+        let white = [255, 255, 255, 255];
+        let red = [255, 0, 0, 255];
+        let light_blue = [128, 128, 255, 255];
+        let light_red = [255, 128, 128, 255];
+        let expected = [
+            [red, red, light_blue, light_blue],
+            [red, red, white, white],
+            [white, white, white, light_red],
+        ];
+        for (y, row) in expected.iter().enumerate() {
+            for (x, color) in row.iter().enumerate() {
+                assert_eq!(
+                    pixel(&buffer, x as u32, y as u32),
+                    *color,
+                    "pixel {} {}",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn leaves_hidden_nodes_out() {
+        let root = shape(SceneNodeKind::Root {
+            width: 1,
+            height: 1,
+            background: None,
+        });
+        let rect = shape(SceneNodeKind::Rect {
+            x: 0.0,
+            y: 0.0,
+            width: 1.0,
+            height: 1.0,
+            paint: filled(Color::rgba(0, 0, 0, 1.0)),
+        });
+        rect.borrow_mut().hidden = true;
+        root.borrow_mut().children = vec![rect];
+        assert_eq!(rasterize(&root).data, [0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,55 @@
+use std::{cell::RefCell, rc::Rc};
+
+use react_reconciler::{fiber::FiberRootNode, react_value::Value, Reconciler};
+use wasm_bindgen::prelude::*;
+
+use crate::raster::rasterize;
+use crate::scene_node::SceneNode;
+
+#[wasm_bindgen]
+pub struct Renderer {
+    // getSnapshot of renderer_exports! reads it
+    pub(crate) root: Rc<RefCell<FiberRootNode>>,
+    reconciler: Reconciler,
+    container: Rc<RefCell<SceneNode>>,
+}
+
+impl Renderer {
+    pub fn new(
+        root: Rc<RefCell<FiberRootNode>>,
+        reconciler: Reconciler,
+        container: Rc<RefCell<SceneNode>>,
+    ) -> Self {
+        Self {
+            root,
+            reconciler,
+            container,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Renderer {
+    pub fn render(&self, element: &JsValue) -> JsValue {
+        self.reconciler
+            .update_container(element.into(), self.root.clone())
+            .to_js()
+    }
+
+    pub fn unmount(&self) {
+        self.reconciler
+            .update_container(Value::null(), self.root.clone());
+    }
+
+    // the committed scene as rgba bytes, row by row
+    #[wasm_bindgen(js_name = getPixels)]
+    pub fn get_pixels(&self) -> Vec<u8> {
+        rasterize(&self.container).data
+    }
+
+    // the committed scene encoded as a png file
+    #[wasm_bindgen(js_name = toPNG)]
+    pub fn to_png(&self) -> Vec<u8> {
+        rasterize(&self.container).to_png()
+    }
+}
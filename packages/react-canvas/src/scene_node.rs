@@ -0,0 +1,245 @@
+use std::{any::Any, cell::RefCell, rc::Rc};
+
+use shared::derive_from_js_value;
+use wasm_bindgen::JsValue;
+
+use crate::color::{parse_color, Color};
+use crate::transform::Transform;
+
+// how a shape is filled and outlined
+#[derive(Clone, Debug, PartialEq)]
+pub struct Paint {
+    pub fill: Option<Color>,
+    pub stroke: Option<Color>,
+    pub stroke_width: f64,
+    pub opacity: f64,
+}
+
+impl Default for Paint {
+    fn default() -> Self {
+        Paint {
+            fill: None,
+            stroke: None,
+            stroke_width: 1.0,
+            opacity: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneNodeKind {
+    Root {
+        width: u32,
+        height: u32,
+        background: Option<Color>,
+    },
+    // <group x y rotation scale opacity>, transforms and fades its children
+    Group {
+        transform: Transform,
+        opacity: f64,
+    },
+    // <rect x y width height>
+    Rect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        paint: Paint,
+    },
+    // <circle cx cy r>
+    Circle {
+        cx: f64,
+        cy: f64,
+        r: f64,
+        paint: Paint,
+    },
+    // <line x1 y1 x2 y2>, only stroked
+    Line {
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+        paint: Paint,
+    },
+    // <text x y fontSize>, x and y are its top left corner
+    Text {
+        x: f64,
+        y: f64,
+        font_size: f64,
+        paint: Paint,
+    },
+    RawText(String),
+}
+
+pub struct SceneNode {
+    pub kind: SceneNodeKind,
+    pub hidden: bool,
+    pub children: Vec<Rc<RefCell<SceneNode>>>,
+}
+
+fn get_number(props: &JsValue, name: &str, default: f64) -> f64 {
+    derive_from_js_value(props, name)
+        .as_f64()
+        .unwrap_or(default)
+}
+
+fn get_color(props: &JsValue, name: &str) -> Option<Color> {
+    derive_from_js_value(props, name)
+        .as_string()
+        .and_then(|color| parse_color(&color))
+}
+
+fn get_paint(props: &JsValue, default_fill: Option<Color>) -> Paint {
+    let fill = match derive_from_js_value(props, "fill").is_undefined() {
+        true => default_fill,
+        false => get_color(props, "fill"),
+    };
+    Paint {
+        fill,
+        stroke: get_color(props, "stroke"),
+        stroke_width: get_number(props, "strokeWidth", 1.0),
+        opacity: get_number(props, "opacity", 1.0),
+    }
+}
+
+// x and y move the group, then it is rotated (in degrees) and scaled around its origin
+fn get_transform(props: &JsValue) -> Transform {
+    let scale = get_number(props, "scale", 1.0);
+    Transform::translate(get_number(props, "x", 0.0), get_number(props, "y", 0.0))
+        .multiply(&Transform::rotate(get_number(props, "rotation", 0.0)))
+        .multiply(&Transform::scale(
+            get_number(props, "scaleX", scale),
+            get_number(props, "scaleY", scale),
+        ))
+}
+
+impl SceneNode {
+    pub fn new(kind: SceneNodeKind) -> Self {
+        SceneNode {
+            kind,
+            hidden: false,
+            children: vec![],
+        }
+    }
+
+    // a node for a host type, its geometry is filled in by apply_props
+    pub fn from_type(_type: &str) -> Option<Self> {
+        let paint = Paint::default();
+        let kind = match _type {
+            "group" => SceneNodeKind::Group {
+                transform: Transform::IDENTITY,
+                opacity: 1.0,
+            },
+            "rect" => SceneNodeKind::Rect {
+                x: 0.0,
+                y: 0.0,
+                width: 0.0,
+                height: 0.0,
+                paint,
+            },
+            "circle" => SceneNodeKind::Circle {
+                cx: 0.0,
+                cy: 0.0,
+                r: 0.0,
+                paint,
+            },
+            "line" => SceneNodeKind::Line {
+                x1: 0.0,
+                y1: 0.0,
+                x2: 0.0,
+                y2: 0.0,
+                paint,
+            },
+            "text" => SceneNodeKind::Text {
+                x: 0.0,
+                y: 0.0,
+                font_size: 7.0,
+                paint,
+            },
+            _ => return None,
+        };
+        Some(SceneNode::new(kind))
+    }
+
+    pub fn from_instance(instance: &Rc<dyn Any>) -> Rc<RefCell<SceneNode>> {
+        instance
+            .clone()
+            .downcast::<RefCell<SceneNode>>()
+            .expect("instance should be SceneNode")
+    }
+
+    // props are read once here, rasterizing only deals with rust values
+    pub fn apply_props(&mut self, props: &JsValue) {
+        let black = Some(Color::rgba(0, 0, 0, 1.0));
+        self.kind = match &self.kind {
+            SceneNodeKind::Group { .. } => SceneNodeKind::Group {
+                transform: get_transform(props),
+                opacity: get_number(props, "opacity", 1.0),
+            },
+            SceneNodeKind::Rect { .. } => SceneNodeKind::Rect {
+                x: get_number(props, "x", 0.0),
+                y: get_number(props, "y", 0.0),
+                width: get_number(props, "width", 0.0),
+                height: get_number(props, "height", 0.0),
+                paint: get_paint(props, black),
+            },
+            SceneNodeKind::Circle { .. } => SceneNodeKind::Circle {
+                cx: get_number(props, "cx", 0.0),
+                cy: get_number(props, "cy", 0.0),
+                r: get_number(props, "r", 0.0),
+                paint: get_paint(props, black),
+            },
+            SceneNodeKind::Line { .. } => {
+                let mut paint = get_paint(props, None);
+                paint.stroke = paint.stroke.or(black);
+                SceneNodeKind::Line {
+                    x1: get_number(props, "x1", 0.0),
+                    y1: get_number(props, "y1", 0.0),
+                    x2: get_number(props, "x2", 0.0),
+                    y2: get_number(props, "y2", 0.0),
+                    paint,
+                }
+            }
+            SceneNodeKind::Text { .. } => SceneNodeKind::Text {
+                x: get_number(props, "x", 0.0),
+                y: get_number(props, "y", 0.0),
+                font_size: get_number(props, "fontSize", 7.0),
+                paint: get_paint(props, black),
+            },
+            kind => kind.clone(),
+        };
+    }
+
+    pub fn append_child(&mut self, child: Rc<RefCell<SceneNode>>) {
+        self.remove_child(&child);
+        self.children.push(child);
+    }
+
+    pub fn insert_before(
+        &mut self,
+        child: Rc<RefCell<SceneNode>>,
+        before: &Rc<RefCell<SceneNode>>,
+    ) {
+        self.remove_child(&child);
+        match self.children.iter().position(|c| Rc::ptr_eq(c, before)) {
+            Some(index) => self.children.insert(index, child),
+            None => self.children.push(child),
+        }
+    }
+
+    pub fn remove_child(&mut self, child: &Rc<RefCell<SceneNode>>) {
+        self.children.retain(|c| !Rc::ptr_eq(c, child));
+    }
+
+    pub fn text_content(&self) -> String {
+        match &self.kind {
+            SceneNodeKind::RawText(content) => content.clone(),
+            _ => self
+                .children
+                .iter()
+                .filter(|child| !child.borrow().hidden)
+                .map(|child| child.borrow().text_content())
+                .collect(),
+        }
+    }
+}
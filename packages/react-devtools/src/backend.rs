@@ -0,0 +1,232 @@
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use react_reconciler::{
+    fiber::{FiberNode, FiberRootNode},
+    fiber_dev_tools_hook::{
+        get_fiber_roots, inject_commit_root_hook, override_hook_state, remove_commit_root_hook,
+        schedule_update,
+    },
+    fiber_snapshot::{find_fiber_by_id, get_fiber_id, inspect_fiber},
+};
+use shared::derive_from_js_value;
+use wasm_bindgen::prelude::*;
+use web_sys::js_sys::{Array, Object, Reflect, JSON};
+
+use crate::operations::{diff_trees, Operation, Tree};
+use crate::transport::Transport;
+
+// the trees the frontend knows of, one per root
+#[derive(Default)]
+struct BackendState {
+    roots: Vec<(Weak<RefCell<FiberRootNode>>, Tree)>,
+    disconnected: bool,
+}
+
+impl BackendState {
+    // the operations message for what changed in root since the last one
+    fn handle_commit(&mut self, root: &Rc<RefCell<FiberRootNode>>) -> Option<JsValue> {
+        self.roots.retain(|(known, _)| known.strong_count() > 0);
+        let index = match self
+            .roots
+            .iter()
+            .position(|(known, _)| std::ptr::eq(known.as_ptr(), Rc::as_ptr(root)))
+        {
+            Some(index) => index,
+            None => {
+                self.roots.push((Rc::downgrade(root), Tree::default()));
+                self.roots.len() - 1
+            }
+        };
+        let next = Tree::from_root(root, &self.roots[index].1);
+        let operations = diff_trees(&self.roots[index].1, &next);
+        self.roots[index].1 = next;
+        if operations.is_empty() {
+            return None;
+        }
+
+        let root_id = get_fiber_id(&root.borrow().current);
+        let message = create_message("operations");
+        set(&message, "rootId", &JsValue::from(root_id));
+        set(
+            &message,
+            "operations",
+            &operations.iter().map(Operation::to_js).collect::<Array>(),
+        );
+        Some(message.into())
+    }
+}
+
+fn create_message(_type: &str) -> Object {
+    let object = Object::new();
+    set(&object, "type", &JsValue::from_str(_type));
+    object
+}
+
+fn set(object: &Object, key: &str, value: &JsValue) {
+    Reflect::set(object, &key.into(), value).expect("devtools panic");
+}
+
+fn error_message(message: &str) -> JsValue {
+    let object = create_message("error");
+    set(&object, "message", &JsValue::from_str(message));
+    object.into()
+}
+
+fn find_fiber(id: u32) -> Option<Rc<RefCell<FiberNode>>> {
+    get_fiber_roots()
+        .iter()
+        .find_map(|root| find_fiber_by_id(root, id))
+}
+
+fn get_message_id(message: &JsValue, name: &str) -> Option<u32> {
+    derive_from_js_value(message, name)
+        .as_f64()
+        .map(|id| id as u32)
+}
+
+// the connection of the fiber trees to a devtools frontend
+#[wasm_bindgen]
+pub struct DevToolsBackend {
+    state: Rc<RefCell<BackendState>>,
+    transport: Rc<Transport>,
+    hook_id: Option<u32>,
+    // passed to transport.listen, it has to live as long as the backend
+    listener: Option<Closure<dyn Fn(JsValue)>>,
+}
+
+impl DevToolsBackend {
+    pub fn new(transport: &JsValue) -> Result<Self, JsValue> {
+        let state = Rc::new(RefCell::new(BackendState::default()));
+        let transport = Rc::new(Transport::new(transport)?);
+
+        let hook_state = state.clone();
+        let hook_transport = transport.clone();
+        let hook_id = inject_commit_root_hook(Rc::new(move |root| {
+            // the borrow ends before send, the frontend may answer right away
+            let message = hook_state.borrow_mut().handle_commit(&root);
+            if let Some(message) = message {
+                hook_transport.send(&message);
+            }
+        }));
+
+        let mut backend = DevToolsBackend {
+            state,
+            transport,
+            hook_id: Some(hook_id),
+            listener: None,
+        };
+        let listener_state = backend.state.clone();
+        let listener_transport = backend.transport.clone();
+        let listener = Closure::<dyn Fn(JsValue)>::new(move |message: JsValue| {
+            handle_message(&listener_state, &listener_transport, &message);
+        });
+        backend.transport.listen(listener.as_ref().unchecked_ref());
+        backend.listener = Some(listener);
+        backend.flush_initial_operations();
+        Ok(backend)
+    }
+
+    // the whole tree of every root that is already rendered, as add operations
+    fn flush_initial_operations(&self) {
+        flush_initial_operations(&self.state, &self.transport);
+    }
+}
+
+fn flush_initial_operations(state: &Rc<RefCell<BackendState>>, transport: &Transport) {
+    state.borrow_mut().roots.clear();
+    for root in get_fiber_roots() {
+        let message = state.borrow_mut().handle_commit(&root);
+        if let Some(message) = message {
+            transport.send(&message);
+        }
+    }
+}
+
+// a message of the frontend, as a JSON string or an object:
+// inspectElement { id, requestId } answers inspectedElement { id, requestId, element } with
+// the props, hooks (so also the state) and child ids of the fiber, element is null when
+// there is no fiber with id,
+// overrideHookState { id, hookId, value } sets the state of the useState at position hookId,
+// scheduleUpdate { id } renders the fiber again,
+// flushInitialOperations sends the add operations of every root again
+fn handle_message(state: &Rc<RefCell<BackendState>>, transport: &Transport, message: &JsValue) {
+    if state.borrow().disconnected {
+        return;
+    }
+    let message = match message.as_string() {
+        Some(json) => match JSON::parse(&json) {
+            Ok(message) => message,
+            Err(_) => {
+                transport.send(&error_message("message is not valid JSON"));
+                return;
+            }
+        },
+        None => message.clone(),
+    };
+    let _type = derive_from_js_value(&message, "type")
+        .as_string()
+        .unwrap_or_default();
+    let id = get_message_id(&message, "id");
+
+    match _type.as_str() {
+        "inspectElement" => {
+            let response = create_message("inspectedElement");
+            set(&response, "id", &derive_from_js_value(&message, "id"));
+            set(
+                &response,
+                "requestId",
+                &derive_from_js_value(&message, "requestId"),
+            );
+            let element = id
+                .and_then(find_fiber)
+                .map(|fiber| inspect_fiber(&fiber))
+                .unwrap_or(JsValue::null());
+            set(&response, "element", &element);
+            transport.send(&response.into());
+        }
+        "overrideHookState" => {
+            let fiber = match id.and_then(find_fiber) {
+                Some(fiber) => fiber,
+                None => return transport.send(&error_message("no element with this id")),
+            };
+            let hook_id = get_message_id(&message, "hookId").unwrap_or(u32::MAX) as usize;
+            let value = derive_from_js_value(&message, "value");
+            if !override_hook_state(&fiber, hook_id, value.into()) {
+                transport.send(&error_message("hookId is not a state hook"));
+            }
+        }
+        "scheduleUpdate" => match id.and_then(find_fiber) {
+            Some(fiber) => {
+                schedule_update(fiber);
+            }
+            None => transport.send(&error_message("no element with this id")),
+        },
+        "flushInitialOperations" => flush_initial_operations(state, transport),
+        _ => transport.send(&error_message(&format!("unknown message type {}", _type))),
+    }
+}
+
+#[wasm_bindgen]
+impl DevToolsBackend {
+    // for transports without listen
+    pub fn receive(&self, message: &JsValue) {
+        handle_message(&self.state, &self.transport, message);
+    }
+
+    // no more messages are sent or handled after this
+    pub fn disconnect(&mut self) {
+        if let Some(hook_id) = self.hook_id.take() {
+            remove_commit_root_hook(hook_id);
+        }
+        self.state.borrow_mut().disconnected = true;
+    }
+}
+
+impl Drop for DevToolsBackend {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
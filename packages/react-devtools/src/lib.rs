@@ -0,0 +1,15 @@
+use wasm_bindgen::prelude::*;
+
+pub use backend::DevToolsBackend;
+
+mod backend;
+mod operations;
+mod transport;
+
+// starts sending the fiber trees of every root to a devtools frontend, transport is
+// { send, listen }, see Transport. The add operations of the roots that are already
+// rendered are sent right away, then the operations of every commit.
+// Renderers export this as connectDevTools, it throws a TypeError for a transport without send
+pub fn connect(transport: &JsValue) -> Result<DevToolsBackend, JsValue> {
+    DevToolsBackend::new(transport)
+}
@@ -0,0 +1,351 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+
+use react_reconciler::{
+    fiber::{FiberNode, FiberRootNode},
+    fiber_snapshot::{get_fiber_id, inspect_fiber},
+};
+use shared::derive_from_js_value;
+use wasm_bindgen::prelude::*;
+use web_sys::js_sys::{Array, Object, Reflect, JSON};
+
+// what the frontend was last told about a fiber
+#[derive(Clone)]
+pub struct NodeRecord {
+    pub parent_id: Option<u32>,
+    pub children: Vec<u32>,
+    pub tag: String,
+    pub display_name: Option<String>,
+    pub key: Option<String>,
+    // props and hooks as JSON, to find the fibers that changed
+    pub props: String,
+    pub hooks: String,
+    // the first child fiber, while it is the same the children were not rendered again
+    pub child: Option<Weak<RefCell<FiberNode>>>,
+}
+
+// the committed tree of a root, flattened
+#[derive(Default)]
+pub struct Tree {
+    // parents before their children
+    pub order: Vec<u32>,
+    pub nodes: HashMap<u32, NodeRecord>,
+}
+
+fn stringify(value: &JsValue) -> String {
+    JSON::stringify(value)
+        .ok()
+        .and_then(|json| json.as_string())
+        .unwrap_or_default()
+}
+
+impl Tree {
+    // the committed tree of root, the subtrees that did not render since prev are copied from it
+    // rather than serialized again
+    pub fn from_root(root: &Rc<RefCell<FiberRootNode>>, prev: &Tree) -> Self {
+        let mut tree = Tree::default();
+        let current = root.borrow().current.clone();
+        tree.add(&current, None, prev);
+        tree
+    }
+
+    fn add(&mut self, fiber: &Rc<RefCell<FiberNode>>, parent_id: Option<u32>, prev: &Tree) {
+        let id = get_fiber_id(fiber);
+        let node = inspect_fiber(fiber);
+        let child = fiber.borrow().child.clone();
+        let children = derive_from_js_value(&node, "children")
+            .dyn_into::<Array>()
+            .unwrap_or_default()
+            .iter()
+            .map(|child| child.as_f64().unwrap_or(0.0) as u32)
+            .collect::<Vec<u32>>();
+        self.order.push(id);
+        self.nodes.insert(
+            id,
+            NodeRecord {
+                parent_id,
+                children: children.clone(),
+                tag: derive_from_js_value(&node, "tag")
+                    .as_string()
+                    .unwrap_or_default(),
+                display_name: derive_from_js_value(&node, "type").as_string(),
+                key: derive_from_js_value(&node, "key").as_string(),
+                props: stringify(&derive_from_js_value(&node, "props")),
+                hooks: stringify(&derive_from_js_value(&node, "hooks")),
+                child: child.as_ref().map(Rc::downgrade),
+            },
+        );
+
+        // a bailed out fiber keeps the child fibers of the last commit
+        let did_bail_out = match (prev.nodes.get(&id), &child) {
+            (Some(prev_node), Some(child)) => prev_node
+                .child
+                .as_ref()
+                .and_then(Weak::upgrade)
+                .is_some_and(|prev_child| Rc::ptr_eq(&prev_child, child)),
+            _ => false,
+        };
+        if did_bail_out {
+            for child_id in children {
+                self.copy(child_id, prev);
+            }
+            return;
+        }
+        let mut next = child;
+        while let Some(child) = next {
+            self.add(&child, Some(id), prev);
+            next = child.borrow().sibling.clone();
+        }
+    }
+
+    fn copy(&mut self, id: u32, prev: &Tree) {
+        let node = match prev.nodes.get(&id) {
+            Some(node) => node.clone(),
+            None => return,
+        };
+        self.order.push(id);
+        for child_id in &node.children {
+            self.copy(*child_id, prev);
+        }
+        self.nodes.insert(id, node);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Operation {
+    Add {
+        id: u32,
+        parent_id: Option<u32>,
+        tag: String,
+        display_name: Option<String>,
+        key: Option<String>,
+    },
+    Remove {
+        id: u32,
+    },
+    Reorder {
+        id: u32,
+        children: Vec<u32>,
+    },
+    Update {
+        id: u32,
+    },
+}
+
+fn set(object: &Object, key: &str, value: &JsValue) {
+    Reflect::set(object, &key.into(), value).expect("devtools panic");
+}
+
+fn optional_string(value: &Option<String>) -> JsValue {
+    value
+        .as_deref()
+        .map(JsValue::from_str)
+        .unwrap_or(JsValue::null())
+}
+
+impl Operation {
+    fn add(id: u32, node: &NodeRecord) -> Self {
+        Operation::Add {
+            id,
+            parent_id: node.parent_id,
+            tag: node.tag.clone(),
+            display_name: node.display_name.clone(),
+            key: node.key.clone(),
+        }
+    }
+
+    // { op, id, ... } as the frontend reads it
+    pub fn to_js(&self) -> JsValue {
+        let object = Object::new();
+        let (op, id) = match self {
+            Operation::Add { id, .. } => ("add", id),
+            Operation::Remove { id } => ("remove", id),
+            Operation::Reorder { id, .. } => ("reorder", id),
+            Operation::Update { id } => ("update", id),
+        };
+        set(&object, "op", &JsValue::from_str(op));
+        set(&object, "id", &JsValue::from(*id));
+        match self {
+            Operation::Add {
+                parent_id,
+                tag,
+                display_name,
+                key,
+                ..
+            } => {
+                let parent_id = parent_id.map(JsValue::from).unwrap_or(JsValue::null());
+                set(&object, "parentId", &parent_id);
+                set(&object, "tag", &JsValue::from_str(tag));
+                set(&object, "displayName", &optional_string(display_name));
+                set(&object, "key", &optional_string(key));
+            }
+            Operation::Reorder { children, .. } => {
+                let children = children
+                    .iter()
+                    .map(|child| JsValue::from(*child))
+                    .collect::<Array>();
+                set(&object, "children", &children);
+            }
+            Operation::Remove { .. } | Operation::Update { .. } => {}
+        }
+        object.into()
+    }
+}
+
+// what turns prev into next for the frontend:
+// remove { id }, children before their parents,
+// add { id, parentId, tag, displayName, key }, parents before their children, an added
+// fiber goes after the children its parent already has,
+// reorder { id, children } when that does not give the order of the children of id,
+// update { id } when the props or hooks of id changed
+pub fn diff_trees(prev: &Tree, next: &Tree) -> Vec<Operation> {
+    let mut operations = vec![];
+
+    for id in prev.order.iter().rev() {
+        if !next.nodes.contains_key(id) {
+            operations.push(Operation::Remove { id: *id });
+        }
+    }
+
+    let mut reorders = vec![];
+    for id in next.order.iter() {
+        let node = &next.nodes[id];
+        let prev_node = match prev.nodes.get(id) {
+            Some(prev_node) => prev_node,
+            None => {
+                operations.push(Operation::add(*id, node));
+                continue;
+            }
+        };
+        if prev_node.props != node.props
+            || prev_node.hooks != node.hooks
+            || prev_node.display_name != node.display_name
+        {
+            operations.push(Operation::Update { id: *id });
+        }
+        let expected = prev_node
+            .children
+            .iter()
+            .filter(|child| next.nodes.contains_key(child))
+            .chain(
+                node.children
+                    .iter()
+                    .filter(|child| !prev.nodes.contains_key(child)),
+            )
+            .copied()
+            .collect::<Vec<u32>>();
+        if expected != node.children {
+            reorders.push(Operation::Reorder {
+                id: *id,
+                children: node.children.clone(),
+            });
+        }
+    }
+
+    operations.extend(reorders);
+    operations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (id, parent id, children, props), parents before their children
+    fn tree(nodes: &[(u32, Option<u32>, &[u32], &str)]) -> Tree {
+        let mut tree = Tree::default();
+        for (id, parent_id, children, props) in nodes {
+            tree.order.push(*id);
+            tree.nodes.insert(
+                *id,
+                NodeRecord {
+                    parent_id: *parent_id,
+                    children: children.to_vec(),
+                    tag: "HostComponent".to_string(),
+                    display_name: Some(format!("node{}", id)),
+                    key: None,
+                    props: props.to_string(),
+                    hooks: "[]".to_string(),
+                    child: None,
+                },
+            );
+        }
+        tree
+    }
+
+    fn add(id: u32, parent_id: Option<u32>) -> Operation {
+        Operation::Add {
+            id,
+            parent_id,
+            tag: "HostComponent".to_string(),
+            display_name: Some(format!("node{}", id)),
+            key: None,
+        }
+    }
+
+    #[test]
+    fn adds_a_new_tree_parents_first() {
+        let next = tree(&[
+            (1, None, &[2, 3], "{}"),
+            (2, Some(1), &[], "{}"),
+            (3, Some(1), &[], "{}"),
+        ]);
+        assert_eq!(
+            diff_trees(&Tree::default(), &next),
+            [add(1, None), add(2, Some(1)), add(3, Some(1))]
+        );
+    }
+
+    #[test]
+    fn removes_children_before_their_parents() {
+        let prev = tree(&[
+            (1, None, &[2], "{}"),
+            (2, Some(1), &[3], "{}"),
+            (3, Some(2), &[], "{}"),
+        ]);
+        let next = tree(&[(1, None, &[], "{}")]);
+        assert_eq!(
+            diff_trees(&prev, &next),
+            [Operation::Remove { id: 3 }, Operation::Remove { id: 2 }]
+        );
+    }
+
+    #[test]
+    fn reorders_moved_children_but_not_appended_ones() {
+        let prev = tree(&[
+            (1, None, &[2, 3], "{}"),
+            (2, Some(1), &[], "{}"),
+            (3, Some(1), &[], "{}"),
+        ]);
+        let appended = tree(&[
+            (1, None, &[2, 3, 4], "{}"),
+            (2, Some(1), &[], "{}"),
+            (3, Some(1), &[], "{}"),
+            (4, Some(1), &[], "{}"),
+        ]);
+        assert_eq!(diff_trees(&prev, &appended), [add(4, Some(1))]);
+
+        let moved = tree(&[
+            (1, None, &[3, 2], "{}"),
+            (3, Some(1), &[], "{}"),
+            (2, Some(1), &[], "{}"),
+        ]);
+        assert_eq!(
+            diff_trees(&prev, &moved),
+            [Operation::Reorder {
+                id: 1,
+                children: vec![3, 2],
+            }]
+        );
+    }
+
+    #[test]
+    fn updates_nodes_whose_props_changed() {
+        let prev = tree(&[(1, None, &[2], "{}"), (2, Some(1), &[], "{\"a\":1}")]);
+        let next = tree(&[(1, None, &[2], "{}"), (2, Some(1), &[], "{\"a\":2}")]);
+        assert_eq!(diff_trees(&prev, &next), [Operation::Update { id: 2 }]);
+        assert_eq!(diff_trees(&next, &next), []);
+    }
+}
@@ -0,0 +1,46 @@
+use shared::{derive_from_js_value, error};
+use wasm_bindgen::prelude::*;
+use web_sys::js_sys::{Function, TypeError, JSON};
+
+// how messages get to the frontend, a JS object { send, listen }:
+// send(message) gets every message of the backend as a JSON string,
+// listen(handler), if there is one, is called once with a function for the messages of
+// the frontend, otherwise they are passed to DevToolsBackend.receive
+pub struct Transport {
+    send: Function,
+    listen: Option<Function>,
+}
+
+impl Transport {
+    // a TypeError when send is not a function
+    pub fn new(transport: &JsValue) -> Result<Self, JsValue> {
+        let send = derive_from_js_value(transport, "send")
+            .dyn_into::<Function>()
+            .map_err(|_| TypeError::new("transport.send should be a function"))?;
+        let listen = derive_from_js_value(transport, "listen")
+            .dyn_into::<Function>()
+            .ok();
+        Ok(Transport { send, listen })
+    }
+
+    pub fn listen(&self, handler: &JsValue) {
+        if let Some(listen) = &self.listen {
+            if let Err(error) = listen.call1(&JsValue::null(), handler) {
+                error!(Commit, "transport.listen throws {:?}", error);
+            }
+        }
+    }
+
+    pub fn send(&self, message: &JsValue) {
+        let message = match JSON::stringify(message) {
+            Ok(message) => message,
+            Err(error) => {
+                error!(Commit, "devtools message can not be serialized {:?}", error);
+                return;
+            }
+        };
+        if let Err(error) = self.send.call1(&JsValue::null(), &message) {
+            error!(Commit, "transport.send throws {:?}", error);
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use shared::{derive_from_js_value, warn};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::js_sys::Object;
+use web_sys::Element;
+
+use crate::dom_namespaces::{XLINK_NAMESPACE, XML_NAMESPACE};
+use crate::dom_style::{hyphenate_style_name, set_value_for_styles, update_styles};
+use crate::synthetic_event::update_fiber_props;
+use crate::{dom_input, dom_select, dom_textarea};
+
+pub fn is_nullish(value: &JsValue) -> bool {
+    value.is_null() || value.is_undefined()
+}
+
+pub fn to_string_value(value: &JsValue) -> String {
+    if let Some(value) = value.as_string() {
+        value
+    } else if let Some(value) = value.as_f64() {
+        value.to_string()
+    } else if let Some(value) = value.as_bool() {
+        value.to_string()
+    } else {
+        String::new()
+    }
+}
+
+pub fn get_prop_keys(props: &JsValue) -> Vec<String> {
+    match props.dyn_ref::<Object>() {
+        Some(props) => Object::keys(props)
+            .iter()
+            .filter_map(|key| key.as_string())
+            .collect(),
+        None => vec![],
+    }
+}
+
+pub fn is_event_prop(key: &str) -> bool {
+    key.len() > 2
+        && key.starts_with("on")
+        && key.chars().nth(2).is_some_and(|c| c.is_ascii_uppercase())
+}
+
+fn is_controlled_prop(tag: &str, key: &str) -> bool {
+    // the wrappers of these elements set them as properties, not attributes
+    match tag {
+        "input" => matches!(key, "value" | "defaultValue" | "checked" | "defaultChecked"),
+        "textarea" | "select" => matches!(key, "value" | "defaultValue"),
+        _ => false,
+    }
+}
+
+fn should_skip_prop(tag: &str, key: &str) -> bool {
+    key == "children"
+        || key == "style"
+        || key == "dangerouslySetInnerHTML"
+        || is_event_prop(key)
+        || is_controlled_prop(tag, key)
+}
+
+fn get_inner_html(props: &JsValue) -> JsValue {
+    let inner_html = derive_from_js_value(props, "dangerouslySetInnerHTML");
+    if !inner_html.is_object() {
+        return JsValue::undefined();
+    }
+    derive_from_js_value(&inner_html, "__html")
+}
+
+fn set_inner_html(element: &Element, html: &JsValue) {
+    // removed html is cleared by the reconciler before new children are inserted
+    if !is_nullish(html) {
+        element.set_inner_html(&to_string_value(html));
+    }
+}
+
+// svg presentation attributes are written camelCased in jsx, e.g. strokeWidth for stroke-width
+static SVG_HYPHENATED_ATTRIBUTES: [&str; 71] = [
+    "accentHeight",
+    "alignmentBaseline",
+    "arabicForm",
+    "baselineShift",
+    "capHeight",
+    "clipPath",
+    "clipRule",
+    "colorInterpolation",
+    "colorInterpolationFilters",
+    "colorProfile",
+    "colorRendering",
+    "dominantBaseline",
+    "enableBackground",
+    "fillOpacity",
+    "fillRule",
+    "floodColor",
+    "floodOpacity",
+    "fontFamily",
+    "fontSize",
+    "fontSizeAdjust",
+    "fontStretch",
+    "fontStyle",
+    "fontVariant",
+    "fontWeight",
+    "glyphName",
+    "glyphOrientationHorizontal",
+    "glyphOrientationVertical",
+    "horizAdvX",
+    "horizOriginX",
+    "imageRendering",
+    "letterSpacing",
+    "lightingColor",
+    "markerEnd",
+    "markerMid",
+    "markerStart",
+    "overlinePosition",
+    "overlineThickness",
+    "paintOrder",
+    "pointerEvents",
+    "renderingIntent",
+    "shapeRendering",
+    "stopColor",
+    "stopOpacity",
+    "strikethroughPosition",
+    "strikethroughThickness",
+    "strokeDasharray",
+    "strokeDashoffset",
+    "strokeLinecap",
+    "strokeLinejoin",
+    "strokeMiterlimit",
+    "strokeOpacity",
+    "strokeWidth",
+    "textAnchor",
+    "textDecoration",
+    "textRendering",
+    "underlinePosition",
+    "underlineThickness",
+    "unicodeBidi",
+    "unicodeRange",
+    "unitsPerEm",
+    "vAlphabetic",
+    "vHanging",
+    "vIdeographic",
+    "vMathematical",
+    "vectorEffect",
+    "vertAdvY",
+    "vertOriginX",
+    "vertOriginY",
+    "wordSpacing",
+    "writingMode",
+    "xHeight",
+];
+
+// the namespace and qualified name of the attribute a prop is written to
+fn get_attribute_name(key: &str) -> (Option<&'static str>, String) {
+    match key {
+        "className" => (None, "class".to_string()),
+        "htmlFor" => (None, "for".to_string()),
+        "xlinkActuate" | "xlinkArcrole" | "xlinkHref" | "xlinkRole" | "xlinkShow"
+        | "xlinkTitle" | "xlinkType" => (
+            Some(XLINK_NAMESPACE),
+            format!("xlink:{}", key["xlink".len()..].to_lowercase()),
+        ),
+        "xmlBase" | "xmlLang" | "xmlSpace" => (
+            Some(XML_NAMESPACE),
+            format!("xml:{}", key["xml".len()..].to_lowercase()),
+        ),
+        _ if SVG_HYPHENATED_ATTRIBUTES.contains(&key) => (None, hyphenate_style_name(key)),
+        _ => (None, key.to_string()),
+    }
+}
+
+fn set_attribute(
+    element: &Element,
+    namespace: Option<&str>,
+    name: &str,
+    value: &str,
+) -> Result<(), JsValue> {
+    match namespace {
+        Some(_) => element.set_attribute_ns(namespace, name, value),
+        None => element.set_attribute(name, value),
+    }
+}
+
+fn remove_attribute(element: &Element, namespace: Option<&str>, name: &str) -> Result<(), JsValue> {
+    match namespace {
+        // attributes in a namespace are removed by their name without the prefix
+        Some(_) => element.remove_attribute_ns(namespace, name.split(':').next_back().unwrap()),
+        None => element.remove_attribute(name),
+    }
+}
+
+fn set_value_for_property(element: &Element, key: &str, value: &JsValue) {
+    let (namespace, name) = get_attribute_name(key);
+    let name = name.as_str();
+    let result = if let Some(value) = value.as_bool() {
+        if name.starts_with("aria-") || name.starts_with("data-") {
+            set_attribute(element, namespace, name, &value.to_string())
+        } else if value {
+            set_attribute(element, namespace, name, "")
+        } else {
+            remove_attribute(element, namespace, name)
+        }
+    } else if is_nullish(value) || value.is_function() {
+        remove_attribute(element, namespace, name)
+    } else if value.is_string() || value.as_f64().is_some() {
+        set_attribute(element, namespace, name, &to_string_value(value))
+    } else {
+        warn!(Host, "unsupported value of prop {} {:?}", key, value);
+        Ok(())
+    };
+
+    if let Err(e) = result {
+        warn!(Host, "set prop {} failed {:?}", key, e);
+    }
+}
+
+pub fn set_initial_properties(element: &Element, tag: &str, props: &JsValue) {
+    update_fiber_props(element, props);
+    for key in get_prop_keys(props) {
+        if should_skip_prop(tag, &key) {
+            continue;
+        }
+        set_value_for_property(element, &key, &derive_from_js_value(props, &key));
+    }
+    set_value_for_styles(element, &derive_from_js_value(props, "style"));
+    set_inner_html(element, &get_inner_html(props));
+
+    match tag {
+        "input" => dom_input::post_mount_wrapper(element, props),
+        "textarea" => dom_textarea::post_mount_wrapper(element, props),
+        "select" => dom_select::post_mount_wrapper(element, props),
+        _ => {}
+    }
+}
+
+pub fn update_properties(element: &Element, tag: &str, last_props: &JsValue, next_props: &JsValue) {
+    update_fiber_props(element, next_props);
+    let next_keys = get_prop_keys(next_props);
+    for key in get_prop_keys(last_props) {
+        if should_skip_prop(tag, &key) || next_keys.contains(&key) {
+            continue;
+        }
+        set_value_for_property(element, &key, &JsValue::null());
+    }
+    for key in next_keys {
+        if should_skip_prop(tag, &key) {
+            continue;
+        }
+        let next_value = derive_from_js_value(next_props, &key);
+        if !Object::is(&next_value, &derive_from_js_value(last_props, &key)) {
+            set_value_for_property(element, &key, &next_value);
+        }
+    }
+
+    let last_style = derive_from_js_value(last_props, "style");
+    let next_style = derive_from_js_value(next_props, "style");
+    if !Object::is(&last_style, &next_style) {
+        update_styles(element, &last_style, &next_style);
+    }
+    let next_html = get_inner_html(next_props);
+    if !Object::is(&next_html, &get_inner_html(last_props)) {
+        set_inner_html(element, &next_html);
+    }
+
+    match tag {
+        "input" => dom_input::update_wrapper(element, next_props),
+        "textarea" => dom_textarea::update_wrapper(element, next_props),
+        "select" => dom_select::post_update_wrapper(element, last_props, next_props),
+        _ => {}
+    }
+}
+
+// after an event handler ran, a controlled element shows its value prop again
+pub fn restore_controlled_state(element: &Element, tag: &str, props: &JsValue) {
+    match tag {
+        "input" => dom_input::update_wrapper(element, props),
+        "textarea" => dom_textarea::update_wrapper(element, props),
+        "select" => dom_select::restore_controlled_state(element, props),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_namespaced_and_renamed_attributes() {
+        assert_eq!(get_attribute_name("className"), (None, "class".to_string()));
+        assert_eq!(get_attribute_name("htmlFor"), (None, "for".to_string()));
+        assert_eq!(
+            get_attribute_name("xlinkHref"),
+            (Some(XLINK_NAMESPACE), "xlink:href".to_string())
+        );
+        assert_eq!(
+            get_attribute_name("xmlLang"),
+            (Some(XML_NAMESPACE), "xml:lang".to_string())
+        );
+        assert_eq!(
+            get_attribute_name("strokeWidth"),
+            (None, "stroke-width".to_string())
+        );
+        // only the svg presentation attributes are hyphenated
+        assert_eq!(get_attribute_name("viewBox"), (None, "viewBox".to_string()));
+    }
+}
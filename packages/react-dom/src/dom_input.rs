@@ -0,0 +1,56 @@
+use shared::derive_from_js_value;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+use web_sys::{Element, HtmlInputElement};
+
+use crate::dom_component::{is_nullish, to_string_value};
+
+pub fn post_mount_wrapper(element: &Element, props: &JsValue) {
+    let input = element.dyn_ref::<HtmlInputElement>().unwrap();
+    let value = derive_from_js_value(props, "value");
+    let default_value = derive_from_js_value(props, "defaultValue");
+    if !is_nullish(&value) {
+        // a form reset goes back to the value the input was mounted with
+        let value = to_string_value(&value);
+        input.set_default_value(&value);
+        input.set_value(&value);
+    } else if !is_nullish(&default_value) {
+        input.set_default_value(&to_string_value(&default_value));
+    }
+
+    let checked = derive_from_js_value(props, "checked");
+    let default_checked = derive_from_js_value(props, "defaultChecked");
+    let initial_checked = if !is_nullish(&checked) {
+        Some(checked.is_truthy())
+    } else if !is_nullish(&default_checked) {
+        Some(default_checked.is_truthy())
+    } else {
+        None
+    };
+    if let Some(initial_checked) = initial_checked {
+        input.set_default_checked(initial_checked);
+        input.set_checked(initial_checked);
+    }
+}
+
+pub fn update_wrapper(element: &Element, props: &JsValue) {
+    let input = element.dyn_ref::<HtmlInputElement>().unwrap();
+    let value = derive_from_js_value(props, "value");
+    if !is_nullish(&value) {
+        let value = to_string_value(&value);
+        // writing the same value would move the cursor to the end
+        if input.value() != value {
+            input.set_value(&value);
+        }
+    } else {
+        let default_value = derive_from_js_value(props, "defaultValue");
+        if !is_nullish(&default_value) {
+            input.set_default_value(&to_string_value(&default_value));
+        }
+    }
+
+    let checked = derive_from_js_value(props, "checked");
+    if !is_nullish(&checked) && input.checked() != checked.is_truthy() {
+        input.set_checked(checked.is_truthy());
+    }
+}
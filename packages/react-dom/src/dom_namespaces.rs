@@ -0,0 +1,49 @@
+pub static HTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
+pub static SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+pub static MATH_NAMESPACE: &str = "http://www.w3.org/1998/Math/MathML";
+pub static XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+pub static XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+// the namespace an element of this type has when created in an html parent
+pub fn get_intrinsic_namespace(_type: &str) -> &'static str {
+    match _type {
+        "svg" => SVG_NAMESPACE,
+        "math" => MATH_NAMESPACE,
+        _ => HTML_NAMESPACE,
+    }
+}
+
+pub fn get_child_namespace(parent_namespace: &str, _type: &str) -> String {
+    if parent_namespace.is_empty() || parent_namespace == HTML_NAMESPACE {
+        return get_intrinsic_namespace(_type).to_string();
+    }
+    if parent_namespace == SVG_NAMESPACE && _type == "foreignObject" {
+        return HTML_NAMESPACE.to_string();
+    }
+    parent_namespace.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enters_svg_and_math_from_html() {
+        assert_eq!(get_child_namespace(HTML_NAMESPACE, "svg"), SVG_NAMESPACE);
+        assert_eq!(get_child_namespace(HTML_NAMESPACE, "math"), MATH_NAMESPACE);
+        assert_eq!(get_child_namespace(HTML_NAMESPACE, "div"), HTML_NAMESPACE);
+        // the root container has no namespace yet
+        assert_eq!(get_child_namespace("", "svg"), SVG_NAMESPACE);
+    }
+
+    #[test]
+    fn stays_in_svg_until_foreign_object() {
+        assert_eq!(get_child_namespace(SVG_NAMESPACE, "g"), SVG_NAMESPACE);
+        assert_eq!(get_child_namespace(SVG_NAMESPACE, "div"), SVG_NAMESPACE);
+        assert_eq!(
+            get_child_namespace(SVG_NAMESPACE, "foreignObject"),
+            HTML_NAMESPACE
+        );
+        assert_eq!(get_child_namespace(MATH_NAMESPACE, "mi"), MATH_NAMESPACE);
+    }
+}
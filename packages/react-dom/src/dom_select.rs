@@ -0,0 +1,103 @@
+use shared::derive_from_js_value;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+use web_sys::js_sys::Array;
+use web_sys::{Element, HtmlOptionElement, HtmlSelectElement};
+
+use crate::dom_component::{is_nullish, to_string_value};
+
+fn get_options(select: &HtmlSelectElement) -> Vec<HtmlOptionElement> {
+    (0..select.length())
+        .filter_map(|index| select.item(index))
+        .filter_map(|option| option.dyn_into::<HtmlOptionElement>().ok())
+        .collect()
+}
+
+fn update_options(
+    select: &HtmlSelectElement,
+    multiple: bool,
+    prop_value: &JsValue,
+    set_default_selected: bool,
+) {
+    let options = get_options(select);
+    if multiple {
+        let selected_values: Vec<String> = Array::from(prop_value)
+            .iter()
+            .map(|value| to_string_value(&value))
+            .collect();
+        for option in options {
+            let selected = selected_values.contains(&option.value());
+            if option.selected() != selected {
+                option.set_selected(selected);
+            }
+            if selected && set_default_selected {
+                option.set_default_selected(true);
+            }
+        }
+        return;
+    }
+
+    // without a matching option the first enabled one is selected, like the browser does
+    let selected_value = to_string_value(prop_value);
+    let mut default_selected: Option<HtmlOptionElement> = None;
+    for option in options {
+        if option.value() == selected_value {
+            option.set_selected(true);
+            if set_default_selected {
+                option.set_default_selected(true);
+            }
+            return;
+        }
+        if default_selected.is_none() && !option.disabled() {
+            default_selected = Some(option);
+        }
+    }
+    if let Some(option) = default_selected {
+        option.set_selected(true);
+    }
+}
+
+fn is_multiple(props: &JsValue) -> bool {
+    derive_from_js_value(props, "multiple").is_truthy()
+}
+
+pub fn post_mount_wrapper(element: &Element, props: &JsValue) {
+    let select = element.dyn_ref::<HtmlSelectElement>().unwrap();
+    let multiple = is_multiple(props);
+    select.set_multiple(multiple);
+    let value = derive_from_js_value(props, "value");
+    let default_value = derive_from_js_value(props, "defaultValue");
+    if !is_nullish(&value) {
+        update_options(select, multiple, &value, false);
+    } else if !is_nullish(&default_value) {
+        update_options(select, multiple, &default_value, true);
+    }
+}
+
+pub fn post_update_wrapper(element: &Element, last_props: &JsValue, props: &JsValue) {
+    let select = element.dyn_ref::<HtmlSelectElement>().unwrap();
+    let was_multiple = is_multiple(last_props);
+    let multiple = is_multiple(props);
+    let value = derive_from_js_value(props, "value");
+    if !is_nullish(&value) {
+        update_options(select, multiple, &value, false);
+    } else if was_multiple != multiple {
+        // switching between single and multiple selection resets what is selected
+        let default_value = derive_from_js_value(props, "defaultValue");
+        if !is_nullish(&default_value) {
+            update_options(select, multiple, &default_value, true);
+        } else if multiple {
+            update_options(select, multiple, &Array::new().into(), false);
+        } else {
+            update_options(select, multiple, &JsValue::from_str(""), false);
+        }
+    }
+}
+
+pub fn restore_controlled_state(element: &Element, props: &JsValue) {
+    let select = element.dyn_ref::<HtmlSelectElement>().unwrap();
+    let value = derive_from_js_value(props, "value");
+    if !is_nullish(&value) {
+        update_options(select, is_multiple(props), &value, false);
+    }
+}
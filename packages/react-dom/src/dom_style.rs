@@ -0,0 +1,190 @@
+use shared::warn;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::js_sys::{Object, Reflect};
+use web_sys::{CssStyleDeclaration, Element};
+
+use crate::dom_component::{get_prop_keys, is_nullish, to_string_value};
+
+// numbers for these properties are used as they are, every other one gets px
+static UNITLESS_NUMBERS: [&str; 43] = [
+    "animationIterationCount",
+    "aspectRatio",
+    "borderImageOutset",
+    "borderImageSlice",
+    "borderImageWidth",
+    "boxFlex",
+    "boxFlexGroup",
+    "boxOrdinalGroup",
+    "columnCount",
+    "columns",
+    "flex",
+    "flexGrow",
+    "flexPositive",
+    "flexShrink",
+    "flexNegative",
+    "flexOrder",
+    "gridArea",
+    "gridRow",
+    "gridRowEnd",
+    "gridRowSpan",
+    "gridRowStart",
+    "gridColumn",
+    "gridColumnEnd",
+    "gridColumnSpan",
+    "gridColumnStart",
+    "fontWeight",
+    "lineClamp",
+    "lineHeight",
+    "opacity",
+    "order",
+    "orphans",
+    "scale",
+    "tabSize",
+    "widows",
+    "zIndex",
+    "zoom",
+    "fillOpacity",
+    "floodOpacity",
+    "stopOpacity",
+    "strokeDasharray",
+    "strokeDashoffset",
+    "strokeMiterlimit",
+    "strokeOpacity",
+];
+
+fn is_custom_property(name: &str) -> bool {
+    name.starts_with("--")
+}
+
+fn is_unitless_number(name: &str) -> bool {
+    // vendor prefixed names, e.g. WebkitLineClamp, are unitless as well
+    UNITLESS_NUMBERS.iter().any(|unitless| {
+        name == *unitless
+            || ["Webkit", "ms", "Moz", "O"].iter().any(|prefix| {
+                name.strip_prefix(prefix).is_some_and(|rest| {
+                    rest.len() == unitless.len()
+                        && rest[..1] == unitless[..1].to_uppercase()
+                        && rest[1..] == unitless[1..]
+                })
+            })
+    })
+}
+
+pub fn hyphenate_style_name(name: &str) -> String {
+    if name == "cssFloat" {
+        return "float".to_string();
+    }
+    let mut hyphenated = String::new();
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            hyphenated.push('-');
+            hyphenated.push(c.to_ascii_lowercase());
+        } else {
+            hyphenated.push(c);
+        }
+    }
+    if hyphenated.starts_with("ms-") {
+        hyphenated.insert(0, '-');
+    }
+    hyphenated
+}
+
+fn dangerous_style_value(name: &str, value: &JsValue) -> String {
+    if is_nullish(value) || value.as_bool().is_some() {
+        return String::new();
+    }
+    if let Some(number) = value.as_f64() {
+        if number != 0.0 && !is_custom_property(name) && !is_unitless_number(name) {
+            return format!("{}px", number);
+        }
+    }
+    to_string_value(value).trim().to_string()
+}
+
+fn get_style(element: &Element) -> Option<CssStyleDeclaration> {
+    Reflect::get(element, &"style".into())
+        .ok()
+        .and_then(|style| style.dyn_into::<CssStyleDeclaration>().ok())
+}
+
+fn get_style_value(styles: &JsValue, name: &str) -> JsValue {
+    if !styles.is_object() {
+        return JsValue::undefined();
+    }
+    Reflect::get(styles, &name.into()).unwrap_or(JsValue::undefined())
+}
+
+fn set_style(style: &CssStyleDeclaration, name: &str, value: &JsValue) {
+    let property = if is_custom_property(name) {
+        name.to_string()
+    } else {
+        hyphenate_style_name(name)
+    };
+    let value = dangerous_style_value(name, value);
+    let result = if value.is_empty() {
+        style.remove_property(&property).map(|_| ())
+    } else {
+        style.set_property(&property, &value)
+    };
+    if let Err(e) = result {
+        warn!(Host, "set style {} failed {:?}", name, e);
+    }
+}
+
+pub fn set_value_for_styles(element: &Element, styles: &JsValue) {
+    let style = match get_style(element) {
+        Some(style) => style,
+        None => return,
+    };
+    for name in get_prop_keys(styles) {
+        set_style(&style, &name, &get_style_value(styles, &name));
+    }
+}
+
+pub fn update_styles(element: &Element, last_styles: &JsValue, next_styles: &JsValue) {
+    let style = match get_style(element) {
+        Some(style) => style,
+        None => return,
+    };
+    let next_names = get_prop_keys(next_styles);
+    for name in get_prop_keys(last_styles) {
+        if !next_names.contains(&name) {
+            set_style(&style, &name, &JsValue::null());
+        }
+    }
+    for name in next_names {
+        let next_value = get_style_value(next_styles, &name);
+        if !Object::is(&next_value, &get_style_value(last_styles, &name)) {
+            set_style(&style, &name, &next_value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hyphenates_camel_cased_names() {
+        assert_eq!(hyphenate_style_name("backgroundColor"), "background-color");
+        assert_eq!(hyphenate_style_name("color"), "color");
+        assert_eq!(hyphenate_style_name("cssFloat"), "float");
+        assert_eq!(
+            hyphenate_style_name("WebkitTransition"),
+            "-webkit-transition"
+        );
+        // ms is the one vendor prefix that is written lowercase in jsx
+        assert_eq!(hyphenate_style_name("msTransition"), "-ms-transition");
+    }
+
+    #[test]
+    fn knows_unitless_numbers_and_their_vendor_prefixes() {
+        assert!(is_unitless_number("opacity"));
+        assert!(is_unitless_number("zIndex"));
+        assert!(is_unitless_number("WebkitLineClamp"));
+        assert!(is_unitless_number("msFlexGrow"));
+        assert!(!is_unitless_number("width"));
+        assert!(!is_unitless_number("Webkitopacity"));
+        assert!(!is_unitless_number("WebkitOpacityX"));
+    }
+}
@@ -0,0 +1,42 @@
+use shared::derive_from_js_value;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+use web_sys::{Element, HtmlTextAreaElement};
+
+use crate::dom_component::{is_nullish, to_string_value};
+
+pub fn post_mount_wrapper(element: &Element, props: &JsValue) {
+    let textarea = element.dyn_ref::<HtmlTextAreaElement>().unwrap();
+    let value = derive_from_js_value(props, "value");
+    let default_value = derive_from_js_value(props, "defaultValue");
+    let initial_value = if !is_nullish(&value) {
+        value
+    } else {
+        default_value
+    };
+    if !is_nullish(&initial_value) {
+        let initial_value = to_string_value(&initial_value);
+        textarea
+            .set_default_value(&initial_value)
+            .expect("set default value panic");
+        textarea.set_value(&initial_value);
+    }
+}
+
+pub fn update_wrapper(element: &Element, props: &JsValue) {
+    let textarea = element.dyn_ref::<HtmlTextAreaElement>().unwrap();
+    let value = derive_from_js_value(props, "value");
+    if !is_nullish(&value) {
+        let value = to_string_value(&value);
+        if textarea.value() != value {
+            textarea.set_value(&value);
+        }
+    } else {
+        let default_value = derive_from_js_value(props, "defaultValue");
+        if !is_nullish(&default_value) {
+            textarea
+                .set_default_value(&to_string_value(&default_value))
+                .expect("set default value panic");
+        }
+    }
+}
@@ -1,8 +1,13 @@
 use std::{any::Any, rc::Rc};
 
-use react_reconciler::HostConfig;
-use shared::log;
-use web_sys::{window, Node};
+use react_reconciler::{react_value::Value, HostConfig};
+use shared::trace;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{window, Element, HtmlElement, Node};
+
+use crate::dom_component::{set_initial_properties, update_properties};
+use crate::dom_namespaces::{get_child_namespace, get_intrinsic_namespace, HTML_NAMESPACE};
+use crate::synthetic_event::mark_portal_container;
 
 pub struct ReactDomHostConfig;
 
@@ -13,43 +18,169 @@ impl HostConfig for ReactDomHostConfig {
         Rc::new(Node::from(document.create_text_node(content.as_str())))
     }
 
-    fn create_instance(&self, _type: String) -> Rc<dyn Any> {
+    fn create_instance(&self, _type: String, host_context: &str) -> Rc<dyn Any> {
         let window = window().expect("no global `window` exists");
         let document = window.document().expect("should have a document on window");
-        match document.create_element(_type.as_ref()) {
+        // an html parent leaves it to the type, e.g. <svg> starts the svg namespace
+        let namespace = if host_context.is_empty() || host_context == HTML_NAMESPACE {
+            get_intrinsic_namespace(&_type)
+        } else {
+            host_context
+        };
+        let element = if namespace == HTML_NAMESPACE {
+            document.create_element(_type.as_ref())
+        } else {
+            document.create_element_ns(Some(namespace), _type.as_ref())
+        };
+        match element {
             Ok(element) => Rc::new(Node::from(element)),
             Err(_) => todo!(),
         }
     }
 
+    fn get_root_host_context(&self, container: Rc<dyn Any>) -> String {
+        let container = container.clone().downcast::<Node>().unwrap();
+        match container.dyn_ref::<Element>() {
+            Some(element) => get_child_namespace(
+                &element.namespace_uri().unwrap_or_default(),
+                &element.local_name(),
+            ),
+            None => HTML_NAMESPACE.to_string(),
+        }
+    }
+
+    fn get_child_host_context(&self, parent_host_context: &str, _type: &str) -> String {
+        get_child_namespace(parent_host_context, _type)
+    }
+
+    fn get_public_instance(&self, instance: Rc<dyn Any>) -> Value {
+        let instance = instance.clone().downcast::<Node>().unwrap();
+        JsValue::from((*instance).clone()).into()
+    }
+
+    fn get_portal_container(&self, container_info: &Value) -> Rc<dyn Any> {
+        match container_info.to_js().dyn_into::<Node>() {
+            Ok(node) => Rc::new(node),
+            Err(_) => panic!("portal container should be Node"),
+        }
+    }
+
     fn append_initial_child(&self, parent: Rc<dyn Any>, child: Rc<dyn Any>) {
         let p = parent.clone().downcast::<Node>().unwrap();
         let c = child.clone().downcast::<Node>().unwrap();
         match p.append_child(&c) {
             Ok(_) => {
-                log!("append_initial_child successfully ele {:?} {:?}", p, c);
+                trace!(
+                    Host,
+                    "append_initial_child successfully ele {:?} {:?}",
+                    p,
+                    c
+                );
             }
             Err(_) => todo!(),
         }
     }
 
+    fn finalize_initial_children(&self, instance: Rc<dyn Any>, _type: String, props: &Value) {
+        let instance = instance.clone().downcast::<Node>().unwrap();
+        if let Some(element) = instance.dyn_ref::<Element>() {
+            set_initial_properties(element, &_type, &props.to_js());
+        }
+    }
+
+    fn commit_update(
+        &self,
+        instance: Rc<dyn Any>,
+        _type: String,
+        old_props: &Value,
+        new_props: &Value,
+    ) {
+        let instance = instance.clone().downcast::<Node>().unwrap();
+        if let Some(element) = instance.dyn_ref::<Element>() {
+            update_properties(element, &_type, &old_props.to_js(), &new_props.to_js());
+        }
+    }
+
+    fn prepare_portal_mount(&self, portal_container: Rc<dyn Any>, parent_container: Rc<dyn Any>) {
+        let portal_container = portal_container.clone().downcast::<Node>().unwrap();
+        let parent_container = parent_container.clone().downcast::<Node>().unwrap();
+        mark_portal_container(&portal_container, &parent_container);
+    }
+
     fn append_child_to_container(&self, child: Rc<dyn Any>, parent: Rc<dyn Any>) {
         self.append_initial_child(parent, child)
     }
 
+    fn insert_child_to_container(
+        &self,
+        child: Rc<dyn Any>,
+        container: Rc<dyn Any>,
+        before: Rc<dyn Any>,
+    ) {
+        let parent = container.clone().downcast::<Node>().unwrap();
+        let before = before.clone().downcast::<Node>().unwrap();
+        let child = child.clone().downcast::<Node>().unwrap();
+        match parent.insert_before(&child, Some(&before)) {
+            Ok(_) => {
+                trace!(
+                    Host,
+                    "insert_child_to_container successfully ele {:?} {:?}",
+                    parent,
+                    child
+                );
+            }
+            Err(_) => todo!(),
+        }
+    }
+
     fn remove_child(&self, child: Rc<dyn Any>, container: Rc<dyn Any>) {
         let p = container.clone().downcast::<Node>().unwrap();
         let c = child.clone().downcast::<Node>().unwrap();
         match p.remove_child(&c) {
             Ok(_) => {
-                log!("remove_child successfully ele {:?} {:?}", p, c);
+                trace!(Host, "remove_child successfully ele {:?} {:?}", p, c);
             }
             Err(_) => todo!(),
         }
     }
 
+    fn reset_after_commit(&self, _container: Rc<dyn Any>) {}
+
+    fn reset_text_content(&self, instance: Rc<dyn Any>) {
+        let instance = instance.clone().downcast::<Node>().unwrap();
+        instance.set_text_content(Some(""));
+    }
+
     fn commit_text_update(&self, text_instance: Rc<dyn Any>, content: String) {
         let text_instance = text_instance.clone().downcast::<Node>().unwrap();
         text_instance.set_node_value(Some(content.as_str()));
     }
+
+    fn hide_instance(&self, instance: Rc<dyn Any>) {
+        let instance = instance.clone().downcast::<Node>().unwrap();
+        if let Some(element) = instance.dyn_ref::<HtmlElement>() {
+            element
+                .style()
+                .set_property_with_priority("display", "none", "important")
+                .expect("hide_instance panic");
+        }
+    }
+
+    fn unhide_instance(&self, instance: Rc<dyn Any>) {
+        let instance = instance.clone().downcast::<Node>().unwrap();
+        if let Some(element) = instance.dyn_ref::<HtmlElement>() {
+            element
+                .style()
+                .remove_property("display")
+                .expect("unhide_instance panic");
+        }
+    }
+
+    fn hide_text_instance(&self, text_instance: Rc<dyn Any>) {
+        self.commit_text_update(text_instance, "".to_string());
+    }
+
+    fn unhide_text_instance(&self, text_instance: Rc<dyn Any>, content: String) {
+        self.commit_text_update(text_instance, content);
+    }
 }
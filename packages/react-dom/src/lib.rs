@@ -1,15 +1,23 @@
-use std::rc::Rc;
+use std::{cell::Cell, rc::Rc};
 
 use host_config::ReactDomHostConfig;
-use react_reconciler::Reconciler;
+use react_reconciler::{ProfilerClock, Reconciler};
 use renderer::Renderer;
-use utils::set_panic_hook;
+use shared::{warn, REACT_PORTAL_TYPE};
+use synthetic_event::{init_event, mark_container_as_root};
 use wasm_bindgen::prelude::*;
+use web_sys::js_sys::{Date, Function, Object, Reflect, TypeError, JSON};
 use web_sys::Node;
 
+mod dom_component;
+mod dom_input;
+mod dom_namespaces;
+mod dom_select;
+mod dom_style;
+mod dom_textarea;
 mod host_config;
 mod renderer;
-mod utils;
+mod synthetic_event;
 
 #[wasm_bindgen(js_name = createRoot)]
 pub fn create_root(container: &JsValue) -> Renderer {
@@ -21,7 +29,75 @@ pub fn create_root(container: &JsValue) -> Renderer {
             panic!("container should be Node")
         }
     };
+    mark_container_as_root(&node);
+    init_event(&node);
     let root = reconciler.create_container(Rc::new(node));
-    let renderer = Renderer::new(root, reconciler);
-    renderer
+    Renderer::new(root, reconciler)
+}
+
+#[wasm_bindgen(js_name = createPortal)]
+pub fn create_portal(
+    children: &JsValue,
+    container: &JsValue,
+    key: &JsValue,
+) -> Result<JsValue, JsValue> {
+    if container.dyn_ref::<Node>().is_none() {
+        return Err(TypeError::new("Target container is not a DOM element.").into());
+    }
+
+    let key = if key.is_undefined() || key.is_null() {
+        JsValue::null()
+    } else if key.is_string() {
+        key.clone()
+    } else {
+        JSON::stringify(key).unwrap().into()
+    };
+
+    let portal = Object::new();
+    Reflect::set(
+        &portal,
+        &"$$typeof".into(),
+        &JsValue::from_str(REACT_PORTAL_TYPE),
+    )
+    .expect("$$typeof panic");
+    Reflect::set(&portal, &"key".into(), &key).expect("key panic");
+    Reflect::set(&portal, &"children".into(), children).expect("children panic");
+    Reflect::set(&portal, &"containerInfo".into(), container).expect("containerInfo panic");
+    Ok(portal.into())
+}
+
+struct JsProfilerClock {
+    now: Function,
+    did_warn: Cell<bool>,
+}
+
+impl ProfilerClock for JsProfilerClock {
+    // Date.now() when the clock throws or does not return a number, with a warning the first time
+    fn now(&self) -> f64 {
+        let error = match self.now.call0(&JsValue::null()) {
+            Ok(time) => match time.as_f64() {
+                Some(time) => return time,
+                None => time,
+            },
+            Err(error) => error,
+        };
+        if !self.did_warn.replace(true) {
+            warn!(
+                Commit,
+                "the profiler clock failed, Date.now() is used instead {:?}", error
+            );
+        }
+        Date::now()
+    }
+}
+
+shared::renderer_exports!(Renderer, react_devtools::connect);
+
+// lets tests control the timings Profiler reports
+#[wasm_bindgen(js_name = unstable_setProfilerClock)]
+pub fn set_profiler_clock(now: Function) {
+    react_reconciler::set_profiler_clock(Rc::new(JsProfilerClock {
+        now,
+        did_warn: Cell::new(false),
+    }));
 }
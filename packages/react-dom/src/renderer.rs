@@ -1,11 +1,12 @@
 use std::{cell::RefCell, rc::Rc};
 
-use react_reconciler::{fiber::FiberRootNode, Reconciler};
+use react_reconciler::{fiber::FiberRootNode, react_value::Value, Reconciler};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
 pub struct Renderer {
-    root: Rc<RefCell<FiberRootNode>>,
+    // getSnapshot of renderer_exports! reads it
+    pub(crate) root: Rc<RefCell<FiberRootNode>>,
     reconciler: Reconciler,
 }
 
@@ -13,12 +14,19 @@ impl Renderer {
     pub fn new(root: Rc<RefCell<FiberRootNode>>, reconciler: Reconciler) -> Self {
         Self { root, reconciler }
     }
+
+    // e.g. an element built with rsx!, reconciled as it is rather than as a javascript object
+    pub fn render_element(&self, element: impl Into<Value>) -> Value {
+        self.reconciler
+            .update_container(element.into(), self.root.clone())
+    }
 }
 
 #[wasm_bindgen]
 impl Renderer {
     pub fn render(&self, element: &JsValue) -> JsValue {
         self.reconciler
-            .update_container(element.clone(), self.root.clone())
+            .update_container(element.into(), self.root.clone())
+            .to_js()
     }
 }
@@ -0,0 +1,197 @@
+use shared::error;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::js_sys::{Function, Reflect};
+use web_sys::{Element, Event, HtmlInputElement, HtmlSelectElement, Node};
+
+use crate::dom_component::restore_controlled_state;
+
+// every listener lives on the container, handlers are found through the __props of the nodes
+static NATIVE_EVENTS: [&str; 11] = [
+    "click",
+    "dblclick",
+    "input",
+    "change",
+    "keydown",
+    "keyup",
+    "mousedown",
+    "mouseup",
+    "submit",
+    "focusin",
+    "focusout",
+];
+
+struct Paths {
+    capture: Vec<Function>,
+    bubble: Vec<Function>,
+}
+
+pub fn update_fiber_props(node: &Element, props: &JsValue) {
+    Reflect::set(node, &"__props".into(), props).expect("update_fiber_props panic");
+}
+
+fn is_flag_set(target: &JsValue, flag: &str) -> bool {
+    Reflect::get(target, &flag.into())
+        .map(|value| value.is_truthy())
+        .unwrap_or(false)
+}
+
+pub fn mark_container_as_root(container: &Node) {
+    Reflect::set(container, &"__reactContainer".into(), &JsValue::TRUE)
+        .expect("mark_container_as_root panic");
+}
+
+// events in a portal bubble to the react parent of the portal, not to its dom parent
+pub fn mark_portal_container(portal_container: &Node, parent_container: &Node) {
+    Reflect::set(
+        portal_container,
+        &"__reactPortalParent".into(),
+        parent_container,
+    )
+    .expect("mark_portal_container panic");
+    init_event(portal_container);
+}
+
+pub fn init_event(container: &Node) {
+    if is_flag_set(container, "__reactListening") {
+        return;
+    }
+    Reflect::set(container, &"__reactListening".into(), &JsValue::TRUE).expect("init_event panic");
+
+    for event_type in NATIVE_EVENTS {
+        let closure = Closure::wrap(Box::new(move |event: Event| {
+            dispatch_event(event);
+        }) as Box<dyn Fn(Event)>);
+        container
+            .add_event_listener_with_callback(event_type, closure.as_ref().unchecked_ref())
+            .expect("add event listener panic");
+        closure.forget();
+    }
+}
+
+fn uses_change_event(target: &JsValue) -> bool {
+    if target.dyn_ref::<HtmlSelectElement>().is_some() {
+        return true;
+    }
+    match target.dyn_ref::<HtmlInputElement>() {
+        Some(input) => matches!(input.type_().as_str(), "checkbox" | "radio" | "file"),
+        None => false,
+    }
+}
+
+fn get_event_prop_names(event_type: &str, target: &JsValue) -> Vec<&'static str> {
+    match event_type {
+        "click" => vec!["onClick"],
+        "dblclick" => vec!["onDoubleClick"],
+        // onChange fires on every keystroke for text fields
+        "input" => {
+            if uses_change_event(target) {
+                vec!["onInput"]
+            } else {
+                vec!["onInput", "onChange"]
+            }
+        }
+        "change" => {
+            if uses_change_event(target) {
+                vec!["onChange"]
+            } else {
+                vec![]
+            }
+        }
+        "keydown" => vec!["onKeyDown"],
+        "keyup" => vec!["onKeyUp"],
+        "mousedown" => vec!["onMouseDown"],
+        "mouseup" => vec!["onMouseUp"],
+        "submit" => vec!["onSubmit"],
+        "focusin" => vec!["onFocus"],
+        "focusout" => vec!["onBlur"],
+        _ => vec![],
+    }
+}
+
+fn collect_paths(target: Option<Node>, prop_names: &Vec<&'static str>) -> Paths {
+    let mut paths = Paths {
+        capture: vec![],
+        bubble: vec![],
+    };
+    let mut node = target;
+    while let Some(current) = node {
+        if is_flag_set(&current, "__reactContainer") {
+            break;
+        }
+        let props = Reflect::get(&current, &"__props".into()).unwrap_or(JsValue::undefined());
+        if props.is_object() {
+            for prop_name in prop_names {
+                let capture = Reflect::get(&props, &format!("{}Capture", prop_name).into());
+                if let Ok(capture) = capture.and_then(|c| c.dyn_into::<Function>()) {
+                    paths.capture.insert(0, capture);
+                }
+                let bubble = Reflect::get(&props, &(*prop_name).into());
+                if let Ok(bubble) = bubble.and_then(|b| b.dyn_into::<Function>()) {
+                    paths.bubble.push(bubble);
+                }
+            }
+        }
+        node = match Reflect::get(&current, &"__reactPortalParent".into()) {
+            Ok(parent) if parent.is_object() => parent.dyn_into::<Node>().ok(),
+            _ => current.parent_node(),
+        };
+    }
+    paths
+}
+
+fn create_synthetic_event(event: &Event) {
+    Reflect::set(event, &"__stopPropagation".into(), &JsValue::FALSE)
+        .expect("create_synthetic_event panic");
+    let stop_propagation = Function::new_no_args(
+        "this.__stopPropagation = true; Event.prototype.stopPropagation.call(this);",
+    );
+    Reflect::set(event, &"stopPropagation".into(), &stop_propagation)
+        .expect("create_synthetic_event panic");
+}
+
+fn trigger_event_flow(paths: &Vec<Function>, event: &Event) {
+    for callback in paths {
+        if let Err(e) = callback.call1(&JsValue::null(), event) {
+            error!(Host, "event handler threw {:?}", e);
+        }
+        if is_flag_set(event, "__stopPropagation") {
+            break;
+        }
+    }
+}
+
+fn dispatch_event(event: Event) {
+    // a portal container inside the root container sees the event first
+    if is_flag_set(&event, "__reactDispatched") {
+        return;
+    }
+    Reflect::set(&event, &"__reactDispatched".into(), &JsValue::TRUE)
+        .expect("dispatch_event panic");
+
+    let target: JsValue = match event.target() {
+        Some(target) => target.into(),
+        None => return,
+    };
+    let event_type = event.type_();
+    let prop_names = get_event_prop_names(&event_type, &target);
+    if prop_names.is_empty() {
+        return;
+    }
+
+    let paths = collect_paths(target.clone().dyn_into::<Node>().ok(), &prop_names);
+    create_synthetic_event(&event);
+    trigger_event_flow(&paths.capture, &event);
+    if !is_flag_set(&event, "__stopPropagation") {
+        trigger_event_flow(&paths.bubble, &event);
+    }
+
+    if event_type == "input" || event_type == "change" {
+        if let Some(element) = target.dyn_ref::<Element>() {
+            let props = Reflect::get(element, &"__props".into()).unwrap_or(JsValue::undefined());
+            if props.is_object() {
+                restore_controlled_state(element, &element.tag_name().to_lowercase(), &props);
+            }
+        }
+    }
+}
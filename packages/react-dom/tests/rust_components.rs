@@ -0,0 +1,121 @@
+//! Rust components rendered by react-dom, in a headless browser.
+
+#![cfg(target_arch = "wasm32")]
+
+extern crate wasm_bindgen_test;
+use react::element::{component_type, Component, Element, Props};
+use react::hooks::use_state;
+use react::rsx;
+use react_dom::create_root;
+use react_reconciler::react_value::Value;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_test::*;
+use web_sys::js_sys::{Function, Object};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn container() -> web_sys::Element {
+    web_sys::window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .create_element("div")
+        .unwrap()
+}
+
+fn greeting(props: &Props) -> Result<Element, Value> {
+    let name = props.get_string("name").unwrap_or_default();
+    Ok(rsx!(span[format!("hello {}", name)]))
+}
+
+struct Badge;
+
+impl Component for Badge {
+    const ID: &'static str = "Badge";
+
+    fn render(props: &Props) -> Result<Element, Value> {
+        Ok(Element::host("b").child(props.children()).into())
+    }
+}
+
+fn counter(props: &Props) -> Result<Element, Value> {
+    let (count, _) = use_state(props.get("initial"))?;
+    let count = count.as_f64().unwrap_or_default();
+    Ok(Element::host("span").child(count.to_string()).into())
+}
+
+fn broken(_: &Props) -> Result<Element, Value> {
+    Err(Value::from_string("broken"))
+}
+
+#[wasm_bindgen_test]
+fn renders_a_rust_component_with_its_props() {
+    let container = container();
+    let root = create_root(&container.clone().into());
+    root.render_element(rsx!(
+        div { className: "row" }[rsx!(@greeting { name: "ada" })]
+    ));
+    assert_eq!(
+        container.inner_html(),
+        "<div class=\"row\"><span>hello ada</span></div>"
+    );
+}
+
+#[wasm_bindgen_test]
+fn gives_a_component_id_the_same_type_every_time() {
+    assert!(Object::is(
+        &component_type("greeting", greeting),
+        &component_type("greeting", greeting)
+    ));
+    assert!(!Object::is(
+        &component_type("greeting", greeting),
+        &component_type("counter", counter)
+    ));
+}
+
+#[wasm_bindgen_test]
+fn renders_a_rust_component_converted_to_js() {
+    let container = container();
+    let root = create_root(&container.clone().into());
+    root.render(&rsx!(@greeting { name: "js" }).into());
+    assert_eq!(container.inner_html(), "<span>hello js</span>");
+}
+
+#[wasm_bindgen_test]
+fn renders_a_component_impl_with_its_children() {
+    let container = container();
+    let root = create_root(&container.clone().into());
+    root.render_element(Element::of::<Badge>().child("new"));
+    assert_eq!(container.inner_html(), "<b>new</b>");
+}
+
+#[wasm_bindgen_test]
+fn keeps_the_state_of_hooks_called_from_rust() {
+    let container = container();
+    let root = create_root(&container.clone().into());
+    root.render_element(Element::component("counter", counter).prop("initial", 1));
+    root.render_element(Element::component("counter", counter).prop("initial", 5));
+    assert_eq!(container.inner_html(), "<span>1</span>");
+}
+
+#[wasm_bindgen_test]
+fn mixes_js_and_rust_components() {
+    let container = container();
+    let root = create_root(&container.clone().into());
+    let label = Function::new_with_args("props", "return props.label");
+    root.render_element(
+        Element::host("p")
+            .child(Element::js_component(&label.into()).prop("label", "js "))
+            .child(rsx!(@greeting { name: "rust" })),
+    );
+    assert_eq!(container.inner_html(), "<p>js <span>hello rust</span></p>");
+}
+
+#[wasm_bindgen_test]
+fn does_not_commit_when_a_rust_component_fails() {
+    let container = container();
+    let root = create_root(&container.clone().into());
+    root.render_element(Element::host("p").child("before"));
+    root.render_element(Element::host("p").child(Element::component("broken", broken)));
+    assert_eq!(container.inner_html(), "<p>before</p>");
+}
@@ -1,38 +1,143 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{any::Any, cell::RefCell, rc::Rc};
 
-use shared::derive_from_js_value;
-use wasm_bindgen::JsValue;
+use shared::REACT_FORWARD_REF_TYPE;
 
 use crate::{
-    child_fiber::{mount_child_fibers, reconcile_child_fibers},
+    child_fiber::{delete_child, mount_child_fibers, reconcile_child_fibers},
     fiber::{FiberNode, MemoizedState},
-    fiber_hooks::render_with_hooks,
-    update_queue::process_update_queue,
+    fiber_context::push_provider,
+    fiber_flags::Flags,
+    fiber_hooks::{check_did_render_id_hook, render_with_hooks},
+    fiber_host_context::{push_host_container, push_host_context},
+    fiber_lanes::Lane,
+    fiber_tree_context::{get_forks_at_level, push_materialized_tree_id, push_tree_id},
+    react_value::{ReactNode, ReactValue, Value},
+    update_queue::{merge_update_lists, process_update_queue},
     work_tags::WorkTag,
+    HostConfig,
 };
 
 pub fn begin_work(
     work_in_progress: Rc<RefCell<FiberNode>>,
-) -> Result<Option<Rc<RefCell<FiberNode>>>, JsValue> {
+    render_lane: Lane,
+    host_config: &Rc<dyn HostConfig>,
+) -> Result<Option<Rc<RefCell<FiberNode>>>, Value> {
     let tag = work_in_progress.clone().borrow().tag.clone();
 
-    return match tag {
-        WorkTag::FunctionComponent => update_function_component(work_in_progress.clone()),
-        WorkTag::HostRoot => Ok(update_host_root(work_in_progress.clone())),
-        WorkTag::HostComponent => Ok(update_host_component(work_in_progress.clone())),
+    if work_in_progress.borrow().flags.contains(Flags::Forked) {
+        let slot_index = work_in_progress.borrow().index;
+        push_tree_id(work_in_progress.clone(), get_forks_at_level(), slot_index);
+    }
+
+    match tag {
+        WorkTag::FunctionComponent => {
+            update_function_component(work_in_progress.clone(), render_lane)
+        }
+        WorkTag::ForwardRef => update_forward_ref(work_in_progress.clone(), render_lane),
+        WorkTag::LazyComponent => mount_lazy_component(work_in_progress.clone(), render_lane),
+        WorkTag::HostRoot => update_host_root(work_in_progress.clone(), render_lane, host_config),
+        WorkTag::HostComponent => Ok(update_host_component(work_in_progress.clone(), host_config)),
         WorkTag::HostText => Ok(None),
-    };
+        WorkTag::HostPortal => Ok(update_portal_component(
+            work_in_progress.clone(),
+            host_config,
+        )),
+        WorkTag::Fragment => Ok(update_fragment(work_in_progress.clone())),
+        WorkTag::Mode => Ok(update_mode(work_in_progress.clone())),
+        WorkTag::Profiler => Ok(update_profiler(work_in_progress.clone())),
+        WorkTag::ContextProvider => Ok(update_context_provider(work_in_progress.clone())),
+        WorkTag::SuspenseComponent => Ok(update_suspense_component(work_in_progress.clone())),
+        WorkTag::OffscreenComponent => Ok(update_offscreen_component(work_in_progress.clone())),
+    }
 }
 
 fn update_function_component(
     work_in_progress: Rc<RefCell<FiberNode>>,
-) -> Result<Option<Rc<RefCell<FiberNode>>>, JsValue> {
-    let next_children = render_with_hooks(work_in_progress.clone())?;
+    render_lane: Lane,
+) -> Result<Option<Rc<RefCell<FiberNode>>>, Value> {
+    let component = work_in_progress.borrow()._type.clone();
+    let next_children = render_with_hooks(
+        work_in_progress.clone(),
+        component,
+        Value::undefined(),
+        render_lane,
+    )?;
+    if check_did_render_id_hook() {
+        push_materialized_tree_id(work_in_progress.clone());
+    }
+    reconcile_children(work_in_progress.clone(), Some(next_children));
+    Ok(work_in_progress.clone().borrow().child.clone())
+}
+
+fn update_forward_ref(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    render_lane: Lane,
+) -> Result<Option<Rc<RefCell<FiberNode>>>, Value> {
+    let (render, _ref) = {
+        let work_in_progress = work_in_progress.borrow();
+        (
+            work_in_progress._type.get("render"),
+            work_in_progress._ref.clone(),
+        )
+    };
+    // the ref isn't attached to this fiber, the render function decides where it goes
+    let next_children = render_with_hooks(work_in_progress.clone(), render, _ref, render_lane)?;
+    if check_did_render_id_hook() {
+        push_materialized_tree_id(work_in_progress.clone());
+    }
     reconcile_children(work_in_progress.clone(), Some(next_children));
     Ok(work_in_progress.clone().borrow().child.clone())
 }
 
-fn update_host_root(work_in_progress: Rc<RefCell<FiberNode>>) -> Option<Rc<RefCell<FiberNode>>> {
+fn mount_lazy_component(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    render_lane: Lane,
+) -> Result<Option<Rc<RefCell<FiberNode>>>, Value> {
+    let lazy_component = work_in_progress.borrow().element_type.clone();
+    let payload = lazy_component.get("_payload");
+    // throws the loader's thenable while the module is pending
+    let component = lazy_component.get("_init").call(&[payload])?;
+
+    // there is no class or memo component tag to resolve to, say so instead of failing
+    // later with an opaque TypeError from calling a class without `new`
+    let is_class = component.is_function()
+        && !component
+            .get("prototype")
+            .get("isReactComponent")
+            .is_nullish();
+    let tag = if is_class {
+        return Err(Value::from_string(
+            "lazy() resolved to a class component, class components are not supported",
+        ));
+    } else if component.is_function() {
+        WorkTag::FunctionComponent
+    } else if component.type_of().as_deref() == Some(REACT_FORWARD_REF_TYPE) {
+        WorkTag::ForwardRef
+    } else {
+        return Err(Value::from_string(format!(
+            "Element type is invalid. Received a promise that resolves to: {:?}",
+            component
+        )));
+    };
+
+    // later renders reuse the resolved type and skip the lazy wrapper
+    {
+        let mut work_in_progress = work_in_progress.borrow_mut();
+        work_in_progress._type = component;
+        work_in_progress.tag = tag.clone();
+    }
+    match tag {
+        WorkTag::ForwardRef => update_forward_ref(work_in_progress, render_lane),
+        _ => update_function_component(work_in_progress, render_lane),
+    }
+}
+
+fn update_host_root(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    render_lane: Lane,
+    host_config: &Rc<dyn HostConfig>,
+) -> Result<Option<Rc<RefCell<FiberNode>>>, Value> {
+    push_host_container(host_config, work_in_progress.clone());
     let work_in_progress_cloned = work_in_progress.clone();
 
     let base_state;
@@ -40,11 +145,24 @@ fn update_host_root(work_in_progress: Rc<RefCell<FiberNode>>) -> Option<Rc<RefCe
     {
         let work_in_progress_borrowed = work_in_progress_cloned.borrow();
         base_state = work_in_progress_borrowed.memoized_state.clone();
-        update_queue = work_in_progress_borrowed.update_queue.clone();
+        update_queue = work_in_progress_borrowed.update_queue.clone().unwrap();
     }
     {
-        work_in_progress.clone().borrow_mut().memoized_state =
-            process_update_queue(base_state, update_queue, work_in_progress.clone());
+        // the updates of a render that never committed come first
+        let pending = {
+            let mut update_queue = update_queue.borrow_mut();
+            let pending = merge_update_lists(
+                update_queue.base_queue.take(),
+                update_queue.shared.pending.take(),
+            );
+            update_queue.base_queue = pending.clone();
+            pending
+        };
+        // an error throws the render away, its updates stay in base_queue
+        let result = process_update_queue(base_state, pending, render_lane)?;
+        // updates skipped by this render stay queued for the render of their own lane
+        update_queue.borrow_mut().shared.pending = result.base_queue;
+        work_in_progress.clone().borrow_mut().memoized_state = result.memoized_state;
     }
 
     let next_children = work_in_progress.clone().borrow().memoized_state.clone();
@@ -52,29 +170,310 @@ fn update_host_root(work_in_progress: Rc<RefCell<FiberNode>>) -> Option<Rc<RefCe
         panic!("update_host_root next_children is none")
     }
 
-    if let MemoizedState::JsValue(next_children) = next_children.unwrap() {
+    if let MemoizedState::Value(next_children) = next_children.unwrap() {
         reconcile_children(work_in_progress.clone(), Some(next_children));
     }
-    work_in_progress.clone().borrow().child.clone()
+    Ok(work_in_progress.clone().borrow().child.clone())
 }
 
 fn update_host_component(
     work_in_progress: Rc<RefCell<FiberNode>>,
+    host_config: &Rc<dyn HostConfig>,
 ) -> Option<Rc<RefCell<FiberNode>>> {
+    push_host_context(host_config, work_in_progress.clone());
     let work_in_progress = Rc::clone(&work_in_progress);
 
+    let next_props = work_in_progress.borrow().pending_props.clone();
+    let mut next_children = next_props.get("children");
+
+    if should_set_text_content(&next_props) {
+        // the host fills the node itself, there are no children to reconcile
+        next_children = Value::null();
+    } else {
+        let current = work_in_progress.borrow().alternate.clone();
+        if let Some(current) = current {
+            if should_set_text_content(&current.borrow().memoized_props) {
+                work_in_progress.borrow_mut().flags |= Flags::ContentReset;
+            }
+        }
+    }
+
+    mark_ref(work_in_progress.clone());
+    {
+        reconcile_children(work_in_progress.clone(), Some(next_children));
+    }
+    work_in_progress.clone().borrow().child.clone()
+}
+
+fn should_set_text_content(props: &Value) -> bool {
+    !props
+        .get("dangerouslySetInnerHTML")
+        .get("__html")
+        .is_nullish()
+}
+
+fn mark_ref(work_in_progress: Rc<RefCell<FiberNode>>) {
+    let _ref = work_in_progress.borrow()._ref.clone();
+    let current = work_in_progress.borrow().alternate.clone();
+    let ref_changed = match current {
+        None => !_ref.is_nullish(),
+        Some(current) => current.borrow()._ref != _ref,
+    };
+    if ref_changed {
+        work_in_progress.borrow_mut().flags |= Flags::Ref;
+    }
+}
+
+fn update_fragment(work_in_progress: Rc<RefCell<FiberNode>>) -> Option<Rc<RefCell<FiberNode>>> {
+    let next_children = work_in_progress.clone().borrow().pending_props.clone();
+    reconcile_children(work_in_progress.clone(), Some(next_children));
+    work_in_progress.clone().borrow().child.clone()
+}
+
+fn update_mode(work_in_progress: Rc<RefCell<FiberNode>>) -> Option<Rc<RefCell<FiberNode>>> {
+    let next_children = work_in_progress.borrow().pending_props.get("children");
+    reconcile_children(work_in_progress.clone(), Some(next_children));
+    work_in_progress.clone().borrow().child.clone()
+}
+
+fn update_profiler(work_in_progress: Rc<RefCell<FiberNode>>) -> Option<Rc<RefCell<FiberNode>>> {
+    // onRender is called in the layout phase of every commit the profiler takes part in
+    work_in_progress.borrow_mut().flags |= Flags::Update;
+    let next_children = work_in_progress.borrow().pending_props.get("children");
+    reconcile_children(work_in_progress.clone(), Some(next_children));
+    work_in_progress.clone().borrow().child.clone()
+}
+
+fn update_portal_component(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    host_config: &Rc<dyn HostConfig>,
+) -> Option<Rc<RefCell<FiberNode>>> {
+    push_host_container(host_config, work_in_progress.clone());
+    let next_children = work_in_progress.clone().borrow().pending_props.clone();
+    let current_first_child = match work_in_progress.borrow().alternate.clone() {
+        Some(current) => current.borrow().child.clone(),
+        None => None,
+    };
+    // the portal itself is never inserted, so even on mount its children need placement
+    work_in_progress.borrow_mut().child = reconcile_child_fibers(
+        work_in_progress.clone(),
+        current_first_child,
+        Some(next_children),
+    );
+    work_in_progress.clone().borrow().child.clone()
+}
+
+fn update_context_provider(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+) -> Option<Rc<RefCell<FiberNode>>> {
+    let (provider_type, new_props) = {
+        let ref_fiber_node = work_in_progress.borrow();
+        (
+            ref_fiber_node._type.clone(),
+            ref_fiber_node.pending_props.clone(),
+        )
+    };
+    let context = provider_type.get("_context");
+    push_provider(&context, new_props.get("value"));
+
+    let next_children = new_props.get("children");
+    reconcile_children(work_in_progress.clone(), Some(next_children));
+    work_in_progress.clone().borrow().child.clone()
+}
+
+fn update_offscreen_component(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+) -> Option<Rc<RefCell<FiberNode>>> {
     let next_children = {
         let ref_fiber_node = work_in_progress.borrow();
-        derive_from_js_value(&ref_fiber_node.pending_props, "children")
+        ref_fiber_node.pending_props.get("children")
     };
+    reconcile_children(work_in_progress.clone(), Some(next_children));
+    work_in_progress.clone().borrow().child.clone()
+}
+
+// the props of the offscreen fiber that wraps the primary children of a suspense boundary
+#[derive(Debug)]
+struct OffscreenProps {
+    mode: &'static str,
+    children: Value,
+}
+
+impl ReactValue for OffscreenProps {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_same(&self, other: &Value) -> bool {
+        other
+            .downcast_ref::<OffscreenProps>()
+            .is_some_and(|other| std::ptr::eq(self, other))
+    }
+
+    fn get(&self, name: &str) -> Value {
+        match name {
+            "mode" => Value::from_string(self.mode),
+            "children" => self.children.clone(),
+            _ => Value::undefined(),
+        }
+    }
+
+    fn keys(&self) -> Option<Vec<String>> {
+        Some(vec!["mode".to_string(), "children".to_string()])
+    }
+}
+
+fn create_offscreen_props(mode: &'static str, children: &Value) -> Value {
+    Value::new(OffscreenProps {
+        mode,
+        children: children.clone(),
+    })
+}
+
+fn update_suspense_component(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+) -> Option<Rc<RefCell<FiberNode>>> {
+    let current = { work_in_progress.borrow().alternate.clone() };
+    let next_props = { work_in_progress.borrow().pending_props.clone() };
+
+    // DidCapture is set by unwind_work when a child suspended during this render
+    let show_fallback = work_in_progress.borrow().flags.contains(Flags::DidCapture);
+    if show_fallback {
+        work_in_progress.borrow_mut().flags -= Flags::DidCapture;
+    }
 
+    let next_primary_children = next_props.get("children");
+    let next_fallback_children = next_props.get("fallback");
+
+    match (current, show_fallback) {
+        (None, false) => {
+            mount_suspense_primary_children(work_in_progress.clone(), &next_primary_children)
+        }
+        (None, true) => mount_suspense_fallback_children(
+            work_in_progress.clone(),
+            &next_primary_children,
+            &next_fallback_children,
+        ),
+        (Some(current), false) => update_suspense_primary_children(
+            work_in_progress.clone(),
+            current,
+            &next_primary_children,
+        ),
+        (Some(current), true) => update_suspense_fallback_children(
+            work_in_progress.clone(),
+            current,
+            &next_primary_children,
+            &next_fallback_children,
+        ),
+    }
+}
+
+fn mount_suspense_primary_children(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    primary_children: &Value,
+) -> Option<Rc<RefCell<FiberNode>>> {
+    let primary_child_fragment = Rc::new(RefCell::new(FiberNode::new(
+        WorkTag::OffscreenComponent,
+        create_offscreen_props("visible", primary_children),
+        Value::null(),
+    )));
+    primary_child_fragment.borrow_mut().mode = work_in_progress.borrow().mode;
+    primary_child_fragment.borrow_mut()._return = Some(work_in_progress.clone());
+    work_in_progress.borrow_mut().child = Some(primary_child_fragment.clone());
+    Some(primary_child_fragment)
+}
+
+fn mount_suspense_fallback_children(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    primary_children: &Value,
+    fallback_children: &Value,
+) -> Option<Rc<RefCell<FiberNode>>> {
+    let primary_child_fragment = Rc::new(RefCell::new(FiberNode::new(
+        WorkTag::OffscreenComponent,
+        create_offscreen_props("hidden", primary_children),
+        Value::null(),
+    )));
+    let fallback_child_fragment = Rc::new(RefCell::new(FiberNode::new(
+        WorkTag::Fragment,
+        fallback_children.clone(),
+        Value::null(),
+    )));
+
+    let mode = work_in_progress.borrow().mode;
+    primary_child_fragment.borrow_mut().mode = mode;
+    fallback_child_fragment.borrow_mut().mode = mode;
+    primary_child_fragment.borrow_mut()._return = Some(work_in_progress.clone());
+    fallback_child_fragment.borrow_mut()._return = Some(work_in_progress.clone());
+    primary_child_fragment.borrow_mut().sibling = Some(fallback_child_fragment.clone());
+    work_in_progress.borrow_mut().child = Some(primary_child_fragment);
+    Some(fallback_child_fragment)
+}
+
+fn update_suspense_primary_children(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    current: Rc<RefCell<FiberNode>>,
+    primary_children: &Value,
+) -> Option<Rc<RefCell<FiberNode>>> {
+    let current_primary_child_fragment = current.borrow().child.clone().unwrap();
+    let current_fallback_child_fragment = current_primary_child_fragment.borrow().sibling.clone();
+
+    let primary_child_fragment = FiberNode::create_work_in_progress(
+        current_primary_child_fragment,
+        create_offscreen_props("visible", primary_children),
+    );
     {
-        reconcile_children(work_in_progress.clone(), Some(next_children));
+        let mut primary_child_fragment = primary_child_fragment.borrow_mut();
+        primary_child_fragment._return = Some(work_in_progress.clone());
+        primary_child_fragment.sibling = None;
     }
-    work_in_progress.clone().borrow().child.clone()
+    work_in_progress.borrow_mut().child = Some(primary_child_fragment.clone());
+
+    if let Some(current_fallback_child_fragment) = current_fallback_child_fragment {
+        delete_child(
+            work_in_progress.clone(),
+            current_fallback_child_fragment,
+            true,
+        );
+    }
+    Some(primary_child_fragment)
+}
+
+fn update_suspense_fallback_children(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    current: Rc<RefCell<FiberNode>>,
+    primary_children: &Value,
+    fallback_children: &Value,
+) -> Option<Rc<RefCell<FiberNode>>> {
+    let current_primary_child_fragment = current.borrow().child.clone().unwrap();
+    let current_fallback_child_fragment = current_primary_child_fragment.borrow().sibling.clone();
+
+    let primary_child_fragment = FiberNode::create_work_in_progress(
+        current_primary_child_fragment,
+        create_offscreen_props("hidden", primary_children),
+    );
+    let fallback_child_fragment = match current_fallback_child_fragment {
+        Some(current_fallback_child_fragment) => FiberNode::create_work_in_progress(
+            current_fallback_child_fragment,
+            fallback_children.clone(),
+        ),
+        None => {
+            let mut fragment =
+                FiberNode::new(WorkTag::Fragment, fallback_children.clone(), Value::null());
+            fragment.mode = work_in_progress.borrow().mode;
+            fragment.flags |= Flags::Placement | Flags::PlacementDEV;
+            Rc::new(RefCell::new(fragment))
+        }
+    };
+
+    primary_child_fragment.borrow_mut()._return = Some(work_in_progress.clone());
+    fallback_child_fragment.borrow_mut()._return = Some(work_in_progress.clone());
+    fallback_child_fragment.borrow_mut().sibling = None;
+    primary_child_fragment.borrow_mut().sibling = Some(fallback_child_fragment.clone());
+    work_in_progress.borrow_mut().child = Some(primary_child_fragment);
+    Some(fallback_child_fragment)
 }
 
-fn reconcile_children(work_in_progress: Rc<RefCell<FiberNode>>, children: Option<JsValue>) {
+fn reconcile_children(work_in_progress: Rc<RefCell<FiberNode>>, children: Option<ReactNode>) {
     let work_in_progress = Rc::clone(&work_in_progress);
     let current = { work_in_progress.borrow().alternate.clone() };
 
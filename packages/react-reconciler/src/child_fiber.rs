@@ -1,12 +1,19 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
-use shared::{derive_from_js_value, log, REACT_ELEMENT_TYPE};
-use wasm_bindgen::JsValue;
-use web_sys::js_sys::{Object, Reflect};
+use crate::{
+    fiber::{FiberNode, StateNode},
+    fiber_dev_warnings::{warn_for_invalid_child, warn_for_missing_key, warn_on_invalid_key},
+    fiber_flags::Flags,
+    fiber_tree_context::push_tree_fork,
+    react_value::{Props, ReactNode, ReactNodeKind, Value},
+    work_tags::WorkTag,
+};
 
-use crate::{fiber::FiberNode, fiber_flags::Flags, work_tags::WorkTag};
-
-fn use_fiber(fiber: Rc<RefCell<FiberNode>>, pending_props: JsValue) -> Rc<RefCell<FiberNode>> {
+fn use_fiber(fiber: Rc<RefCell<FiberNode>>, pending_props: Props) -> Rc<RefCell<FiberNode>> {
     let clone = FiberNode::create_work_in_progress(fiber, pending_props);
     clone.borrow_mut().index = 0;
     clone.borrow_mut().sibling = None;
@@ -20,12 +27,12 @@ fn place_single_child(
     if should_track_effect && fiber.clone().borrow().alternate.is_none() {
         let fiber = fiber.clone();
         let mut fiber = fiber.borrow_mut();
-        fiber.flags |= Flags::Placement;
+        fiber.flags |= Flags::Placement | Flags::PlacementDEV;
     }
-    return fiber;
+    fiber
 }
 
-fn delete_child(
+pub fn delete_child(
     return_fiber: Rc<RefCell<FiberNode>>,
     child_to_delete: Rc<RefCell<FiberNode>>,
     should_track_effect: bool,
@@ -43,56 +50,120 @@ fn delete_child(
         return_fiber.borrow_mut().deletions = Some(vec![child_to_delete.clone()]);
         return_fiber.borrow_mut().flags |= Flags::ChildDeletion;
     } else {
-        let mut del = return_fiber.borrow_mut().deletions.clone().unwrap();
-        del.push(child_to_delete.clone());
+        let mut return_fiber_borrowed = return_fiber.borrow_mut();
+        return_fiber_borrowed
+            .deletions
+            .as_mut()
+            .unwrap()
+            .push(child_to_delete.clone());
+        return_fiber_borrowed.flags |= Flags::ChildDeletion;
+    }
+}
+
+fn delete_remaining_children(
+    return_fiber: Rc<RefCell<FiberNode>>,
+    current_first_child: Option<Rc<RefCell<FiberNode>>>,
+    should_track_effect: bool,
+) {
+    if !should_track_effect {
+        return;
+    }
+
+    let mut child_to_delete = current_first_child;
+    while let Some(child) = child_to_delete {
+        delete_child(return_fiber.clone(), child.clone(), should_track_effect);
+        child_to_delete = child.borrow().sibling.clone();
     }
 }
 
 fn reconcile_single_element(
     return_fiber: Rc<RefCell<FiberNode>>,
     current_first_child: Option<Rc<RefCell<FiberNode>>>,
-    element: Option<JsValue>,
+    element: &ReactNode,
     should_track_effect: bool,
 ) -> Rc<RefCell<FiberNode>> {
-    if element.is_none() {
-        panic!("reconcile_single_element, element is none");
+    let key = element.element_key();
+    let mut current = current_first_child;
+    while let Some(current_child) = current {
+        if current_child.borrow().key.as_key_string() == key.as_key_string() {
+            if current_child.borrow().element_type == element.element_type() {
+                let sibling = current_child.borrow().sibling.clone();
+                delete_remaining_children(return_fiber.clone(), sibling, should_track_effect);
+                let existing = use_fiber(current_child.clone(), element.element_props());
+                existing.clone().borrow_mut()._ref = element.element_ref();
+                existing.clone().borrow_mut()._return = Some(return_fiber);
+                return existing;
+            }
+            // same key but a different type, none of the old children can be reused
+            delete_remaining_children(
+                return_fiber.clone(),
+                Some(current_child.clone()),
+                should_track_effect,
+            );
+            break;
+        } else {
+            delete_child(
+                return_fiber.clone(),
+                current_child.clone(),
+                should_track_effect,
+            );
+        }
+        current = current_child.borrow().sibling.clone();
     }
 
-    let element = element.as_ref().unwrap();
-    let key = derive_from_js_value(&(*element).clone(), "key");
-    if current_first_child.is_some() {
-        let current_first_child_cloned = current_first_child.clone().unwrap().clone();
-        if Object::is(&current_first_child_cloned.borrow().key, &key) {
-            if derive_from_js_value(&(*element).clone(), "$$typeof") != REACT_ELEMENT_TYPE {
-                panic!("Undefined $$typeof");
-            }
+    let mode = return_fiber.borrow().mode;
+    let mut fiber = FiberNode::create_fiber_from_element(element, mode);
+    fiber._return = Some(return_fiber.clone());
+    Rc::new(RefCell::new(fiber))
+}
 
-            if Object::is(
-                &current_first_child_cloned.borrow()._type,
-                &derive_from_js_value(&(*element).clone(), "type"),
-            ) {
-                let existing = use_fiber(
-                    current_first_child.clone().unwrap().clone(),
-                    derive_from_js_value(&(*element).clone(), "props"),
-                );
+fn is_same_portal_container(fiber: &Rc<RefCell<FiberNode>>, portal: &ReactNode) -> bool {
+    if fiber.borrow().tag != WorkTag::HostPortal {
+        return false;
+    }
+    match fiber.borrow().state_node.as_deref() {
+        Some(StateNode::Portal(container_info)) => {
+            *container_info == portal.portal_container_info()
+        }
+        _ => false,
+    }
+}
+
+fn reconcile_single_portal(
+    return_fiber: Rc<RefCell<FiberNode>>,
+    current_first_child: Option<Rc<RefCell<FiberNode>>>,
+    portal: &ReactNode,
+    should_track_effect: bool,
+) -> Rc<RefCell<FiberNode>> {
+    let key = portal.element_key();
+    let mut current = current_first_child;
+    while let Some(current_child) = current {
+        if current_child.borrow().key.as_key_string() == key.as_key_string() {
+            if is_same_portal_container(&current_child, portal) {
+                let sibling = current_child.borrow().sibling.clone();
+                delete_remaining_children(return_fiber.clone(), sibling, should_track_effect);
+                let existing = use_fiber(current_child.clone(), portal.portal_children());
                 existing.clone().borrow_mut()._return = Some(return_fiber);
                 return existing;
             }
-            delete_child(
+            delete_remaining_children(
                 return_fiber.clone(),
-                current_first_child.clone().unwrap().clone(),
+                Some(current_child.clone()),
                 should_track_effect,
             );
+            break;
         } else {
             delete_child(
                 return_fiber.clone(),
-                current_first_child.clone().unwrap().clone(),
+                current_child.clone(),
                 should_track_effect,
             );
         }
+        current = current_child.borrow().sibling.clone();
     }
 
-    let mut fiber = FiberNode::create_fiber_from_element(element);
+    let mode = return_fiber.borrow().mode;
+    let mut fiber = FiberNode::create_fiber_from_portal(portal, mode);
     fiber._return = Some(return_fiber.clone());
     Rc::new(RefCell::new(fiber))
 }
@@ -100,81 +171,253 @@ fn reconcile_single_element(
 fn reconcile_single_text_node(
     return_fiber: Rc<RefCell<FiberNode>>,
     current_first_child: Option<Rc<RefCell<FiberNode>>>,
-    content: Option<JsValue>,
+    content: &str,
     should_track_effect: bool,
 ) -> Rc<RefCell<FiberNode>> {
-    let props = Object::new();
-    Reflect::set(&props, &JsValue::from("content"), &content.unwrap().clone())
-        .expect("props panic");
+    // the props of a HostText fiber are its text
+    let props = Value::from_string(content);
 
-    if current_first_child.is_some()
-        && current_first_child.as_ref().unwrap().borrow().tag == WorkTag::HostText
+    if let Some(current) = current_first_child
+        .as_ref()
+        .filter(|current| current.borrow().tag == WorkTag::HostText)
     {
-        let existing = use_fiber(
-            current_first_child.clone().unwrap().clone(),
-            (*props).clone(),
-        );
+        let sibling = current.borrow().sibling.clone();
+        delete_remaining_children(return_fiber.clone(), sibling, should_track_effect);
+        let existing = use_fiber(current.clone(), props);
         existing.clone().borrow_mut()._return = Some(return_fiber.clone());
         return existing;
     }
 
-    if current_first_child.is_some() {
-        delete_child(
-            return_fiber.clone(),
-            current_first_child.clone().unwrap(),
-            should_track_effect,
-        );
-    }
+    delete_remaining_children(
+        return_fiber.clone(),
+        current_first_child,
+        should_track_effect,
+    );
 
-    let mut created = FiberNode::new(WorkTag::HostText, (*props).clone(), JsValue::null());
+    let mut created = FiberNode::new(WorkTag::HostText, props, Value::null());
+    created.mode = return_fiber.borrow().mode;
     created._return = Some(return_fiber.clone());
     Rc::new(RefCell::new(created))
 }
 
-fn _reconcile_child_fibers(
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ChildKey {
+    Key(String),
+    Index(u32),
+}
+
+fn update_from_map(
+    existing_children: &mut HashMap<ChildKey, Rc<RefCell<FiberNode>>>,
+    return_fiber: &Rc<RefCell<FiberNode>>,
+    index: u32,
+    element: &ReactNode,
+) -> Option<Rc<RefCell<FiberNode>>> {
+    let kind = element.kind();
+    let key = match kind {
+        ReactNodeKind::Element | ReactNodeKind::Portal => element.element_key(),
+        _ => Value::null(),
+    };
+    let key_to_use = match key.as_key_string() {
+        Some(key) => ChildKey::Key(key),
+        None => ChildKey::Index(index),
+    };
+    let before = existing_children.get(&key_to_use).cloned();
+    let mode = return_fiber.borrow().mode;
+
+    match kind {
+        ReactNodeKind::Text(content) => {
+            let props = Value::from_string(content);
+            if let Some(before) = before {
+                if before.borrow().tag == WorkTag::HostText {
+                    existing_children.remove(&key_to_use);
+                    return Some(use_fiber(before, props));
+                }
+            }
+            let mut created = FiberNode::new(WorkTag::HostText, props, Value::null());
+            created.mode = mode;
+            Some(Rc::new(RefCell::new(created)))
+        }
+        ReactNodeKind::List(_) => {
+            // nested lists get a fragment of their own
+            if let Some(before) = before {
+                if before.borrow().tag == WorkTag::Fragment {
+                    existing_children.remove(&key_to_use);
+                    return Some(use_fiber(before, element.clone()));
+                }
+            }
+            let mut created = FiberNode::new(WorkTag::Fragment, element.clone(), key);
+            created.mode = mode;
+            Some(Rc::new(RefCell::new(created)))
+        }
+        ReactNodeKind::Element => {
+            if let Some(before) = before {
+                if before.borrow().element_type == element.element_type() {
+                    existing_children.remove(&key_to_use);
+                    let existing = use_fiber(before, element.element_props());
+                    existing.borrow_mut()._ref = element.element_ref();
+                    return Some(existing);
+                }
+            }
+            Some(Rc::new(RefCell::new(FiberNode::create_fiber_from_element(
+                element, mode,
+            ))))
+        }
+        ReactNodeKind::Portal => {
+            if let Some(before) = before {
+                if is_same_portal_container(&before, element) {
+                    existing_children.remove(&key_to_use);
+                    return Some(use_fiber(before, element.portal_children()));
+                }
+            }
+            Some(Rc::new(RefCell::new(FiberNode::create_fiber_from_portal(
+                element, mode,
+            ))))
+        }
+        ReactNodeKind::Empty | ReactNodeKind::Unsupported => None,
+    }
+}
+
+fn reconcile_children_array(
     return_fiber: Rc<RefCell<FiberNode>>,
     current_first_child: Option<Rc<RefCell<FiberNode>>>,
-    new_child: Option<JsValue>,
+    new_children: &[ReactNode],
     should_track_effect: bool,
 ) -> Option<Rc<RefCell<FiberNode>>> {
-    if new_child.is_some() {
-        let new_child = &new_child.unwrap();
+    // the largest old index of the reused fibers, anything reused from before it has to move
+    let mut last_placed_index = 0;
+    let mut first_new_fiber: Option<Rc<RefCell<FiberNode>>> = None;
+    let mut last_new_fiber: Option<Rc<RefCell<FiberNode>>> = None;
 
-        if new_child.is_string() {
-            return Some(place_single_child(
-                reconcile_single_text_node(
+    let mut existing_children = HashMap::new();
+    let mut current = current_first_child;
+    while let Some(current_child) = current {
+        let key_to_use = match current_child.borrow().key.as_key_string() {
+            Some(key) => ChildKey::Key(key),
+            None => ChildKey::Index(current_child.borrow().index),
+        };
+        existing_children.insert(key_to_use, current_child.clone());
+        current = current_child.borrow().sibling.clone();
+    }
+
+    let mut known_keys = HashSet::new();
+    for (i, after) in new_children.iter().enumerate() {
+        let i = i as u32;
+        warn_on_invalid_key(&return_fiber, after, &mut known_keys);
+        warn_for_missing_key(&return_fiber, after);
+        let new_fiber = update_from_map(&mut existing_children, &return_fiber, i, after);
+        if new_fiber.is_none() {
+            if let ReactNodeKind::Unsupported = after.kind() {
+                warn_for_invalid_child(&return_fiber, after);
+            }
+            continue;
+        }
+
+        let new_fiber = new_fiber.unwrap();
+        {
+            let mut new_fiber = new_fiber.borrow_mut();
+            new_fiber.index = i;
+            new_fiber._return = Some(return_fiber.clone());
+            // useId derives ids from the position in a list of children
+            new_fiber.flags |= Flags::Forked;
+        }
+
+        match last_new_fiber {
+            None => first_new_fiber = Some(new_fiber.clone()),
+            Some(last_new_fiber) => last_new_fiber.borrow_mut().sibling = Some(new_fiber.clone()),
+        }
+        last_new_fiber = Some(new_fiber.clone());
+
+        if !should_track_effect {
+            continue;
+        }
+
+        let current = new_fiber.borrow().alternate.clone();
+        match current {
+            Some(current) => {
+                let old_index = current.borrow().index;
+                if old_index < last_placed_index {
+                    new_fiber.borrow_mut().flags |= Flags::Placement;
+                } else {
+                    last_placed_index = old_index;
+                }
+            }
+            None => new_fiber.borrow_mut().flags |= Flags::Placement | Flags::PlacementDEV,
+        }
+    }
+
+    for (_, fiber) in existing_children {
+        delete_child(return_fiber.clone(), fiber, should_track_effect);
+    }
+
+    push_tree_fork(return_fiber, new_children.len() as u32);
+    first_new_fiber
+}
+
+fn _reconcile_child_fibers(
+    return_fiber: Rc<RefCell<FiberNode>>,
+    current_first_child: Option<Rc<RefCell<FiberNode>>>,
+    new_child: Option<ReactNode>,
+    should_track_effect: bool,
+) -> Option<Rc<RefCell<FiberNode>>> {
+    if let Some(new_child) = new_child {
+        match new_child.kind() {
+            ReactNodeKind::Text(content) => {
+                return Some(place_single_child(
+                    reconcile_single_text_node(
+                        return_fiber,
+                        current_first_child,
+                        &content,
+                        should_track_effect,
+                    ),
+                    should_track_effect,
+                ));
+            }
+            ReactNodeKind::List(new_children) => {
+                return reconcile_children_array(
                     return_fiber,
                     current_first_child,
-                    Some(new_child.clone()),
+                    &new_children,
                     should_track_effect,
-                ),
-                should_track_effect,
-            ));
-        } else if new_child.is_object() {
-            if let Some(_typeof) = derive_from_js_value(&new_child, "$$typeof").as_string() {
-                if _typeof == REACT_ELEMENT_TYPE {
-                    return Some(place_single_child(
-                        reconcile_single_element(
-                            return_fiber,
-                            current_first_child,
-                            Some(new_child.clone()),
-                            should_track_effect,
-                        ),
+                );
+            }
+            ReactNodeKind::Element => {
+                return Some(place_single_child(
+                    reconcile_single_element(
+                        return_fiber,
+                        current_first_child,
+                        &new_child,
                         should_track_effect,
-                    ));
-                }
+                    ),
+                    should_track_effect,
+                ));
+            }
+            ReactNodeKind::Portal => {
+                return Some(place_single_child(
+                    reconcile_single_portal(
+                        return_fiber,
+                        current_first_child,
+                        &new_child,
+                        should_track_effect,
+                    ),
+                    should_track_effect,
+                ));
+            }
+            ReactNodeKind::Unsupported => {
+                warn_for_invalid_child(&return_fiber, &new_child);
             }
+            ReactNodeKind::Empty => {}
         }
     }
 
-    log!("Unsupported child type when reconcile");
+    // nothing to render, e.g. null, undefined or false
+    delete_remaining_children(return_fiber, current_first_child, should_track_effect);
     None
 }
 
 pub fn reconcile_child_fibers(
     return_fiber: Rc<RefCell<FiberNode>>,
     current_first_child: Option<Rc<RefCell<FiberNode>>>,
-    new_child: Option<JsValue>,
+    new_child: Option<ReactNode>,
 ) -> Option<Rc<RefCell<FiberNode>>> {
     _reconcile_child_fibers(return_fiber, current_first_child, new_child, true)
 }
@@ -182,7 +425,7 @@ pub fn reconcile_child_fibers(
 pub fn mount_child_fibers(
     return_fiber: Rc<RefCell<FiberNode>>,
     current_first_child: Option<Rc<RefCell<FiberNode>>>,
-    new_child: Option<JsValue>,
+    new_child: Option<ReactNode>,
 ) -> Option<Rc<RefCell<FiberNode>>> {
     _reconcile_child_fibers(return_fiber, current_first_child, new_child, false)
 }
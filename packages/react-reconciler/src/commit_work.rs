@@ -1,10 +1,16 @@
 use std::{any::Any, cell::RefCell, rc::Rc};
 
-use shared::{derive_from_js_value, log};
+use shared::{error, warn};
 
 use crate::{
-    fiber::{FiberNode, StateNode},
-    fiber_flags::{get_mutation_mask, Flags},
+    complete_work::CompleteWork,
+    fiber::{FiberNode, FiberRootNode, StateNode},
+    fiber_flags::{get_layout_mask, get_mutation_mask, get_passive_mask, Flags},
+    fiber_hooks::Effect,
+    hook_effect_tags::HookEffectTags,
+    profiler_timer::get_commit_time,
+    react_value::Value,
+    type_of_mode::TypeOfMode,
     work_tags::WorkTag,
     HostConfig,
 };
@@ -22,18 +28,63 @@ impl CommitWork {
         }
     }
 
-    pub fn commit_mutation_effects(&mut self, finished_work: Rc<RefCell<FiberNode>>) {
+    pub fn commit_mutation_effects(
+        &mut self,
+        finished_work: Rc<RefCell<FiberNode>>,
+        root: Rc<RefCell<FiberRootNode>>,
+    ) {
+        self.next_effect = Some(finished_work);
+        while self.next_effect.is_some() {
+            let next_effect = self.next_effect.clone().unwrap().clone();
+            let child = next_effect.borrow().child.clone();
+            if child.is_some()
+                && (get_mutation_mask() | get_passive_mask())
+                    .intersects(next_effect.borrow().subtree_flags.clone())
+            {
+                self.next_effect = child;
+            } else {
+                while self.next_effect.is_some() {
+                    self.commit_mutation_effects_on_fiber(
+                        self.next_effect.clone().unwrap(),
+                        root.clone(),
+                    );
+                    let sibling = self.next_effect.clone().unwrap().borrow().sibling.clone();
+                    if sibling.is_some() {
+                        self.next_effect = sibling;
+                        break;
+                    }
+
+                    let _return = self
+                        .next_effect
+                        .clone()
+                        .unwrap()
+                        .clone()
+                        .borrow()
+                        ._return
+                        .clone();
+
+                    if _return.is_none() {
+                        self.next_effect = None;
+                    } else {
+                        self.next_effect = _return;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn commit_layout_effects(&mut self, finished_work: Rc<RefCell<FiberNode>>) {
         self.next_effect = Some(finished_work);
         while self.next_effect.is_some() {
             let next_effect = self.next_effect.clone().unwrap().clone();
             let child = next_effect.borrow().child.clone();
             if child.is_some()
-                && get_mutation_mask().contains(next_effect.borrow().subtree_flags.clone())
+                && get_layout_mask().intersects(next_effect.borrow().subtree_flags.clone())
             {
                 self.next_effect = child;
             } else {
                 while self.next_effect.is_some() {
-                    self.commit_mutation_effects_on_fiber(self.next_effect.clone().unwrap());
+                    self.commit_layout_effects_on_fiber(self.next_effect.clone().unwrap());
                     let sibling = self.next_effect.clone().unwrap().borrow().sibling.clone();
                     if sibling.is_some() {
                         self.next_effect = sibling;
@@ -59,142 +110,465 @@ impl CommitWork {
         }
     }
 
-    fn commit_mutation_effects_on_fiber(&self, finished_work: Rc<RefCell<FiberNode>>) {
+    fn commit_layout_effects_on_fiber(&self, finished_work: Rc<RefCell<FiberNode>>) {
+        let tag = finished_work.borrow().tag.clone();
+        match tag {
+            WorkTag::FunctionComponent | WorkTag::ForwardRef => {
+                // the Update flag was consumed by the mutation phase, HasEffect tells what to run
+                let last_effect = get_last_effect(&finished_work);
+                if let Some(last_effect) = last_effect {
+                    commit_hook_effect_list_mount(
+                        HookEffectTags::Layout | HookEffectTags::HasEffect,
+                        last_effect,
+                    );
+                }
+            }
+            WorkTag::Profiler => self.commit_profiler_update(finished_work.clone()),
+            WorkTag::HostComponent if finished_work.borrow().flags.contains(Flags::Ref) => {
+                self.commit_attach_ref(finished_work.clone());
+                finished_work.borrow_mut().flags -= Flags::Ref;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn commit_double_invoke_effects_in_dev(
+        &self,
+        fiber: Rc<RefCell<FiberNode>>,
+        root: Rc<RefCell<FiberRootNode>>,
+        is_placed: bool,
+    ) {
+        // StrictMode unmounts and mounts the effects of new components once more,
+        // to surface effects that don't clean up after themselves
+        let is_placed = is_placed || fiber.borrow().flags.contains(Flags::PlacementDEV);
+        fiber.borrow_mut().flags -= Flags::PlacementDEV;
+
+        let (tag, mode) = {
+            let fiber = fiber.borrow();
+            (fiber.tag.clone(), fiber.mode)
+        };
+        if is_placed
+            && mode.contains(TypeOfMode::StrictEffectsMode)
+            && (tag == WorkTag::FunctionComponent || tag == WorkTag::ForwardRef)
+        {
+            if let Some(last_effect) = get_last_effect(&fiber) {
+                commit_hook_effect_list_destroy(
+                    HookEffectTags::Layout | HookEffectTags::HasEffect,
+                    last_effect.clone(),
+                );
+                commit_hook_effect_list_mount(
+                    HookEffectTags::Layout | HookEffectTags::HasEffect,
+                    last_effect.clone(),
+                );
+                // passive effects haven't run yet, they are replayed when they are flushed
+                let pending_passive_effects = root.borrow().pending_passive_effects.clone();
+                pending_passive_effects
+                    .borrow_mut()
+                    .remount
+                    .push(last_effect);
+            }
+        }
+
+        let mut child = fiber.borrow().child.clone();
+        while let Some(node) = child {
+            self.commit_double_invoke_effects_in_dev(node.clone(), root.clone(), is_placed);
+            child = node.borrow().sibling.clone();
+        }
+    }
+
+    fn commit_profiler_update(&self, finished_work: Rc<RefCell<FiberNode>>) {
+        let finished_work = finished_work.borrow();
+        let on_render = finished_work.memoized_props.get("onRender");
+        if on_render.is_function() {
+            let phase = match finished_work.alternate {
+                None => "mount",
+                Some(_) => "update",
+            };
+            let args = [
+                finished_work.memoized_props.get("id"),
+                Value::from_string(phase),
+                Value::from_f64(finished_work.actual_duration),
+                Value::from_f64(finished_work.tree_base_duration),
+                Value::from_f64(finished_work.actual_start_time),
+                Value::from_f64(get_commit_time()),
+            ];
+            if let Err(e) = on_render.call(&args) {
+                error!(Commit, "onRender of Profiler failed {:?}", e);
+            }
+        }
+    }
+
+    fn commit_attach_ref(&self, fiber: Rc<RefCell<FiberNode>>) {
+        let _ref = fiber.borrow()._ref.clone();
+        if _ref.is_nullish() {
+            return;
+        }
+
+        let instance = FiberNode::derive_state_node(fiber.clone()).unwrap();
+        set_ref(&_ref, self.host_config.get_public_instance(instance));
+    }
+
+    fn commit_mutation_effects_on_fiber(
+        &self,
+        finished_work: Rc<RefCell<FiberNode>>,
+        root: Rc<RefCell<FiberRootNode>>,
+    ) {
         let flags = finished_work.clone().borrow().flags.clone();
+        // persistent hosts got their new trees in the complete phase, only containers change
+        let is_persistent = self.host_config.persistent().is_some();
         if flags.contains(Flags::Placement) {
-            self.commit_placement(finished_work.clone());
+            if !is_persistent {
+                self.commit_placement(finished_work.clone());
+            }
             finished_work.clone().borrow_mut().flags -= Flags::Placement;
         }
 
         if flags.contains(Flags::ChildDeletion) {
             let deletions = finished_work.clone().borrow().deletions.clone();
-            if deletions.is_some() {
-                let deletions = deletions.unwrap();
+            if let Some(deletions) = deletions {
                 for child_to_delete in deletions {
-                    self.commit_deletion(child_to_delete);
+                    self.commit_deletion(child_to_delete, root.clone());
                 }
             }
             finished_work.clone().borrow_mut().flags -= Flags::ChildDeletion;
         }
 
+        if flags.contains(Flags::ContentReset) {
+            if is_persistent {
+                finished_work.borrow_mut().flags -= Flags::ContentReset;
+            } else {
+                self.commit_reset_text_content(finished_work.clone());
+            }
+        }
+
         if flags.contains(Flags::Update) {
             self.commit_update(finished_work.clone());
             finished_work.clone().borrow_mut().flags -= Flags::Update;
         }
+
+        if flags.contains(Flags::Ref) {
+            // the new ref is attached in the layout phase
+            let current = finished_work.borrow().alternate.clone();
+            if let Some(current) = current {
+                safely_detach_ref(current);
+            }
+        }
+
+        if flags.contains(Flags::PassiveEffect) {
+            self.commit_passive_effect(finished_work.clone(), root.clone(), false);
+            finished_work.clone().borrow_mut().flags -= Flags::PassiveEffect;
+        }
+
+        if flags.contains(Flags::Visibility) {
+            let is_hidden = CompleteWork::is_offscreen_hidden(&finished_work);
+            // persistent hosts got hidden clones instead
+            if !is_persistent {
+                self.hide_or_unhide_all_children(finished_work.clone(), is_hidden);
+            }
+            finished_work.clone().borrow_mut().flags -= Flags::Visibility;
+        }
+    }
+
+    fn hide_or_unhide_all_children(&self, finished_work: Rc<RefCell<FiberNode>>, is_hidden: bool) {
+        // only the top level host nodes of the subtree need to be toggled
+        let mut child = finished_work.borrow().child.clone();
+        while let Some(node) = child {
+            let tag = node.borrow().tag.clone();
+            match tag {
+                WorkTag::HostComponent => {
+                    let instance = FiberNode::derive_state_node(node.clone()).unwrap();
+                    if is_hidden {
+                        self.host_config.hide_instance(instance);
+                    } else {
+                        self.host_config.unhide_instance(instance);
+                    }
+                }
+                WorkTag::HostText => {
+                    let text_instance = FiberNode::derive_state_node(node.clone()).unwrap();
+                    if is_hidden {
+                        self.host_config.hide_text_instance(text_instance);
+                    } else {
+                        let content = node.borrow().memoized_props.text_content().unwrap();
+                        self.host_config
+                            .unhide_text_instance(text_instance, content);
+                    }
+                }
+                WorkTag::OffscreenComponent if CompleteWork::is_offscreen_hidden(&node) => {
+                    // nested hidden trees keep their own visibility
+                }
+                _ => self.hide_or_unhide_all_children(node.clone(), is_hidden),
+            }
+            child = node.borrow().sibling.clone();
+        }
+    }
+
+    fn commit_passive_effect(
+        &self,
+        fiber: Rc<RefCell<FiberNode>>,
+        root: Rc<RefCell<FiberRootNode>>,
+        is_unmount: bool,
+    ) {
+        if let Some(last_effect) = get_last_effect(&fiber) {
+            let pending_passive_effects = root.borrow().pending_passive_effects.clone();
+            if is_unmount {
+                pending_passive_effects
+                    .borrow_mut()
+                    .unmount
+                    .push(last_effect);
+            } else {
+                pending_passive_effects
+                    .borrow_mut()
+                    .update
+                    .push(last_effect);
+            }
+        }
     }
 
     fn commit_update(&self, finished_work: Rc<RefCell<FiberNode>>) {
         let cloned = finished_work.clone();
+        if let Some(persistent) = self.host_config.persistent() {
+            let tag = cloned.borrow().tag.clone();
+            match tag {
+                WorkTag::HostRoot | WorkTag::HostPortal => {
+                    let pending_children = cloned.borrow_mut().pending_children.take();
+                    if let Some(pending_children) = pending_children {
+                        let container = self.get_host_parent_container(finished_work.clone());
+                        persistent.replace_container_children(container, pending_children);
+                    }
+                    return;
+                }
+                // the clones already have the new props and text
+                WorkTag::HostText | WorkTag::HostComponent => return,
+                _ => {}
+            }
+        }
         match cloned.borrow().tag {
             WorkTag::HostText => {
-                let new_content = derive_from_js_value(&cloned.borrow().pending_props, "content");
+                let new_content = cloned.borrow().pending_props.text_content().unwrap();
                 let state_node = FiberNode::derive_state_node(finished_work.clone());
                 if let Some(state_node) = state_node.clone() {
                     self.host_config
-                        .commit_text_update(state_node.clone(), new_content.as_string().unwrap());
+                        .commit_text_update(state_node.clone(), new_content);
                 }
             }
-            _ => log!("commit_update, unsupported type"),
-        };
-    }
-
-    fn commit_deletion(&self, child_to_delete: Rc<RefCell<FiberNode>>) {
-        let first_host_fiber: Rc<RefCell<Option<Rc<RefCell<FiberNode>>>>> =
-            Rc::new(RefCell::new(None));
-        self.commit_nested_unmounts(child_to_delete.clone(), |unmount_fiber| {
-            let cloned = first_host_fiber.clone();
-            match unmount_fiber.borrow().tag {
-                WorkTag::FunctionComponent => {}
-                WorkTag::HostRoot => {}
-                WorkTag::HostComponent => {
-                    if cloned.borrow().is_none() {
-                        *cloned.borrow_mut() = Some(unmount_fiber.clone());
-                    }
-                }
-                WorkTag::HostText => {
-                    if cloned.borrow().is_none() {
-                        *cloned.borrow_mut() = Some(unmount_fiber.clone());
-                    }
+            WorkTag::HostComponent => {
+                let instance = FiberNode::derive_state_node(finished_work.clone()).unwrap();
+                let _type = cloned.borrow()._type.as_string().unwrap();
+                let new_props = cloned.borrow().memoized_props.clone();
+                let old_props = match cloned.borrow().alternate.clone() {
+                    Some(current) => current.borrow().memoized_props.clone(),
+                    None => Value::null(),
+                };
+                self.host_config
+                    .commit_update(instance, _type, &old_props, &new_props);
+            }
+            WorkTag::FunctionComponent | WorkTag::ForwardRef => {
+                // insertion effects run before any layout effect can read the dom,
+                // layout effects of the previous commit are destroyed before the dom changes
+                if let Some(last_effect) = get_last_effect(&finished_work) {
+                    commit_hook_effect_list_destroy(
+                        HookEffectTags::Insertion | HookEffectTags::HasEffect,
+                        last_effect.clone(),
+                    );
+                    commit_hook_effect_list_mount(
+                        HookEffectTags::Insertion | HookEffectTags::HasEffect,
+                        last_effect.clone(),
+                    );
+                    commit_hook_effect_list_destroy(
+                        HookEffectTags::Layout | HookEffectTags::HasEffect,
+                        last_effect,
+                    );
                 }
             }
-        });
+            // onRender waits for the layout phase, when the durations of the subtree are final
+            WorkTag::Profiler => {}
+            _ => warn!(Commit, "commit_update, unsupported type"),
+        };
+    }
 
-        let first_host_fiber = first_host_fiber.clone();
-        if first_host_fiber.borrow().is_some() {
-            let host_parent_state_node = FiberNode::derive_state_node(
-                self.get_host_parent(child_to_delete.clone()).unwrap(),
-            );
-            let first_host_fiber_state_node =
-                FiberNode::derive_state_node((*first_host_fiber.borrow()).clone().unwrap());
-            self.host_config.remove_child(
-                first_host_fiber_state_node.unwrap(),
-                host_parent_state_node.unwrap(),
-            )
-        }
+    fn commit_deletion(
+        &self,
+        child_to_delete: Rc<RefCell<FiberNode>>,
+        root: Rc<RefCell<FiberRootNode>>,
+    ) {
+        // persistent hosts leave the deleted nodes out of the next child set instead
+        let host_parent = match self.host_config.persistent() {
+            Some(_) => None,
+            None => self
+                .get_host_parent(child_to_delete.clone())
+                .map(|host_parent| self.get_host_parent_container(host_parent)),
+        };
+        self.commit_deletion_effects_on_fiber(child_to_delete.clone(), host_parent, root);
 
         child_to_delete.clone().borrow_mut()._return = None;
         child_to_delete.clone().borrow_mut().child = None;
+        // setState of the deleted subtree can reach the root through either fiber
+        let alternate = child_to_delete.borrow().alternate.clone();
+        if let Some(alternate) = alternate {
+            alternate.borrow_mut()._return = None;
+        }
     }
 
-    fn commit_nested_unmounts<F>(&self, root: Rc<RefCell<FiberNode>>, on_commit_unmount: F)
-    where
-        F: Fn(Rc<RefCell<FiberNode>>),
-    {
-        let mut node = root.clone();
-        loop {
-            on_commit_unmount(node.clone());
-
-            let node_cloned = node.clone();
-            if node_cloned.borrow().child.is_some() {
-                node_cloned
-                    .borrow_mut()
-                    .child
-                    .clone()
-                    .unwrap()
-                    .clone()
-                    .borrow_mut()
-                    ._return = Some(node.clone());
-                node = node_cloned.borrow().child.clone().unwrap();
-                continue;
-            }
+    fn recursively_traverse_deletion_effects(
+        &self,
+        parent: Rc<RefCell<FiberNode>>,
+        host_parent: Option<Rc<dyn Any>>,
+        root: Rc<RefCell<FiberRootNode>>,
+    ) {
+        let mut child = parent.borrow().child.clone();
+        while let Some(node) = child {
+            self.commit_deletion_effects_on_fiber(node.clone(), host_parent.clone(), root.clone());
+            child = node.borrow().sibling.clone();
+        }
+    }
 
-            if Rc::ptr_eq(&node, &root.clone()) {
-                return;
+    fn commit_deletion_effects_on_fiber(
+        &self,
+        deleted_fiber: Rc<RefCell<FiberNode>>,
+        host_parent: Option<Rc<dyn Any>>,
+        root: Rc<RefCell<FiberRootNode>>,
+    ) {
+        let tag = deleted_fiber.borrow().tag.clone();
+        match tag {
+            WorkTag::HostComponent | WorkTag::HostText => {
+                if tag == WorkTag::HostComponent {
+                    safely_detach_ref(deleted_fiber.clone());
+                }
+                // nested host nodes leave together with this one
+                self.recursively_traverse_deletion_effects(deleted_fiber.clone(), None, root);
+                if let Some(host_parent) = host_parent {
+                    let state_node = FiberNode::derive_state_node(deleted_fiber.clone()).unwrap();
+                    self.host_config.remove_child(state_node, host_parent);
+                }
             }
-
-            while node.clone().borrow().sibling.is_none() {
-                if node.clone().borrow()._return.is_none()
-                    || Rc::ptr_eq(node.clone().borrow()._return.as_ref().unwrap(), &root)
-                {
+            WorkTag::HostPortal => {
+                // the children of a portal are removed from its own container
+                let container = self.get_host_parent_container(deleted_fiber.clone());
+                if let Some(persistent) = self.host_config.persistent() {
+                    let empty_child_set = persistent.create_container_child_set(container.clone());
+                    persistent.replace_container_children(container, empty_child_set);
+                    self.recursively_traverse_deletion_effects(deleted_fiber.clone(), None, root);
                     return;
                 }
-                node = node_cloned.borrow()._return.clone().unwrap();
+                self.recursively_traverse_deletion_effects(
+                    deleted_fiber.clone(),
+                    Some(container),
+                    root,
+                );
+            }
+            WorkTag::FunctionComponent | WorkTag::ForwardRef => {
+                if let Some(last_effect) = get_last_effect(&deleted_fiber) {
+                    commit_hook_effect_list_unmount(HookEffectTags::Insertion, last_effect.clone());
+                    commit_hook_effect_list_unmount(HookEffectTags::Layout, last_effect);
+                }
+                self.commit_passive_effect(deleted_fiber.clone(), root.clone(), true);
+                self.recursively_traverse_deletion_effects(
+                    deleted_fiber.clone(),
+                    host_parent,
+                    root,
+                );
+            }
+            WorkTag::HostRoot
+            | WorkTag::Fragment
+            | WorkTag::Mode
+            | WorkTag::Profiler
+            | WorkTag::ContextProvider
+            | WorkTag::LazyComponent
+            | WorkTag::SuspenseComponent
+            | WorkTag::OffscreenComponent => {
+                self.recursively_traverse_deletion_effects(
+                    deleted_fiber.clone(),
+                    host_parent,
+                    root,
+                );
             }
-
-            node_cloned
-                .borrow_mut()
-                .sibling
-                .clone()
-                .unwrap()
-                .clone()
-                .borrow_mut()
-                ._return = node_cloned.borrow()._return.clone();
-            node = node_cloned.borrow().sibling.clone().unwrap();
         }
     }
 
+    fn commit_reset_text_content(&self, fiber: Rc<RefCell<FiberNode>>) {
+        let instance = FiberNode::derive_state_node(fiber.clone()).unwrap();
+        self.host_config.reset_text_content(instance);
+        fiber.borrow_mut().flags -= Flags::ContentReset;
+    }
+
     fn commit_placement(&self, finished_work: Rc<RefCell<FiberNode>>) {
         let host_parent = self.get_host_parent(finished_work.clone());
         if host_parent.is_none() {
             return;
         }
 
-        let parent_state_node = FiberNode::derive_state_node(host_parent.unwrap());
-        if parent_state_node.is_some() {
-            self.append_placement_node_into_container(
-                finished_work.clone(),
-                parent_state_node.unwrap(),
-            );
+        let host_parent = host_parent.unwrap();
+        if host_parent.borrow().tag == WorkTag::HostPortal {
+            // events inside the portal bubble to where the portal is in the react tree
+            let portal_container = self.get_host_parent_container(host_parent.clone());
+            if let Some(outer_parent) = self.get_host_parent(host_parent.clone()) {
+                let outer_parent_container = self.get_host_parent_container(outer_parent);
+                self.host_config
+                    .prepare_portal_mount(portal_container, outer_parent_container);
+            }
+        }
+        if host_parent.borrow().flags.contains(Flags::ContentReset) {
+            // the old content has to go before the new children are inserted
+            self.commit_reset_text_content(host_parent.clone());
+        }
+        let parent_state_node = self.get_host_parent_container(host_parent);
+        // with a list of children the node may have to go in front of an existing one
+        let before = self.get_host_sibling(finished_work.clone());
+        self.insert_or_append_placement_node_into_container(
+            finished_work.clone(),
+            parent_state_node,
+            before,
+        );
+    }
+
+    fn get_host_sibling(&self, fiber: Rc<RefCell<FiberNode>>) -> Option<Rc<dyn Any>> {
+        let mut node = fiber;
+        'find_sibling: loop {
+            while node.borrow().sibling.is_none() {
+                let parent = node.borrow()._return.clone();
+                match parent {
+                    None => return None,
+                    Some(parent) => {
+                        let parent_tag = parent.borrow().tag.clone();
+                        if parent_tag == WorkTag::HostComponent
+                            || parent_tag == WorkTag::HostRoot
+                            || parent_tag == WorkTag::HostPortal
+                        {
+                            return None;
+                        }
+                        node = parent;
+                    }
+                }
+            }
+
+            let sibling = node.borrow().sibling.clone().unwrap();
+            sibling.borrow_mut()._return = node.borrow()._return.clone();
+            node = sibling;
+
+            while node.borrow().tag != WorkTag::HostText
+                && node.borrow().tag != WorkTag::HostComponent
+            {
+                // a node that is moved or inserted itself can't be the anchor
+                if node.borrow().flags.contains(Flags::Placement) {
+                    continue 'find_sibling;
+                }
+                // nothing inside a portal lives in the same container
+                if node.borrow().tag == WorkTag::HostPortal {
+                    continue 'find_sibling;
+                }
+                let child = node.borrow().child.clone();
+                match child {
+                    None => continue 'find_sibling,
+                    Some(child) => {
+                        child.borrow_mut()._return = Some(node.clone());
+                        node = child;
+                    }
+                }
+            }
+
+            if !node.borrow().flags.contains(Flags::Placement) {
+                return FiberNode::derive_state_node(node.clone());
+            }
         }
     }
 
@@ -202,31 +576,58 @@ impl CommitWork {
         match &*state_node {
             StateNode::FiberRootNode(root) => root.clone().borrow().container.clone(),
             StateNode::Element(ele) => ele.clone(),
+            StateNode::Portal(container_info) => {
+                self.host_config.get_portal_container(container_info)
+            }
         }
     }
 
-    fn append_placement_node_into_container(
+    fn get_host_parent_container(&self, host_parent: Rc<RefCell<FiberNode>>) -> Rc<dyn Any> {
+        let state_node = host_parent.borrow().state_node.clone().unwrap();
+        self.get_element_from_state_node(state_node)
+    }
+
+    fn insert_or_append_placement_node_into_container(
         &self,
         fiber: Rc<RefCell<FiberNode>>,
         parent: Rc<dyn Any>,
+        before: Option<Rc<dyn Any>>,
     ) {
         let fiber = fiber.clone();
         let tag = fiber.borrow().tag.clone();
         if tag == WorkTag::HostComponent || tag == WorkTag::HostText {
             let state_node = fiber.clone().borrow().state_node.clone().unwrap();
-            self.host_config.append_child_to_container(
-                self.get_element_from_state_node(state_node),
-                parent.clone(),
-            );
+            let child = self.get_element_from_state_node(state_node);
+            match before {
+                Some(before) => {
+                    self.host_config
+                        .insert_child_to_container(child, parent.clone(), before)
+                }
+                None => self
+                    .host_config
+                    .append_child_to_container(child, parent.clone()),
+            }
+            return;
+        }
+        if tag == WorkTag::HostPortal {
+            // a portal that is inserted keeps its children in its own container
             return;
         }
 
         let child = fiber.borrow().child.clone();
-        if child.is_some() {
-            self.append_placement_node_into_container(child.clone().unwrap(), parent.clone());
-            let mut sibling = child.unwrap().clone().borrow().sibling.clone();
+        if let Some(child) = child {
+            self.insert_or_append_placement_node_into_container(
+                child.clone(),
+                parent.clone(),
+                before.clone(),
+            );
+            let mut sibling = child.borrow().sibling.clone();
             while sibling.is_some() {
-                self.append_placement_node_into_container(sibling.clone().unwrap(), parent.clone());
+                self.insert_or_append_placement_node_into_container(
+                    sibling.clone().unwrap(),
+                    parent.clone(),
+                    before.clone(),
+                );
                 sibling = sibling.clone().unwrap().clone().borrow().sibling.clone();
             }
         }
@@ -237,12 +638,89 @@ impl CommitWork {
         while parent.is_some() {
             let p = parent.clone().unwrap();
             let parent_tag = p.borrow().tag.clone();
-            if parent_tag == WorkTag::HostComponent || parent_tag == WorkTag::HostRoot {
+            if parent_tag == WorkTag::HostComponent
+                || parent_tag == WorkTag::HostRoot
+                || parent_tag == WorkTag::HostPortal
+            {
                 return Some(p);
             }
             parent = p.borrow()._return.clone();
         }
-        
+
         None
     }
 }
+
+fn get_last_effect(fiber: &Rc<RefCell<FiberNode>>) -> Option<Rc<RefCell<Effect>>> {
+    let update_queue = fiber.borrow().update_queue.clone();
+    match update_queue {
+        Some(update_queue) => update_queue.borrow().last_effect.clone(),
+        None => None,
+    }
+}
+
+fn safely_detach_ref(current: Rc<RefCell<FiberNode>>) {
+    let _ref = current.borrow()._ref.clone();
+    if !_ref.is_nullish() {
+        set_ref(&_ref, Value::null());
+    }
+}
+
+fn set_ref(_ref: &Value, value: Value) {
+    if _ref.is_function() {
+        if let Err(e) = _ref.call(&[value]) {
+            error!(Commit, "ref callback failed {:?}", e);
+        }
+    } else if let Err(e) = _ref.set("current", value) {
+        error!(Commit, "set ref.current failed {:?}", e);
+    }
+}
+
+fn commit_hook_effect_list<F>(flags: HookEffectTags, last_effect: Rc<RefCell<Effect>>, callback: F)
+where
+    F: Fn(Rc<RefCell<Effect>>),
+{
+    let first_effect = last_effect.borrow().next.clone().unwrap();
+    let mut effect = first_effect.clone();
+    loop {
+        if effect.borrow().tag.contains(flags) {
+            callback(effect.clone());
+        }
+        let next = effect.borrow().next.clone().unwrap();
+        if Rc::ptr_eq(&next, &first_effect) {
+            break;
+        }
+        effect = next;
+    }
+}
+
+fn call_destroy(effect: &Rc<RefCell<Effect>>) {
+    let destroy = effect.borrow().destroy.clone();
+    if destroy.is_function() {
+        if let Err(e) = destroy.call(&[]) {
+            error!(Commit, "destroy of effect failed {:?}", e);
+        }
+    }
+}
+
+pub fn commit_hook_effect_list_unmount(flags: HookEffectTags, last_effect: Rc<RefCell<Effect>>) {
+    commit_hook_effect_list(flags, last_effect, |effect| {
+        call_destroy(&effect);
+        // the fiber is gone, its effects must not be mounted again
+        effect.borrow_mut().tag -= HookEffectTags::HasEffect;
+    });
+}
+
+pub fn commit_hook_effect_list_destroy(flags: HookEffectTags, last_effect: Rc<RefCell<Effect>>) {
+    commit_hook_effect_list(flags, last_effect, |effect| call_destroy(&effect));
+}
+
+pub fn commit_hook_effect_list_mount(flags: HookEffectTags, last_effect: Rc<RefCell<Effect>>) {
+    commit_hook_effect_list(flags, last_effect, |effect| {
+        let create = effect.borrow().create.clone();
+        match create.call(&[]) {
+            Ok(destroy) => effect.borrow_mut().destroy = destroy,
+            Err(e) => error!(Commit, "create of effect failed {:?}", e),
+        }
+    });
+}
@@ -1,13 +1,15 @@
 use std::{any::Any, cell::RefCell, rc::Rc};
 
-use shared::{derive_from_js_value, log};
-use web_sys::js_sys::Object;
-
 use crate::{
     fiber::{FiberNode, StateNode},
-    fiber_flags::Flags,
+    fiber_context::pop_provider,
+    fiber_flags::{get_mutation_mask, Flags},
+    fiber_host_context::{get_host_context, pop_host_container, pop_host_context},
+    fiber_tree_context::pop_tree_context,
+    react_value::Value,
+    type_of_mode::TypeOfMode,
     work_tags::WorkTag,
-    HostConfig,
+    HostConfig, PersistentHostConfig,
 };
 
 pub struct CompleteWork {
@@ -30,6 +32,8 @@ impl CompleteWork {
                     parent.clone(),
                     FiberNode::derive_state_node(node.clone().unwrap()).unwrap(),
                 )
+            } else if n.borrow().tag == WorkTag::HostPortal {
+                // the children of a portal go into its own container
             } else if n.borrow().child.is_some() {
                 let n = node_unwrap.clone();
                 {
@@ -62,7 +66,7 @@ impl CompleteWork {
                 let node_cloned = node.clone().unwrap().clone();
                 if node_cloned.borrow()._return.is_none()
                     || Rc::ptr_eq(
-                        &node_cloned.borrow()._return.as_ref().unwrap(),
+                        node_cloned.borrow()._return.as_ref().unwrap(),
                         &work_in_progress,
                     )
                 {
@@ -80,7 +84,7 @@ impl CompleteWork {
                     .unwrap()
                     .clone()
                     .borrow_mut()
-                    ._return = node_unwrap.borrow()._return.clone();
+                    ._return = node.clone().unwrap().borrow()._return.clone();
             }
 
             node = node.clone().unwrap().borrow().sibling.clone();
@@ -105,12 +109,199 @@ impl CompleteWork {
             }
         }
         complete_work.clone().borrow_mut().subtree_flags |= subtree_flags.clone();
+
+        if complete_work
+            .borrow()
+            .mode
+            .contains(TypeOfMode::ProfileMode)
+        {
+            // a Profiler reports the time spent in its whole subtree
+            let mut child = complete_work.borrow().child.clone();
+            let mut complete_work = complete_work.borrow_mut();
+            let mut actual_duration = complete_work.actual_duration;
+            let mut tree_base_duration = complete_work.self_base_duration;
+            while let Some(child_rc) = child {
+                actual_duration += child_rc.borrow().actual_duration;
+                tree_base_duration += child_rc.borrow().tree_base_duration;
+                child = child_rc.borrow().sibling.clone();
+            }
+            complete_work.actual_duration = actual_duration;
+            complete_work.tree_base_duration = tree_base_duration;
+        }
+    }
+
+    // persistent mode, appends the host nodes of this render below fiber, the ones in a
+    // hidden Offscreen as hidden clones
+    fn append_all_children_persistent<F>(
+        &self,
+        persistent: &dyn PersistentHostConfig,
+        append: &F,
+        fiber: Rc<RefCell<FiberNode>>,
+        is_hidden: bool,
+    ) where
+        F: Fn(Rc<dyn Any>),
+    {
+        let mut child = fiber.borrow().child.clone();
+        while let Some(node) = child {
+            let tag = node.borrow().tag.clone();
+            match tag {
+                WorkTag::HostComponent => {
+                    let instance = FiberNode::derive_state_node(node.clone()).unwrap();
+                    if is_hidden {
+                        let _type = node.borrow()._type.as_host_type().unwrap();
+                        let props = node.borrow().memoized_props.clone();
+                        append(persistent.clone_hidden_instance(instance, _type, &props));
+                    } else {
+                        append(instance);
+                    }
+                }
+                WorkTag::HostText => {
+                    let text_instance = FiberNode::derive_state_node(node.clone()).unwrap();
+                    if is_hidden {
+                        let content = node.borrow().memoized_props.text_content().unwrap();
+                        append(persistent.clone_hidden_text_instance(text_instance, content));
+                    } else {
+                        append(text_instance);
+                    }
+                }
+                // the children of a portal go into its own container
+                WorkTag::HostPortal => {}
+                WorkTag::OffscreenComponent => {
+                    let is_hidden = is_hidden || CompleteWork::is_offscreen_hidden(&node);
+                    self.append_all_children_persistent(
+                        persistent,
+                        append,
+                        node.clone(),
+                        is_hidden,
+                    );
+                }
+                _ => {
+                    self.append_all_children_persistent(persistent, append, node.clone(), is_hidden)
+                }
+            }
+            child = node.borrow().sibling.clone();
+        }
+    }
+
+    // whether the host nodes below work_in_progress are the same ones as in the current tree
+    fn had_no_mutations_effects(
+        current: &Option<Rc<RefCell<FiberNode>>>,
+        work_in_progress: &Rc<RefCell<FiberNode>>,
+    ) -> bool {
+        let current = match current {
+            Some(current) => current,
+            // mounted in this render, e.g. a new portal
+            None => return false,
+        };
+        // bailed out, the children weren't even cloned
+        if let (Some(current_child), Some(child)) =
+            (&current.borrow().child, &work_in_progress.borrow().child)
+        {
+            if Rc::ptr_eq(current_child, child) {
+                return true;
+            }
+        }
+        if work_in_progress
+            .borrow()
+            .flags
+            .contains(Flags::ChildDeletion)
+        {
+            return false;
+        }
+        let mut child = work_in_progress.borrow().child.clone();
+        while let Some(node) = child {
+            let flags = node.borrow().flags.clone() | node.borrow().subtree_flags.clone();
+            if flags.intersects(get_mutation_mask()) {
+                return false;
+            }
+            child = node.borrow().sibling.clone();
+        }
+        true
+    }
+
+    // persistent mode, a root or portal with new children gets a child set to commit
+    fn update_host_container(
+        &self,
+        persistent: &dyn PersistentHostConfig,
+        current: &Option<Rc<RefCell<FiberNode>>>,
+        work_in_progress: Rc<RefCell<FiberNode>>,
+    ) {
+        if CompleteWork::had_no_mutations_effects(current, &work_in_progress) {
+            return;
+        }
+        let state_node = work_in_progress.borrow().state_node.clone().unwrap();
+        let container = match &*state_node {
+            StateNode::FiberRootNode(root) => root.borrow().container.clone(),
+            StateNode::Portal(container_info) => {
+                self.host_config.get_portal_container(container_info)
+            }
+            StateNode::Element(element) => element.clone(),
+        };
+        let child_set = persistent.create_container_child_set(container);
+        self.append_all_children_persistent(
+            persistent,
+            &|child| persistent.append_child_to_container_child_set(child_set.clone(), child),
+            work_in_progress.clone(),
+            false,
+        );
+        work_in_progress.borrow_mut().pending_children = Some(child_set);
+        CompleteWork::mark_update(work_in_progress);
+    }
+
+    // persistent mode, a host component with new props or children becomes a clone
+    fn update_host_component_persistent(
+        &self,
+        persistent: &dyn PersistentHostConfig,
+        current: &Option<Rc<RefCell<FiberNode>>>,
+        work_in_progress: Rc<RefCell<FiberNode>>,
+        old_props: &Value,
+        new_props: &Value,
+    ) {
+        let current_instance = FiberNode::derive_state_node(current.clone().unwrap()).unwrap();
+        let children_unchanged = CompleteWork::had_no_mutations_effects(current, &work_in_progress);
+        if children_unchanged && old_props == new_props {
+            work_in_progress.borrow_mut().state_node =
+                Some(Rc::new(StateNode::Element(current_instance)));
+            return;
+        }
+        let _type = work_in_progress.borrow()._type.as_host_type().unwrap();
+        let instance = persistent.clone_instance(
+            current_instance,
+            _type,
+            old_props,
+            new_props,
+            children_unchanged,
+        );
+        if !children_unchanged {
+            self.append_all_children_persistent(
+                persistent,
+                &|child| {
+                    self.host_config
+                        .append_initial_child(instance.clone(), child)
+                },
+                work_in_progress.clone(),
+                false,
+            );
+        }
+        work_in_progress.borrow_mut().state_node = Some(Rc::new(StateNode::Element(instance)));
+        // the parents have to take the clone in place of the current instance
+        CompleteWork::mark_update(work_in_progress);
     }
 
     fn mark_update(fiber: Rc<RefCell<FiberNode>>) {
         fiber.borrow_mut().flags |= Flags::Update;
     }
 
+    pub fn is_offscreen_hidden(fiber: &Rc<RefCell<FiberNode>>) -> bool {
+        fiber
+            .borrow()
+            .pending_props
+            .get("mode")
+            .as_string()
+            .as_deref()
+            == Some("hidden")
+    }
+
     pub fn complete_work(
         &self,
         work_in_progress: Rc<RefCell<FiberNode>>,
@@ -120,29 +311,88 @@ impl CompleteWork {
         let current = { work_in_progress_cloned.borrow().alternate.clone() };
         let tag = { work_in_progress_cloned.borrow().tag.clone() };
 
+        pop_tree_context(&work_in_progress);
         match tag {
-            WorkTag::FunctionComponent => {
+            WorkTag::FunctionComponent | WorkTag::ForwardRef | WorkTag::LazyComponent => {
+                self.bubble_properties(work_in_progress.clone());
+                None
+            }
+            WorkTag::HostRoot | WorkTag::HostPortal => {
+                pop_host_container();
+                if let Some(persistent) = self.host_config.persistent() {
+                    self.update_host_container(persistent, &current, work_in_progress.clone());
+                }
                 self.bubble_properties(work_in_progress.clone());
                 None
             }
-            WorkTag::HostRoot => {
+            WorkTag::Fragment | WorkTag::Mode | WorkTag::Profiler | WorkTag::OffscreenComponent => {
+                self.bubble_properties(work_in_progress.clone());
+                None
+            }
+            WorkTag::ContextProvider => {
+                let context = work_in_progress.borrow()._type.get("_context");
+                pop_provider(&context);
+                self.bubble_properties(work_in_progress.clone());
+                None
+            }
+            WorkTag::SuspenseComponent => {
+                let offscreen_fiber = work_in_progress.borrow().child.clone().unwrap();
+                let is_hidden = CompleteWork::is_offscreen_hidden(&offscreen_fiber);
+                let was_hidden = match current {
+                    Some(current) => {
+                        let current_offscreen_fiber = current.borrow().child.clone().unwrap();
+                        CompleteWork::is_offscreen_hidden(&current_offscreen_fiber)
+                    }
+                    None => false,
+                };
+                if is_hidden != was_hidden {
+                    offscreen_fiber.borrow_mut().flags |= Flags::Visibility;
+                    self.bubble_properties(offscreen_fiber.clone());
+                }
                 self.bubble_properties(work_in_progress.clone());
                 None
             }
             WorkTag::HostComponent => {
+                pop_host_context();
                 if current.is_some() && work_in_progress_cloned.borrow().state_node.is_some() {
-                    log!("update properties")
+                    let old_props = current.clone().unwrap().borrow().memoized_props.clone();
+                    if let Some(persistent) = self.host_config.persistent() {
+                        self.update_host_component_persistent(
+                            persistent,
+                            &current,
+                            work_in_progress.clone(),
+                            &old_props,
+                            &new_props,
+                        );
+                    } else if old_props != new_props {
+                        CompleteWork::mark_update(work_in_progress.clone());
+                    }
                 } else {
-                    let instance = self.host_config.create_instance(
-                        work_in_progress
-                            .clone()
-                            .borrow()
-                            ._type
-                            .as_ref()
-                            .as_string()
-                            .unwrap(),
-                    );
-                    self.append_all_children(instance.clone(), work_in_progress.clone());
+                    let _type = work_in_progress
+                        .clone()
+                        .borrow()
+                        ._type
+                        .as_host_type()
+                        .unwrap();
+                    let instance = self
+                        .host_config
+                        .create_instance(_type.clone(), &get_host_context());
+                    match self.host_config.persistent() {
+                        Some(persistent) => self.append_all_children_persistent(
+                            persistent,
+                            &|child| {
+                                self.host_config
+                                    .append_initial_child(instance.clone(), child)
+                            },
+                            work_in_progress.clone(),
+                            false,
+                        ),
+                        None => {
+                            self.append_all_children(instance.clone(), work_in_progress.clone())
+                        }
+                    }
+                    self.host_config
+                        .finalize_initial_children(instance.clone(), _type, &new_props);
                     work_in_progress.clone().borrow_mut().state_node =
                         Some(Rc::new(StateNode::Element(instance.clone())));
                 }
@@ -152,20 +402,27 @@ impl CompleteWork {
             }
             WorkTag::HostText => {
                 if current.is_some() && work_in_progress_cloned.borrow().state_node.is_some() {
-                    let old_text = derive_from_js_value(
-                        &current.clone().unwrap().clone().borrow().memoized_props,
-                        "content",
-                    );
-                    let new_text = derive_from_js_value(&new_props, "content");
-                    if !Object::is(&old_text, &new_text) {
+                    let old_text = current
+                        .clone()
+                        .unwrap()
+                        .borrow()
+                        .memoized_props
+                        .text_content();
+                    let new_text = new_props.text_content();
+                    if old_text != new_text {
+                        if self.host_config.persistent().is_some() {
+                            // text instances can't change either, the new text gets a new one
+                            let text_instance =
+                                self.host_config.create_text_instance(new_text.unwrap());
+                            work_in_progress.borrow_mut().state_node =
+                                Some(Rc::new(StateNode::Element(text_instance)));
+                        }
                         CompleteWork::mark_update(work_in_progress.clone());
                     }
                 } else {
-                    let text_instance = self.host_config.create_text_instance(
-                        derive_from_js_value(&new_props, "content")
-                            .as_string()
-                            .unwrap(),
-                    );
+                    let text_instance = self
+                        .host_config
+                        .create_text_instance(new_props.text_content().unwrap());
                     work_in_progress.clone().borrow_mut().state_node =
                         Some(Rc::new(StateNode::Element(text_instance.clone())));
                 }
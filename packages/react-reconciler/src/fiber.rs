@@ -5,33 +5,40 @@ use std::fmt::{Debug, Formatter};
 use std::ops::Deref;
 use std::rc::Rc;
 
-use wasm_bindgen::JsValue;
-use web_sys::js_sys::Reflect;
-
-use shared::derive_from_js_value;
+use shared::{
+    REACT_FORWARD_REF_TYPE, REACT_LAZY_TYPE, REACT_PROFILER_TYPE, REACT_PROVIDER_TYPE,
+    REACT_STRICT_MODE_TYPE, REACT_SUSPENSE_TYPE,
+};
 
 use crate::fiber_flags::Flags;
-use crate::fiber_hooks::Hook;
-use crate::update_queue::{Update, UpdateQueue};
+use crate::fiber_hooks::{Effect, Hook};
+use crate::fiber_lanes::{get_highest_priority_lane, merge_lanes, Lane};
+use crate::react_value::{ElementType, Key, Props, ReactNode, StateValue, Value};
+use crate::type_of_mode::TypeOfMode;
+use crate::update_queue::{enqueue_update, Update, UpdateQueue};
 use crate::work_tags::WorkTag;
 
 #[derive(Debug)]
 pub enum StateNode {
     FiberRootNode(Rc<RefCell<FiberRootNode>>),
     Element(Rc<dyn Any>),
+    // the containerInfo passed to createPortal, the host config turns it into a container
+    Portal(Value),
 }
 
 #[derive(Debug, Clone)]
 pub enum MemoizedState {
-    JsValue(JsValue),
+    Value(StateValue),
     Hook(Rc<RefCell<Hook>>),
+    Effect(Rc<RefCell<Effect>>),
 }
 
 impl MemoizedState {
-    pub fn js_value(&self) -> Option<JsValue> {
+    pub fn value(&self) -> Option<StateValue> {
         match self {
-            MemoizedState::JsValue(js_value) => Some(js_value.clone()),
-            MemoizedState::Hook(_) => None
+            MemoizedState::Value(value) => Some(value.clone()),
+            MemoizedState::Hook(_) => None,
+            MemoizedState::Effect(_) => None,
         }
     }
 }
@@ -40,57 +47,124 @@ impl MemoizedState {
 pub struct FiberNode {
     pub index: u32,
     pub tag: WorkTag,
-    pub pending_props: JsValue,
-    pub key: JsValue,
+    pub mode: TypeOfMode,
+    pub pending_props: Props,
+    pub key: Key,
+    pub _ref: Value,
     pub state_node: Option<Rc<StateNode>>,
     pub update_queue: Option<Rc<RefCell<UpdateQueue>>>,
     pub _return: Option<Rc<RefCell<FiberNode>>>,
     pub sibling: Option<Rc<RefCell<FiberNode>>>,
     pub child: Option<Rc<RefCell<FiberNode>>>,
     pub alternate: Option<Rc<RefCell<FiberNode>>>,
-    pub _type: JsValue,
+    pub element_type: ElementType,
+    pub _type: ElementType,
     pub flags: Flags,
     pub subtree_flags: Flags,
-    pub memoized_props: JsValue,
+    pub memoized_props: Props,
     pub memoized_state: Option<MemoizedState>,
     pub deletions: Option<Vec<Rc<RefCell<FiberNode>>>>,
+    // persistent mode, the child set a HostRoot or HostPortal puts into its container on commit
+    pub pending_children: Option<Rc<dyn Any>>,
+    // Profiler timings, only measured in ProfileMode
+    pub actual_duration: f64,
+    pub actual_start_time: f64,
+    pub self_base_duration: f64,
+    pub tree_base_duration: f64,
+    // the hooks of the last render in call order, to find hook order changes in development
+    pub debug_hook_types: Option<Vec<&'static str>>,
+    // the id snapshots and devtools know the fiber by, shared with its alternate
+    pub debug_id: u32,
+}
+
+static mut DEBUG_COUNTER: u32 = 1;
+
+fn next_debug_id() -> u32 {
+    unsafe {
+        let id = DEBUG_COUNTER;
+        DEBUG_COUNTER += 1;
+        id
+    }
 }
 
 impl FiberNode {
-    pub fn new(tag: WorkTag, pending_props: JsValue, key: JsValue) -> Self {
+    pub fn new(tag: WorkTag, pending_props: Props, key: Key) -> Self {
         Self {
             index: 0,
             tag,
+            mode: TypeOfMode::NoMode,
             pending_props,
             key,
+            _ref: Value::null(),
             state_node: None,
             update_queue: None,
             _return: None,
             sibling: None,
             child: None,
             alternate: None,
-            _type: JsValue::null(),
-            memoized_props: JsValue::null(),
+            element_type: Value::null(),
+            _type: Value::null(),
+            memoized_props: Value::null(),
             memoized_state: None,
             flags: Flags::NoFlags,
             subtree_flags: Flags::NoFlags,
             deletions: None,
+            pending_children: None,
+            actual_duration: 0.0,
+            actual_start_time: -1.0,
+            self_base_duration: 0.0,
+            tree_base_duration: 0.0,
+            debug_hook_types: None,
+            debug_id: next_debug_id(),
         }
     }
 
-    pub fn create_fiber_from_element(ele: &JsValue) -> Self {
-        let _type = derive_from_js_value(ele, "type");
-        let key = derive_from_js_value(ele, "key");
-        let props = derive_from_js_value(ele, "props");
+    pub fn create_fiber_from_element(ele: &ReactNode, mode: TypeOfMode) -> Self {
+        let _type = ele.element_type();
+        let key = ele.element_key();
+        let props = ele.element_props();
+        let _ref = ele.element_ref();
 
         let mut fiber_tag = WorkTag::FunctionComponent;
-        if _type.is_string() {
-            fiber_tag = WorkTag::HostComponent
+        let mut mode = mode;
+        if let Some(host_type) = _type.as_host_type() {
+            // the built in types are strings as well
+            if host_type == REACT_SUSPENSE_TYPE {
+                fiber_tag = WorkTag::SuspenseComponent
+            } else if host_type == REACT_STRICT_MODE_TYPE {
+                fiber_tag = WorkTag::Mode;
+                mode |= TypeOfMode::StrictLegacyMode | TypeOfMode::StrictEffectsMode;
+            } else if host_type == REACT_PROFILER_TYPE {
+                fiber_tag = WorkTag::Profiler;
+                mode |= TypeOfMode::ProfileMode;
+            } else {
+                fiber_tag = WorkTag::HostComponent
+            }
+        } else if let Some(_typeof) = _type.type_of() {
+            if _typeof == REACT_PROVIDER_TYPE {
+                fiber_tag = WorkTag::ContextProvider
+            } else if _typeof == REACT_LAZY_TYPE {
+                fiber_tag = WorkTag::LazyComponent
+            } else if _typeof == REACT_FORWARD_REF_TYPE {
+                fiber_tag = WorkTag::ForwardRef
+            }
         }
 
-
         let mut fiber = FiberNode::new(fiber_tag, props, key);
+        fiber.mode = mode;
+        fiber.element_type = _type.clone();
         fiber._type = _type;
+        fiber._ref = _ref;
+        fiber
+    }
+
+    pub fn create_fiber_from_portal(portal: &ReactNode, mode: TypeOfMode) -> Self {
+        // unlike elements the children of a portal are its props
+        let children = portal.portal_children();
+        let key = portal.element_key();
+        let mut fiber = FiberNode::new(WorkTag::HostPortal, children, key);
+        fiber.mode = mode;
+        fiber.state_node = Some(Rc::new(StateNode::Portal(portal.portal_container_info())));
         fiber
     }
 
@@ -102,13 +176,12 @@ impl FiberNode {
             Some(a) => a.clone(),
         };
 
-        let mut u = update_queue.borrow_mut();
-        u.shared.pending = Some(update);
+        enqueue_update(update_queue, update);
     }
 
     pub fn create_work_in_progress(
         current: Rc<RefCell<FiberNode>>,
-        pending_props: JsValue,
+        pending_props: Props,
     ) -> Rc<RefCell<FiberNode>> {
         let c_rc = Rc::clone(&current);
         let w = {
@@ -116,24 +189,25 @@ impl FiberNode {
             c.deref().alternate.clone()
         };
 
-        return if w.is_none() {
+        if w.is_none() {
             let mut wip = {
                 let c = c_rc.borrow();
                 let mut wip = FiberNode::new(c.tag.clone(), pending_props, c.key.clone());
-                wip.update_queue = match c.update_queue.as_ref() {
-                    None => None,
-                    Some(update_queue) => {
-                        Some(update_queue.clone())
-                    }
-                };
+                wip.mode = c.mode;
+                wip.update_queue = c.update_queue.clone();
                 wip.flags = c.flags.clone();
                 wip.child = c.child.clone();
                 wip.memoized_props = c.memoized_props.clone();
                 wip.memoized_state = c.memoized_state.clone();
                 wip
             };
+            wip.element_type = c_rc.borrow().element_type.clone();
             wip._type = c_rc.borrow()._type.clone();
+            wip._ref = c_rc.borrow()._ref.clone();
             wip.state_node = c_rc.borrow().state_node.clone();
+            wip.self_base_duration = c_rc.borrow().self_base_duration;
+            wip.tree_base_duration = c_rc.borrow().tree_base_duration;
+            wip.debug_id = c_rc.borrow().debug_id;
             wip.alternate = Some(current);
             let wip_rc = Rc::new(RefCell::new(wip));
             {
@@ -148,25 +222,33 @@ impl FiberNode {
                 let mut wip = wip_cloned.borrow_mut();
                 let c = c_rc.borrow();
                 wip.pending_props = pending_props;
-                wip.update_queue = Some(c.update_queue.as_ref().unwrap().clone());
-                wip.flags = c.flags.clone();
+                wip.update_queue = c.update_queue.clone();
+                wip.flags = Flags::NoFlags;
+                wip.subtree_flags = Flags::NoFlags;
+                wip.deletions = None;
+                wip.pending_children = None;
+                // persistent hosts give every render its own instances
+                wip.state_node = c.state_node.clone();
                 wip.child = c.child.clone();
                 wip.memoized_props = c.memoized_props.clone();
                 wip.memoized_state = c.memoized_state.clone();
+                wip._ref = c._ref.clone();
+                wip.actual_duration = 0.0;
+                wip.actual_start_time = -1.0;
+                wip.self_base_duration = c.self_base_duration;
+                wip.tree_base_duration = c.tree_base_duration;
             }
             w.clone()
-        };
+        }
     }
 
     pub fn derive_state_node(fiber: Rc<RefCell<FiberNode>>) -> Option<Rc<dyn Any>> {
-        let state_node = fiber.clone().borrow().state_node.clone();
-        if state_node.is_none() {
-            return None;
-        }
+        let state_node = fiber.clone().borrow().state_node.clone()?;
 
-        Some(match &*state_node.unwrap().clone() {
+        Some(match &*state_node {
             StateNode::FiberRootNode(root) => root.clone().borrow().container.clone(),
             StateNode::Element(ele) => ele.clone(),
+            StateNode::Portal(_) => return None,
         })
     }
 }
@@ -175,6 +257,21 @@ pub struct FiberRootNode {
     pub container: Rc<dyn Any>,
     pub current: Rc<RefCell<FiberNode>>,
     pub finished_work: Option<Rc<RefCell<FiberNode>>>,
+    pub pending_lanes: Lane,
+    pub suspended_lanes: Lane,
+    pub pinged_lanes: Lane,
+    pub ping_cache: Vec<Value>,
+    pub callback_node: Option<u32>,
+    pub callback_priority: Lane,
+    pub pending_passive_effects: Rc<RefCell<PendingPassiveEffects>>,
+}
+
+#[derive(Debug, Default)]
+pub struct PendingPassiveEffects {
+    pub unmount: Vec<Rc<RefCell<Effect>>>,
+    pub update: Vec<Rc<RefCell<Effect>>>,
+    // mounted under StrictMode, run once more right after their first run
+    pub remount: Vec<Rc<RefCell<Effect>>>,
 }
 
 impl FiberRootNode {
@@ -183,14 +280,57 @@ impl FiberRootNode {
             container,
             current: host_root_fiber,
             finished_work: None,
+            pending_lanes: Lane::NoLane,
+            suspended_lanes: Lane::NoLane,
+            pinged_lanes: Lane::NoLane,
+            ping_cache: vec![],
+            callback_node: None,
+            callback_priority: Lane::NoLane,
+            pending_passive_effects: Rc::new(RefCell::new(PendingPassiveEffects::default())),
         }
     }
+
+    pub fn mark_root_updated(&mut self, lane: Lane) {
+        self.pending_lanes = merge_lanes(self.pending_lanes, lane);
+        self.suspended_lanes = Lane::NoLane;
+        self.pinged_lanes = Lane::NoLane;
+    }
+
+    pub fn mark_root_suspended(&mut self, lane: Lane) {
+        self.suspended_lanes |= lane;
+        self.pinged_lanes -= lane;
+    }
+
+    pub fn mark_root_pinged(&mut self, lane: Lane) {
+        self.pinged_lanes |= self.suspended_lanes & lane;
+    }
+
+    pub fn mark_root_finished(&mut self, lane: Lane, spawned_lane: Lane) {
+        self.pending_lanes -= lane;
+        // e.g. the deferred lane requested by useDeferredValue during this render
+        self.pending_lanes |= spawned_lane;
+        self.suspended_lanes -= lane;
+        self.pinged_lanes -= lane;
+    }
+
+    pub fn get_next_lane(&self) -> Lane {
+        let pending_lanes = self.pending_lanes;
+        if pending_lanes == Lane::NoLane {
+            return Lane::NoLane;
+        }
+
+        let unsuspended_lanes = pending_lanes - self.suspended_lanes;
+        if unsuspended_lanes != Lane::NoLane {
+            return get_highest_priority_lane(unsuspended_lanes);
+        }
+        get_highest_priority_lane(pending_lanes & self.pinged_lanes)
+    }
 }
 
 impl Debug for FiberRootNode {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let root = self.current.clone().borrow().alternate.clone();
-        Ok(if let Some(node) = root {
+        if let Some(node) = root {
             let mut queue = VecDeque::new();
             queue.push_back(Rc::clone(&node));
 
@@ -203,11 +343,11 @@ impl Debug for FiberRootNode {
                         write!(
                             f,
                             "{:?}(flags:{:?}, subtreeFlags:{:?})",
-                            current_borrowed._type.as_ref(),
+                            current_borrowed._type,
                             current_borrowed.flags,
                             current_borrowed.subtree_flags
                         )
-                            .expect("print error");
+                        .expect("print error");
                     }
                     WorkTag::HostRoot => {
                         write!(
@@ -216,20 +356,34 @@ impl Debug for FiberRootNode {
                             WorkTag::HostRoot,
                             current_ref.subtree_flags
                         )
-                            .expect("print error");
+                        .expect("print error");
                     }
                     WorkTag::HostComponent => {
                         let current_borrowed = current.borrow();
                         write!(
                             f,
                             "{:?}(flags:{:?}, subtreeFlags:{:?})",
-                            current_borrowed
-                                ._type
-                                .as_ref().as_string().unwrap(),
+                            current_borrowed._type.as_host_type().unwrap(),
                             current_borrowed.flags,
                             current_borrowed.subtree_flags
                         )
-                            .expect("print error");
+                        .expect("print error");
+                    }
+                    WorkTag::Fragment
+                    | WorkTag::Mode
+                    | WorkTag::Profiler
+                    | WorkTag::HostPortal
+                    | WorkTag::ContextProvider
+                    | WorkTag::ForwardRef
+                    | WorkTag::LazyComponent
+                    | WorkTag::SuspenseComponent
+                    | WorkTag::OffscreenComponent => {
+                        write!(
+                            f,
+                            "{:?}(flags:{:?}, subtreeFlags:{:?})",
+                            current_ref.tag, current_ref.flags, current_ref.subtree_flags
+                        )
+                        .expect("print error");
                     }
                     WorkTag::HostText => {
                         let current_borrowed = current.borrow();
@@ -238,16 +392,10 @@ impl Debug for FiberRootNode {
                             f,
                             "{:?}(state_node:{:?}, flags:{:?})",
                             current_borrowed.tag,
-                            Reflect::get(
-                                current_borrowed.pending_props.as_ref(),
-                                &JsValue::from_str("content"),
-                            )
-                                .unwrap()
-                                .as_string()
-                                .unwrap(),
+                            current_borrowed.pending_props.text_content().unwrap(),
                             current_borrowed.flags
                         )
-                            .expect("print error");
+                        .expect("print error");
                     }
                 };
                 if let Some(ref child) = current_ref.child {
@@ -265,7 +413,7 @@ impl Debug for FiberRootNode {
                         (current_ref._return.as_ref(), next_ref._return.as_ref())
                     {
                         if !Rc::ptr_eq(current_parent, next_parent) {
-                            writeln!(f, "").expect("print error");
+                            writeln!(f).expect("print error");
                             writeln!(f, "------------------------------------")
                                 .expect("print error");
                             continue;
@@ -275,11 +423,12 @@ impl Debug for FiberRootNode {
                     if current_ref._return.is_some() {
                         write!(f, ",").expect("print error");
                     } else {
-                        writeln!(f, "").expect("print error");
+                        writeln!(f).expect("print error");
                         writeln!(f, "------------------------------------").expect("print error");
                     }
                 }
             }
-        })
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,25 @@
+use crate::react_value::Value;
+
+static mut PREV_CONTEXT_VALUE_STACK: Vec<Value> = vec![];
+
+pub fn push_provider(context: &Value, new_value: Value) {
+    unsafe {
+        PREV_CONTEXT_VALUE_STACK.push(context.get("_currentValue"));
+    }
+    context
+        .set("_currentValue", new_value)
+        .expect("push_provider panic");
+}
+
+pub fn pop_provider(context: &Value) {
+    let prev_context_value = unsafe { PREV_CONTEXT_VALUE_STACK.pop() };
+    if let Some(prev_context_value) = prev_context_value {
+        context
+            .set("_currentValue", prev_context_value)
+            .expect("pop_provider panic");
+    }
+}
+
+pub fn read_context(context: &Value) -> Value {
+    context.get("_currentValue")
+}
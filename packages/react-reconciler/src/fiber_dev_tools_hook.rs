@@ -0,0 +1,88 @@
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use crate::fiber::{FiberNode, FiberRootNode};
+use crate::fiber_hooks::WORK_LOOP;
+use crate::fiber_lanes::Lane;
+use crate::fiber_snapshot::prune_host_node_ids;
+use crate::react_value::Value;
+
+pub type CommitRootHook = Rc<dyn Fn(Rc<RefCell<FiberRootNode>>)>;
+
+// called after every commit with the root that was committed, e.g. by devtools
+static mut COMMIT_ROOT_HOOKS: Vec<(u32, CommitRootHook)> = vec![];
+static mut NEXT_HOOK_ID: u32 = 1;
+// every root that committed at least once and is still alive
+static mut FIBER_ROOTS: Vec<Weak<RefCell<FiberRootNode>>> = vec![];
+
+// returns an id for remove_commit_root_hook
+pub fn inject_commit_root_hook(hook: CommitRootHook) -> u32 {
+    unsafe {
+        let id = NEXT_HOOK_ID;
+        NEXT_HOOK_ID += 1;
+        COMMIT_ROOT_HOOKS.push((id, hook));
+        id
+    }
+}
+
+pub fn remove_commit_root_hook(id: u32) {
+    unsafe {
+        COMMIT_ROOT_HOOKS.retain(|(hook_id, _)| *hook_id != id);
+    }
+}
+
+pub fn get_fiber_roots() -> Vec<Rc<RefCell<FiberRootNode>>> {
+    unsafe {
+        FIBER_ROOTS
+            .iter()
+            .filter_map(|root| root.upgrade())
+            .collect()
+    }
+}
+
+pub(crate) fn on_commit_root(root: Rc<RefCell<FiberRootNode>>) {
+    unsafe {
+        FIBER_ROOTS.retain(|root| root.strong_count() > 0);
+        if !FIBER_ROOTS
+            .iter()
+            .any(|known| std::ptr::eq(known.as_ptr(), Rc::as_ptr(&root)))
+        {
+            FIBER_ROOTS.push(Rc::downgrade(&root));
+        }
+    }
+    // the host nodes the commit removed
+    prune_host_node_ids();
+    // copied, a hook can add or remove hooks
+    let hooks = unsafe { COMMIT_ROOT_HOOKS.clone() };
+    for (_, hook) in hooks {
+        hook(root.clone());
+    }
+}
+
+// renders fiber again without changing its props or state
+pub fn schedule_update(fiber: Rc<RefCell<FiberNode>>) -> bool {
+    match unsafe { WORK_LOOP.clone() } {
+        Some(work_loop) => {
+            work_loop
+                .borrow()
+                .schedule_update_on_fiber(fiber, Lane::SyncLane);
+            true
+        }
+        None => false,
+    }
+}
+
+// as if the setState of a useState in fiber was called, hook_index is the position of the
+// hook in the component, counting every hook
+pub fn override_hook_state(
+    fiber: &Rc<RefCell<FiberNode>>,
+    hook_index: usize,
+    value: Value,
+) -> bool {
+    if unsafe { WORK_LOOP.is_none() } {
+        return false;
+    }
+    crate::fiber_hooks::override_hook_state(fiber, hook_index, value)
+}
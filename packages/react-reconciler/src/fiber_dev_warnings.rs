@@ -0,0 +1,195 @@
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+use shared::{error, __DEV__};
+
+use crate::fiber::FiberNode;
+use crate::get_component_name::{get_component_name_from_fiber, get_component_name_from_type};
+use crate::react_value::{ReactNode, ReactNodeKind, Value};
+use crate::work_tags::WorkTag;
+
+// every warning below is shown once per component
+static mut OWNER_HAS_KEY_USE_WARNING: Vec<String> = vec![];
+static mut DID_WARN_ABOUT_MISMATCHED_HOOKS: Vec<String> = vec![];
+static mut DID_WARN_ABOUT_UNMOUNTED_UPDATE: Vec<String> = vec![];
+
+fn should_warn_once(warned: &mut Vec<String>, name: &str) -> bool {
+    if warned.iter().any(|known| known == name) {
+        return false;
+    }
+    warned.push(name.to_string());
+    true
+}
+
+// "\n    in Button\n    in div\n    in App", from fiber up to its root
+pub fn get_stack_by_fiber_in_dev(fiber: &Rc<RefCell<FiberNode>>) -> String {
+    let mut stack = String::new();
+    let mut node = Some(fiber.clone());
+    while let Some(current) = node {
+        if let Some(name) = get_component_name_from_fiber(&current.borrow()) {
+            stack.push_str(&format!("\n    in {}", name));
+        }
+        node = current.borrow()._return.clone();
+    }
+    stack
+}
+
+// the name of the component that rendered fiber, fiber itself when it is a component
+fn get_owner_name(fiber: &Rc<RefCell<FiberNode>>) -> Option<String> {
+    let mut node = Some(fiber.clone());
+    while let Some(current) = node {
+        let tag = current.borrow().tag.clone();
+        if tag == WorkTag::FunctionComponent || tag == WorkTag::ForwardRef {
+            return get_component_name_from_fiber(&current.borrow());
+        }
+        node = current.borrow()._return.clone();
+    }
+    None
+}
+
+fn get_fiber_name(fiber: &Rc<RefCell<FiberNode>>) -> String {
+    get_component_name_from_fiber(&fiber.borrow()).unwrap_or("Unknown".to_string())
+}
+
+fn get_element_stack(child: &ReactNode, return_fiber: &Rc<RefCell<FiberNode>>) -> String {
+    let name = get_component_name_from_type(&child.element_type());
+    let child_frame = name
+        .map(|name| format!("\n    in {}", name))
+        .unwrap_or_default();
+    format!("{}{}", child_frame, get_stack_by_fiber_in_dev(return_fiber))
+}
+
+// an element in a list without a key, elements that came from static jsx children are
+// validated by react already
+pub fn warn_for_missing_key(return_fiber: &Rc<RefCell<FiberNode>>, child: &ReactNode) {
+    if !__DEV__ || !matches!(child.kind(), ReactNodeKind::Element) {
+        return;
+    }
+    if child.element_key().as_key_string().is_some() {
+        return;
+    }
+    let store = child.get("_store");
+    if store.is_nullish() || store.get("validated").as_bool() == Some(true) {
+        return;
+    }
+    let _ = store.set("validated", Value::from_bool(true));
+
+    let owner_name = get_owner_name(return_fiber);
+    let owner_key = owner_name.clone().unwrap_or_default();
+    if !should_warn_once(unsafe { &mut OWNER_HAS_KEY_USE_WARNING }, &owner_key) {
+        return;
+    }
+    let check = owner_name
+        .map(|name| format!("\n\nCheck the render method of `{}`.", name))
+        .unwrap_or_default();
+    error!(
+        Render,
+        "Each child in a list should have a unique \"key\" prop.{} See https://react.dev/link/warning-keys for more information.{}",
+        check,
+        get_element_stack(child, return_fiber)
+    );
+}
+
+// known_keys are the keys of the children before child in the same list
+pub fn warn_on_invalid_key(
+    return_fiber: &Rc<RefCell<FiberNode>>,
+    child: &ReactNode,
+    known_keys: &mut HashSet<String>,
+) {
+    if !__DEV__ {
+        return;
+    }
+    if !matches!(child.kind(), ReactNodeKind::Element | ReactNodeKind::Portal) {
+        return;
+    }
+    let key = match child.element_key().as_key_string() {
+        Some(key) => key,
+        None => return,
+    };
+    if known_keys.insert(key.clone()) {
+        return;
+    }
+    error!(
+        Render,
+        "Encountered two children with the same key, `{}`. Keys should be unique so that components maintain their identity across updates. Non-unique keys may cause children to be duplicated and/or omitted — the behavior is unsupported and could change in a future version.{}",
+        key,
+        get_element_stack(child, return_fiber)
+    );
+}
+
+// child is neither text, a list, an element, a portal nor empty
+pub fn warn_for_invalid_child(return_fiber: &Rc<RefCell<FiberNode>>, child: &ReactNode) {
+    if !__DEV__ {
+        return;
+    }
+    let stack = get_stack_by_fiber_in_dev(return_fiber);
+    if child.is_function() {
+        let name = child
+            .get("name")
+            .as_string()
+            .filter(|name| !name.is_empty())
+            .unwrap_or("Component".to_string());
+        error!(
+            Render,
+            "Functions are not valid as a React child. This may happen if you return {} instead of <{} /> from render. Or maybe you meant to call this function rather than return it.{}",
+            name,
+            name,
+            stack
+        );
+    } else if let Some(keys) = child.keys() {
+        let keys = keys.join(", ");
+        error!(
+            Render,
+            "Objects are not valid as a React child (found: object with keys {{{}}}). If you meant to render a collection of children, use an array instead.{}",
+            keys,
+            stack
+        );
+    } else {
+        error!(
+            Render,
+            "{:?} is not valid as a React child.{}", child, stack
+        );
+    }
+}
+
+pub fn warn_about_update_on_unmounted_fiber_in_dev(fiber: &Rc<RefCell<FiberNode>>) {
+    if !__DEV__ {
+        return;
+    }
+    let name = get_fiber_name(fiber);
+    if !should_warn_once(unsafe { &mut DID_WARN_ABOUT_UNMOUNTED_UPDATE }, &name) {
+        return;
+    }
+    error!(
+        Hooks,
+        "Can't perform a React state update on a component that is not mounted. This is a no-op, but it indicates a memory leak in your application. To fix, cancel all subscriptions and asynchronous tasks in a useEffect cleanup function.{}",
+        get_stack_by_fiber_in_dev(fiber)
+    );
+}
+
+// prev_hooks are the hooks of the last render, next_hooks the ones so far in this render
+pub fn warn_on_hook_mismatch_in_dev(
+    fiber: &Rc<RefCell<FiberNode>>,
+    prev_hooks: &[&'static str],
+    next_hooks: &[&'static str],
+) {
+    if !__DEV__ {
+        return;
+    }
+    let name = get_fiber_name(fiber);
+    if !should_warn_once(unsafe { &mut DID_WARN_ABOUT_MISMATCHED_HOOKS }, &name) {
+        return;
+    }
+    let mut table = String::new();
+    for (index, next_hook) in next_hooks.iter().enumerate() {
+        let prev_hook = prev_hooks.get(index).copied().unwrap_or("undefined");
+        let row = format!("{}. {}", index + 1, prev_hook);
+        table.push_str(&format!("{:<30}{}\n", row, next_hook));
+    }
+    error!(
+        Hooks,
+        "React has detected a change in the order of Hooks called by {}. This will lead to bugs and errors if not fixed. For more information, read the Rules of Hooks: https://react.dev/link/rules-of-hooks\n\n   Previous render            Next render\n   ------------------------------------------------------\n{}   ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^\n{}",
+        name,
+        table,
+        get_stack_by_fiber_in_dev(fiber)
+    );
+}
@@ -2,14 +2,39 @@ use bitflags::bitflags;
 
 bitflags! {
     #[derive(Debug, Clone)]
-    pub struct Flags:u8 {
-        const NoFlags = 0b00000000;
-        const Placement = 0b00000010;
-        const Update = 0b00000100;
-        const ChildDeletion = 0b00010000;
+    pub struct Flags:u16 {
+        const NoFlags = 0b0000000000000000;
+        const Placement = 0b0000000000000010;
+        const Update = 0b0000000000000100;
+        const ChildDeletion = 0b0000000000010000;
+        // the host set the content of the node itself, e.g. from dangerouslySetInnerHTML
+        const ContentReset = 0b0000000000100000;
+        const DidCapture = 0b0000000010000000;
+        // part of a list of children, see fiber_tree_context
+        const Forked = 0b0000000100000000;
+        const Ref = 0b0000001000000000;
+        const PassiveEffect = 0b0000100000000000;
+        const StoreConsistency = 0b0001000000000000;
+        const Visibility = 0b0010000000000000;
+        const ShouldCapture = 0b0100000000000000;
+        // inserted by this commit, StrictMode replays the effects of these subtrees
+        const PlacementDEV = 0b1000000000000000;
     }
 }
 
 pub fn get_mutation_mask() -> Flags {
-    Flags::Placement | Flags::Update | Flags::ChildDeletion
+    Flags::Placement
+        | Flags::Update
+        | Flags::ChildDeletion
+        | Flags::ContentReset
+        | Flags::Visibility
+        | Flags::Ref
+}
+
+pub fn get_layout_mask() -> Flags {
+    Flags::Update | Flags::Ref
+}
+
+pub fn get_passive_mask() -> Flags {
+    Flags::PassiveEffect | Flags::ChildDeletion
 }
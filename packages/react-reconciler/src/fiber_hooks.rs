@@ -1,33 +1,110 @@
+use std::any::Any;
 use std::cell::RefCell;
+use std::fmt::{Debug, Formatter};
 use std::rc::Rc;
 
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::{wasm_bindgen, Closure};
-use wasm_bindgen::{JsCast, JsValue};
-use web_sys::js_sys::{Function, Object, Reflect};
+use wasm_bindgen::JsValue;
 
-use shared::log;
+use react::current_dispatcher::{set_current_dispatcher, Dispatcher};
+use react::element::{is_rust_component, render_rust_component};
+use shared::{error, trace, __DEV__, REACT_CONTEXT_TYPE};
 
 use crate::fiber::{FiberNode, MemoizedState};
+use crate::fiber_context::read_context;
+use crate::fiber_dev_warnings::{
+    warn_about_update_on_unmounted_fiber_in_dev, warn_on_hook_mismatch_in_dev,
+};
+use crate::fiber_flags::Flags;
+use crate::fiber_lanes::{includes_only_non_urgent_lanes, request_update_lane, Lane};
+use crate::fiber_throw::is_thenable;
+use crate::fiber_tree_context::{get_tree_id, to_base_32};
+use crate::hook_effect_tags::HookEffectTags;
+use crate::react_value::{ReactValue, Value};
+use crate::thenable::track_used_thenable;
+use crate::type_of_mode::TypeOfMode;
 use crate::update_queue::{
-    create_update, create_update_queue, enqueue_update, process_update_queue, UpdateQueue,
+    create_update, create_update_queue, enqueue_update, process_update_queue, Update, UpdateQueue,
 };
-use crate::work_loop::WorkLoop;
+use crate::work_loop::{request_deferred_lane, WorkLoop};
 
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern "C" {
+    // the react module, js components call the hooks of its dispatcher
     fn updateDispatcher(args: &JsValue);
+    #[wasm_bindgen(catch)]
+    fn startTransition(scope: &JsValue) -> Result<(), JsValue>;
+}
+
+// the transition flag lives in the react module, which request_update_lane asks as well
+#[cfg(target_arch = "wasm32")]
+fn start_transition_scope(
+    scope: impl FnOnce() -> Result<(), Value> + 'static,
+) -> Result<(), Value> {
+    let scope = Closure::once_into_js(move || scope().map_err(JsValue::from));
+    startTransition(&scope).map_err(Value::from)
+}
+
+// without javascript it is the copy of react linked into this crate
+#[cfg(not(target_arch = "wasm32"))]
+fn start_transition_scope(
+    scope: impl FnOnce() -> Result<(), Value> + 'static,
+) -> Result<(), Value> {
+    react::current_batch_config::with_transition(scope)
 }
 
 static mut CURRENTLY_RENDERING_FIBER: Option<Rc<RefCell<FiberNode>>> = None;
 static mut WORK_IN_PROGRESS_HOOK: Option<Rc<RefCell<Hook>>> = None;
 static mut CURRENT_HOOK: Option<Rc<RefCell<Hook>>> = None;
+static mut RENDER_LANE: Lane = Lane::NoLane;
 pub static mut WORK_LOOP: Option<Rc<RefCell<WorkLoop>>> = None;
+static mut THENABLE_STATE: Option<Vec<Value>> = None;
+// development only, the hooks called so far in this render and the ones of the last render
+static mut HOOK_TYPES_DEV: Vec<&'static str> = vec![];
+static mut PREV_HOOK_TYPES_DEV: Option<Vec<&'static str>> = None;
+static mut THENABLE_INDEX_COUNTER: usize = 0;
+static mut LOCAL_ID_COUNTER: u32 = 0;
+static mut DID_RENDER_ID_HOOK: bool = false;
+// thenables of the last fiber that suspended, handed back when that fiber renders again
+static mut SUSPENDED_THENABLE_STATE: Option<(Rc<RefCell<FiberNode>>, Vec<Value>)> = None;
+// development only, like React it is reported once
+static mut DID_WARN_UNCACHED_SERVER_SNAPSHOT: bool = false;
 
 #[derive(Debug, Clone)]
 pub struct Hook {
     memoized_state: Option<MemoizedState>,
     update_queue: Option<Rc<RefCell<UpdateQueue>>>,
     next: Option<Rc<RefCell<Hook>>>,
+    base_state: Option<MemoizedState>,
+    base_queue: Option<Rc<RefCell<Update>>>,
+}
+
+#[derive(Clone)]
+pub struct Effect {
+    pub tag: HookEffectTags,
+    pub create: Value,
+    // what create returned, called when it is a function
+    pub destroy: Value,
+    pub deps: Value,
+    pub next: Option<Rc<RefCell<Effect>>>,
+}
+
+impl Debug for Effect {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // `next` is circular, so it is left out
+        f.debug_struct("Effect")
+            .field("tag", &self.tag)
+            .field("deps", &self.deps)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StoreConsistencyCheck {
+    pub value: Value,
+    pub get_snapshot: Value,
 }
 
 impl Hook {
@@ -40,25 +117,240 @@ impl Hook {
             memoized_state,
             update_queue,
             next,
+            base_state: None,
+            base_queue: None,
+        }
+    }
+}
+
+// a hook as devtools and snapshots show it
+pub struct HookState {
+    // State for useState and the hooks built on it, an effect kind or Memo for the rest
+    pub kind: &'static str,
+    // the state, the deps of an effect
+    pub value: Value,
+}
+
+// the hooks of a rendered function component, in call order
+pub fn inspect_hooks(fiber: &Rc<RefCell<FiberNode>>) -> Vec<HookState> {
+    let mut hooks = vec![];
+    let mut hook = match fiber.borrow().memoized_state.clone() {
+        Some(MemoizedState::Hook(hook)) => Some(hook),
+        _ => None,
+    };
+    while let Some(current) = hook {
+        let current = current.borrow();
+        let hook_state = match (&current.memoized_state, &current.update_queue) {
+            (Some(MemoizedState::Effect(effect)), _) => {
+                let effect = effect.borrow();
+                let kind = if effect.tag.contains(HookEffectTags::Insertion) {
+                    "InsertionEffect"
+                } else if effect.tag.contains(HookEffectTags::Layout) {
+                    "LayoutEffect"
+                } else {
+                    "Effect"
+                };
+                HookState {
+                    kind,
+                    value: effect.deps.clone(),
+                }
+            }
+            (memoized_state, Some(_)) => HookState {
+                kind: "State",
+                value: memoized_state
+                    .as_ref()
+                    .and_then(|state| state.value())
+                    .unwrap_or_else(Value::undefined),
+            },
+            (memoized_state, None) => HookState {
+                kind: "Memo",
+                value: memoized_state
+                    .as_ref()
+                    .and_then(|state| state.value())
+                    .unwrap_or_else(Value::undefined),
+            },
+        };
+        hooks.push(hook_state);
+        hook = current.next.clone();
+    }
+    hooks
+}
+
+// false when the hook at hook_index is not a state hook
+pub fn override_hook_state(
+    fiber: &Rc<RefCell<FiberNode>>,
+    hook_index: usize,
+    value: Value,
+) -> bool {
+    let mut hook = match fiber.borrow().memoized_state.clone() {
+        Some(MemoizedState::Hook(hook)) => Some(hook),
+        _ => None,
+    };
+    for _ in 0..hook_index {
+        hook = hook.and_then(|hook| hook.borrow().next.clone());
+    }
+    let update_queue = match hook {
+        Some(hook) => match (&hook.borrow().memoized_state, &hook.borrow().update_queue) {
+            (Some(MemoizedState::Effect(_)), _) => None,
+            (_, update_queue) => update_queue.clone(),
+        },
+        None => None,
+    };
+    match update_queue {
+        Some(update_queue) => {
+            dispatch_set_state(fiber.clone(), update_queue, value);
+            true
         }
+        None => false,
+    }
+}
+
+// the hooks of every component that mounts or updates, built once
+fn create_dispatcher(is_update: bool) -> Dispatcher {
+    let use_state = if is_update { update_state } else { mount_state };
+    let use_transition = if is_update {
+        update_transition
+    } else {
+        mount_transition
+    };
+    let use_deferred_value = if is_update {
+        update_deferred_value
+    } else {
+        mount_deferred_value
+    };
+    let use_sync_external_store = if is_update {
+        update_sync_external_store
+    } else {
+        mount_sync_external_store
+    };
+    let use_id = if is_update { update_id } else { mount_id };
+    let use_imperative_handle = if is_update {
+        update_imperative_handle
+    } else {
+        mount_imperative_handle
+    };
+    let use_insertion_effect = if is_update {
+        update_insertion_effect
+    } else {
+        mount_insertion_effect
+    };
+    let use_memo = if is_update { update_memo } else { mount_memo };
+
+    Dispatcher {
+        use_state: Value::function(move |args| use_state(Value::arg(args, 0))),
+        _use: Value::function(|args| _use(Value::arg(args, 0))),
+        use_transition: Value::function(move |_| use_transition()),
+        use_deferred_value: Value::function(move |args| {
+            use_deferred_value(Value::arg(args, 0), Value::arg(args, 1))
+        }),
+        use_sync_external_store: Value::function(move |args| {
+            use_sync_external_store(
+                Value::arg(args, 0),
+                Value::arg(args, 1),
+                Value::arg(args, 2),
+            )
+        }),
+        use_id: Value::function(move |_| use_id()),
+        use_imperative_handle: Value::function(move |args| {
+            use_imperative_handle(
+                Value::arg(args, 0),
+                Value::arg(args, 1),
+                Value::arg(args, 2),
+            )
+        }),
+        use_insertion_effect: Value::function(move |args| {
+            use_insertion_effect(Value::arg(args, 0), Value::arg(args, 1))
+        }),
+        use_memo: Value::function(move |args| use_memo(Value::arg(args, 0), Value::arg(args, 1))),
     }
 }
 
+thread_local! {
+    static HOOKS_DISPATCHER_ON_MOUNT: Rc<Dispatcher> = Rc::new(create_dispatcher(false));
+    static HOOKS_DISPATCHER_ON_UPDATE: Rc<Dispatcher> = Rc::new(create_dispatcher(true));
+}
+
+// the same dispatchers as the react module sees them, built once as well
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    static JS_HOOKS_DISPATCHER_ON_MOUNT: JsValue =
+        HOOKS_DISPATCHER_ON_MOUNT.with(|dispatcher| dispatcher.to_js());
+    static JS_HOOKS_DISPATCHER_ON_UPDATE: JsValue =
+        HOOKS_DISPATCHER_ON_UPDATE.with(|dispatcher| dispatcher.to_js());
+}
+
 fn update_hooks_to_dispatcher(is_update: bool) {
-    let object = Object::new();
+    #[cfg(target_arch = "wasm32")]
+    if is_update {
+        JS_HOOKS_DISPATCHER_ON_UPDATE.with(updateDispatcher);
+    } else {
+        JS_HOOKS_DISPATCHER_ON_MOUNT.with(updateDispatcher);
+    }
+    // rust components call the hooks of the copy of react linked into this crate
+    let dispatcher = if is_update {
+        HOOKS_DISPATCHER_ON_UPDATE.with(Rc::clone)
+    } else {
+        HOOKS_DISPATCHER_ON_MOUNT.with(Rc::clone)
+    };
+    set_current_dispatcher(Some(dispatcher));
+}
+
+fn take_thenable_state(work_in_progress: &Rc<RefCell<FiberNode>>) -> Option<Vec<Value>> {
+    unsafe {
+        match &SUSPENDED_THENABLE_STATE {
+            Some((fiber, _)) if Rc::ptr_eq(fiber, work_in_progress) => SUSPENDED_THENABLE_STATE
+                .take()
+                .map(|(_, thenable_state)| thenable_state),
+            _ => None,
+        }
+    }
+}
+
+pub fn render_with_hooks(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    component: Value,
+    second_arg: Value,
+    lane: Lane,
+) -> Result<Value, Value> {
+    unsafe {
+        THENABLE_STATE = take_thenable_state(&work_in_progress);
+    }
+
+    let mut children = call_component(work_in_progress.clone(), &component, &second_arg, lane);
+    if __DEV__
+        && children.is_ok()
+        && work_in_progress
+            .borrow()
+            .mode
+            .contains(TypeOfMode::StrictLegacyMode)
+    {
+        // an impure component shows up when the second render differs, its hooks are the ones kept
+        children = call_component(work_in_progress.clone(), &component, &second_arg, lane);
+    }
 
-    let closure = Closure::wrap(Box::new(if is_update { update_state } else { mount_state })
-        as Box<dyn Fn(&JsValue) -> Result<Vec<JsValue>, JsValue>>);
-    let function = closure.as_ref().unchecked_ref::<Function>().clone();
-    closure.forget();
-    Reflect::set(&object, &"use_state".into(), &function).expect("TODO: panic set use_state");
+    unsafe {
+        if let Err(thrown_value) = &children {
+            if is_thenable(thrown_value) {
+                if let Some(thenable_state) = THENABLE_STATE.take() {
+                    SUSPENDED_THENABLE_STATE = Some((work_in_progress.clone(), thenable_state));
+                }
+            }
+        }
+        THENABLE_STATE = None;
+    }
 
-    updateDispatcher(&object.into());
+    children
 }
 
-pub fn render_with_hooks(work_in_progress: Rc<RefCell<FiberNode>>) -> Result<JsValue, JsValue> {
+fn call_component(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    component: &Value,
+    second_arg: &Value,
+    lane: Lane,
+) -> Result<Value, Value> {
     unsafe {
         CURRENTLY_RENDERING_FIBER = Some(work_in_progress.clone());
+        RENDER_LANE = lane;
     }
 
     let work_in_progress_cloned = work_in_progress.clone();
@@ -68,38 +360,114 @@ pub fn render_with_hooks(work_in_progress: Rc<RefCell<FiberNode>>) -> Result<JsV
     }
 
     let current = work_in_progress_cloned.borrow().alternate.clone();
-    if current.is_some() {
-        update_hooks_to_dispatcher(true);
-    } else {
-        update_hooks_to_dispatcher(false);
+    if __DEV__ {
+        unsafe {
+            HOOK_TYPES_DEV = vec![];
+            PREV_HOOK_TYPES_DEV = current
+                .as_ref()
+                .and_then(|current| current.borrow().debug_hook_types.clone());
+        }
     }
+    let props = work_in_progress_cloned.borrow().pending_props.clone();
 
-    let _type;
-    let props;
-    {
-        let work_in_progress_borrow = work_in_progress_cloned.borrow();
-        _type = work_in_progress_borrow._type.clone();
-        props = work_in_progress_borrow.pending_props.clone();
+    unsafe {
+        // thenables are looked up by index, a second render reuses those of the first
+        THENABLE_INDEX_COUNTER = 0;
+        LOCAL_ID_COUNTER = 0;
     }
 
-    let component = JsValue::dyn_ref::<Function>(&_type).unwrap();
-    let children = component.call1(&JsValue::null(), &props);
+    update_hooks_to_dispatcher(current.is_some());
+    let mut children = match component.downcast_ref::<JsValue>() {
+        Some(js_component) if is_rust_component(js_component) => {
+            render_rust_component(js_component, &props.to_js())
+                .map(Value::from)
+                .map_err(Value::from)
+        }
+        _ => component.call(&[props, second_arg.clone()]),
+    };
+    // hooks called from now on are outside of a component
+    reset_dispatcher();
+
+    // hooks of the previous render that were not reached this time
+    let mut did_render_too_few_hooks = unsafe {
+        CURRENT_HOOK
+            .as_ref()
+            .is_some_and(|current_hook| current_hook.borrow().next.is_some())
+    };
 
     unsafe {
         CURRENTLY_RENDERING_FIBER = None;
         WORK_IN_PROGRESS_HOOK = None;
         CURRENT_HOOK = None;
+        RENDER_LANE = Lane::NoLane;
+        THENABLE_INDEX_COUNTER = 0;
+        DID_RENDER_ID_HOOK = LOCAL_ID_COUNTER != 0;
+        LOCAL_ID_COUNTER = 0;
+    }
+
+    if __DEV__ && children.is_ok() {
+        let hook_types = unsafe { std::mem::take(&mut HOOK_TYPES_DEV) };
+        let prev_hook_types = unsafe { PREV_HOOK_TYPES_DEV.take() };
+        if let Some(prev_hook_types) = prev_hook_types {
+            // also catches a component that called no hook at all this time
+            did_render_too_few_hooks |= hook_types.len() < prev_hook_types.len();
+        }
+        work_in_progress.borrow_mut().debug_hook_types = Some(hook_types);
+    }
+
+    // as with more hooks, the render is thrown away rather than committed with missing state
+    if children.is_ok() && did_render_too_few_hooks {
+        children = Err(Value::from_string(
+            "Rendered fewer hooks than expected. This may be caused by an accidental early return statement.",
+        ));
     }
 
     children
 }
 
+fn reset_dispatcher() {
+    #[cfg(target_arch = "wasm32")]
+    updateDispatcher(&JsValue::null());
+    set_current_dispatcher(None);
+}
+
+// in development, every hook a component calls is compared with the one at the same
+// position in its last render
+fn record_hook_type_dev(hook_name: &'static str) {
+    if !__DEV__ {
+        return;
+    }
+    unsafe {
+        HOOK_TYPES_DEV.push(hook_name);
+        let index = HOOK_TYPES_DEV.len() - 1;
+        if let (Some(prev_hook_types), Some(fiber)) =
+            (&PREV_HOOK_TYPES_DEV, &CURRENTLY_RENDERING_FIBER)
+        {
+            // more hooks than last time is reported by update_work_in_progress_hook
+            if index < prev_hook_types.len() && prev_hook_types[index] != hook_name {
+                warn_on_hook_mismatch_in_dev(fiber, prev_hook_types, &HOOK_TYPES_DEV);
+            }
+        }
+    }
+}
+
+pub fn check_did_render_id_hook() -> bool {
+    unsafe {
+        let did_render_id_hook = DID_RENDER_ID_HOOK;
+        DID_RENDER_ID_HOOK = false;
+        did_render_id_hook
+    }
+}
+
 fn mount_work_in_progress_hook() -> Option<Rc<RefCell<Hook>>> {
     let hook = Rc::new(RefCell::new(Hook::new(None, None, None)));
     unsafe {
         if WORK_IN_PROGRESS_HOOK.is_none() {
             if CURRENTLY_RENDERING_FIBER.is_none() {
-                log!("WORK_IN_PROGRESS_HOOK and CURRENTLY_RENDERING_FIBER is empty")
+                error!(
+                    Hooks,
+                    "WORK_IN_PROGRESS_HOOK and CURRENTLY_RENDERING_FIBER is empty"
+                )
             } else {
                 CURRENTLY_RENDERING_FIBER
                     .as_ref()
@@ -122,14 +490,11 @@ fn mount_work_in_progress_hook() -> Option<Rc<RefCell<Hook>>> {
     }
 }
 
-fn update_work_in_progress_hook() -> Option<Rc<RefCell<Hook>>> {
+fn update_work_in_progress_hook() -> Result<Option<Rc<RefCell<Hook>>>, Value> {
     // case1: Update triggered by interaction, the wip_hook is none, use hook in current_hook to clone wip_hook
     // case2: Update triggered in render process, the wip_hook exists
-    let mut next_current_hook: Option<Rc<RefCell<Hook>>> = None;
-    let mut next_work_in_progress_hook: Option<Rc<RefCell<Hook>>> = None;
-
     unsafe {
-        next_current_hook = match &CURRENT_HOOK {
+        let next_current_hook: Option<Rc<RefCell<Hook>>> = match &CURRENT_HOOK {
             None => {
                 let current = CURRENTLY_RENDERING_FIBER
                     .as_ref()
@@ -150,7 +515,7 @@ fn update_work_in_progress_hook() -> Option<Rc<RefCell<Hook>>> {
             Some(current_hook) => current_hook.clone().borrow().next.clone(),
         };
 
-        next_work_in_progress_hook = match &WORK_IN_PROGRESS_HOOK {
+        let next_work_in_progress_hook: Option<Rc<RefCell<Hook>>> = match &WORK_IN_PROGRESS_HOOK {
             None => match CURRENTLY_RENDERING_FIBER.clone() {
                 Some(current) => match current.clone().borrow().memoized_state.clone() {
                     Some(MemoizedState::Hook(memoized_state)) => Some(memoized_state.clone()),
@@ -166,15 +531,9 @@ fn update_work_in_progress_hook() -> Option<Rc<RefCell<Hook>>> {
             CURRENT_HOOK = next_current_hook.clone();
         } else {
             if next_current_hook.is_none() {
-                log!(
-                    "{:?} hooks is more than last",
-                    CURRENTLY_RENDERING_FIBER
-                        .as_ref()
-                        .unwrap()
-                        .clone()
-                        .borrow()
-                        ._type
-                );
+                return Err(Value::from_string(
+                    "Rendered more hooks than during the previous render.",
+                ));
             }
 
             CURRENT_HOOK = next_current_hook;
@@ -185,6 +544,8 @@ fn update_work_in_progress_hook() -> Option<Rc<RefCell<Hook>>> {
                 current_hook.update_queue.clone(),
                 None,
             )));
+            new_hook.borrow_mut().base_state = current_hook.base_state.clone();
+            new_hook.borrow_mut().base_queue = current_hook.base_queue.clone();
 
             if WORK_IN_PROGRESS_HOOK.is_none() {
                 WORK_IN_PROGRESS_HOOK = Some(new_hook.clone());
@@ -200,88 +561,807 @@ fn update_work_in_progress_hook() -> Option<Rc<RefCell<Hook>>> {
                 WORK_IN_PROGRESS_HOOK = Some(new_hook.clone());
             }
         }
-        WORK_IN_PROGRESS_HOOK.clone()
+        Ok(WORK_IN_PROGRESS_HOOK.clone())
     }
 }
 
-fn mount_state(initial_state: &JsValue) -> Result<Vec<JsValue>, JsValue> {
-    let hook = mount_work_in_progress_hook();
-    let memoized_state: JsValue;
+fn mount_state(initial_state: Value) -> Result<Value, Value> {
+    record_hook_type_dev("useState");
+    let (state, dispatch) = mount_state_impl(initial_state)?;
+    Ok(Value::from_list(vec![state, dispatch]))
+}
 
-    if initial_state.is_function() {
-        memoized_state = initial_state
-            .dyn_ref::<Function>()
-            .unwrap()
-            .call0(&JsValue::null())?;
+fn mount_state_impl(initial_state: Value) -> Result<(Value, Value), Value> {
+    let hook = mount_work_in_progress_hook();
+    let memoized_state = if initial_state.is_function() {
+        initial_state.call(&[])?
     } else {
-        memoized_state = initial_state.clone();
-    }
+        initial_state
+    };
     hook.as_ref().unwrap().clone().borrow_mut().memoized_state =
-        Some(MemoizedState::JsValue(memoized_state.clone()));
+        Some(MemoizedState::Value(memoized_state.clone()));
+    hook.as_ref().unwrap().clone().borrow_mut().base_state =
+        Some(MemoizedState::Value(memoized_state.clone()));
 
     unsafe {
         if CURRENTLY_RENDERING_FIBER.is_none() {
-            log!("mount_state, currentlyRenderingFiber is empty");
+            error!(Hooks, "mount_state, currentlyRenderingFiber is empty");
         }
     }
     let queue = create_update_queue();
     hook.as_ref().unwrap().clone().borrow_mut().update_queue = Some(queue.clone());
-    let q_rc = Rc::new(queue.clone());
-    let q_rc_cloned = q_rc.clone();
     let fiber = unsafe { CURRENTLY_RENDERING_FIBER.clone().unwrap() };
-    let closure = Closure::wrap(Box::new(move |action: &JsValue| {
-        dispatch_set_state(fiber.clone(), (*q_rc_cloned).clone(), action)
-    }) as Box<dyn Fn(&JsValue)>);
-    let function = closure.as_ref().unchecked_ref::<Function>().clone();
-    closure.forget();
+    let dispatch_queue = queue.clone();
+    let dispatch = Value::function(move |args| {
+        dispatch_set_state(fiber.clone(), dispatch_queue.clone(), Value::arg(args, 0));
+        Ok(Value::undefined())
+    });
 
-    queue.clone().borrow_mut().dispatch = Some(function.clone());
+    queue.borrow_mut().dispatch = Some(dispatch.clone());
 
-    Ok(vec![memoized_state, function.into()])
+    Ok((memoized_state, dispatch))
 }
 
-fn update_state(initial_state: &JsValue) -> Result<Vec<JsValue>, JsValue> {
-    let hook = update_work_in_progress_hook();
+fn update_state(action: Value) -> Result<Value, Value> {
+    record_hook_type_dev("useState");
+    let (state, dispatch) = update_state_impl(action)?;
+    Ok(Value::from_list(vec![state, dispatch]))
+}
+
+fn update_state_impl(_: Value) -> Result<(Value, Value), Value> {
+    let hook = update_work_in_progress_hook()?;
 
     if hook.is_none() {
         panic!("update_state hook is none")
     }
 
     let hook_cloned = hook.clone().unwrap().clone();
-    let queue = hook_cloned.borrow().update_queue.clone();
-    let base_state = hook_cloned.borrow().memoized_state.clone();
+    let queue = hook_cloned.borrow().update_queue.clone().unwrap();
+    let base_state = hook_cloned.borrow().base_state.clone();
+    let pending = queue.borrow().shared.pending.clone();
+    let current = unsafe { CURRENT_HOOK.clone().unwrap() };
+    let mut base_queue = current.borrow().base_queue.clone();
+
+    if let Some(pending) = pending {
+        if let Some(base_queue) = base_queue.clone() {
+            // append the pending updates after the ones skipped by earlier renders
+            let base_first = base_queue.borrow().next.clone();
+            let pending_first = pending.borrow().next.clone();
+            base_queue.borrow_mut().next = pending_first;
+            pending.borrow_mut().next = base_first;
+        }
+        base_queue = Some(pending);
+        // kept on the current hook as well, in case this render is thrown away
+        current.borrow_mut().base_queue = base_queue.clone();
+        queue.borrow_mut().shared.pending = None;
+    }
+
+    if base_queue.is_some() {
+        let result = process_update_queue(base_state, base_queue, unsafe { RENDER_LANE })?;
+        let mut hook = hook_cloned.borrow_mut();
+        hook.memoized_state = result.memoized_state;
+        hook.base_state = result.base_state;
+        hook.base_queue = result.base_queue;
+    }
+    trace!(
+        Hooks,
+        "memoized_state {:?}",
+        hook_cloned.borrow().memoized_state
+    );
+
+    let state = hook_cloned
+        .borrow()
+        .memoized_state
+        .clone()
+        .unwrap()
+        .value()
+        .unwrap();
+    let dispatch = queue
+        .borrow()
+        .dispatch
+        .clone()
+        .unwrap_or_else(Value::undefined);
+    Ok((state, dispatch))
+}
+
+fn mount_transition() -> Result<Value, Value> {
+    record_hook_type_dev("useTransition");
+    let (is_pending, set_pending) = mount_state_impl(Value::from_bool(false))?;
+
+    let hook = mount_work_in_progress_hook();
+    let start = Value::function(move |args| {
+        start_transition(&set_pending, Value::arg(args, 0))?;
+        Ok(Value::undefined())
+    });
+    hook.as_ref().unwrap().clone().borrow_mut().memoized_state =
+        Some(MemoizedState::Value(start.clone()));
+
+    Ok(Value::from_list(vec![is_pending, start]))
+}
+
+fn update_transition() -> Result<Value, Value> {
+    record_hook_type_dev("useTransition");
+    let (is_pending, _) = update_state_impl(Value::undefined())?;
+    let hook = update_work_in_progress_hook()?;
+    let start = hook
+        .as_ref()
+        .unwrap()
+        .clone()
+        .borrow()
+        .memoized_state
+        .clone()
+        .unwrap()
+        .value()
+        .unwrap();
+
+    Ok(Value::from_list(vec![is_pending, start]))
+}
+
+fn start_transition(set_pending: &Value, callback: Value) -> Result<(), Value> {
+    // the pending flag is urgent, everything inside the callback is not
+    set_pending.call(&[Value::from_bool(true)])?;
+
+    let set_pending = set_pending.clone();
+    start_transition_scope(move || {
+        set_pending.call(&[Value::from_bool(false)])?;
+        callback.call(&[])?;
+        Ok(())
+    })
+}
+
+fn mount_deferred_value(value: Value, initial_value: Value) -> Result<Value, Value> {
+    record_hook_type_dev("useDeferredValue");
+    let hook = mount_work_in_progress_hook();
+    let value = if !initial_value.is_same(&Value::undefined())
+        && !includes_only_non_urgent_lanes(unsafe { RENDER_LANE })
+    {
+        // show the initial value first, the deferred render switches to the real one
+        request_deferred_lane();
+        initial_value
+    } else {
+        value
+    };
+    hook.as_ref().unwrap().clone().borrow_mut().memoized_state =
+        Some(MemoizedState::Value(value.clone()));
+    Ok(value)
+}
+
+fn update_deferred_value(value: Value, _: Value) -> Result<Value, Value> {
+    record_hook_type_dev("useDeferredValue");
+    let hook = update_work_in_progress_hook()?;
+    let prev_value = hook
+        .as_ref()
+        .unwrap()
+        .clone()
+        .borrow()
+        .memoized_state
+        .clone()
+        .unwrap()
+        .value()
+        .unwrap();
+
+    if value.is_same(&prev_value) {
+        return Ok(value);
+    }
+
+    if !includes_only_non_urgent_lanes(unsafe { RENDER_LANE }) {
+        // urgent render, keep showing the previous value and re-render with the new one later
+        request_deferred_lane();
+        return Ok(prev_value);
+    }
+
+    hook.as_ref().unwrap().clone().borrow_mut().memoized_state =
+        Some(MemoizedState::Value(value.clone()));
+    Ok(value)
+}
+
+fn function_component_update_queue(fiber: &Rc<RefCell<FiberNode>>) -> Rc<RefCell<UpdateQueue>> {
+    let update_queue = fiber.borrow().update_queue.clone();
+    match update_queue {
+        Some(update_queue) => update_queue,
+        None => {
+            let update_queue = create_update_queue();
+            fiber.borrow_mut().update_queue = Some(update_queue.clone());
+            update_queue
+        }
+    }
+}
+
+fn push_effect(
+    tag: HookEffectTags,
+    create: Value,
+    destroy: Value,
+    deps: Value,
+) -> Rc<RefCell<Effect>> {
+    let effect = Rc::new(RefCell::new(Effect {
+        tag,
+        create,
+        destroy,
+        deps,
+        next: None,
+    }));
+    let fiber = unsafe { CURRENTLY_RENDERING_FIBER.clone().unwrap() };
+    let update_queue = function_component_update_queue(&fiber);
+    let last_effect = update_queue.borrow().last_effect.clone();
+    match last_effect {
+        None => {
+            effect.borrow_mut().next = Some(effect.clone());
+        }
+        Some(last_effect) => {
+            let first_effect = last_effect.borrow().next.clone();
+            last_effect.borrow_mut().next = Some(effect.clone());
+            effect.borrow_mut().next = first_effect;
+        }
+    }
+    // last_effect always points to the last effect, last_effect.next is the first one
+    update_queue.borrow_mut().last_effect = Some(effect.clone());
+    effect
+}
+
+// development only, the hook being called for the warnings about its deps
+fn current_hook_name_dev() -> &'static str {
+    unsafe { HOOK_TYPES_DEV.last().copied().unwrap_or("a hook") }
+}
+
+fn are_hook_inputs_equal(next_deps: &Value, prev_deps: &Value) -> bool {
+    // without deps the hook runs on every render
+    if next_deps.is_nullish() || prev_deps.is_nullish() {
+        return false;
+    }
+    let (Some(next_deps), Some(prev_deps)) = (next_deps.as_list(), prev_deps.as_list()) else {
+        if __DEV__ {
+            let not_a_list = if next_deps.as_list().is_none() {
+                next_deps
+            } else {
+                prev_deps
+            };
+            error!(
+                Hooks,
+                "{} received a final argument that is not an array (instead, received `{:?}`). When specified, the final argument must be an array.",
+                current_hook_name_dev(),
+                not_a_list
+            );
+        }
+        return false;
+    };
+    if next_deps.len() != prev_deps.len() {
+        if __DEV__ {
+            error!(
+                Hooks,
+                "The final argument passed to {} changed size between renders. The order and size of this array must remain constant.\n\nPrevious: {:?}\nIncoming: {:?}",
+                current_hook_name_dev(),
+                prev_deps,
+                next_deps
+            );
+        }
+        return false;
+    }
+    next_deps
+        .iter()
+        .zip(prev_deps.iter())
+        .all(|(next, prev)| next.is_same(prev))
+}
+
+fn mount_effect_impl(fiber_flags: Flags, hook_flags: HookEffectTags, create: Value, deps: Value) {
+    let hook = mount_work_in_progress_hook();
+    unsafe {
+        CURRENTLY_RENDERING_FIBER
+            .as_ref()
+            .unwrap()
+            .clone()
+            .borrow_mut()
+            .flags |= fiber_flags;
+    }
+    hook.as_ref().unwrap().clone().borrow_mut().memoized_state =
+        Some(MemoizedState::Effect(push_effect(
+            HookEffectTags::HasEffect | hook_flags,
+            create,
+            Value::undefined(),
+            deps,
+        )));
+}
+
+fn update_effect_impl(
+    fiber_flags: Flags,
+    hook_flags: HookEffectTags,
+    create: Value,
+    deps: Value,
+) -> Result<(), Value> {
+    let hook = update_work_in_progress_hook()?;
+    let mut destroy = Value::undefined();
+
+    let current_hook = unsafe { CURRENT_HOOK.clone() };
+    if let Some(current_hook) = current_hook {
+        if let Some(MemoizedState::Effect(prev_effect)) =
+            current_hook.borrow().memoized_state.clone()
+        {
+            destroy = prev_effect.borrow().destroy.clone();
+            if are_hook_inputs_equal(&deps, &prev_effect.borrow().deps) {
+                // deps are unchanged, keep the effect in the list without HasEffect
+                hook.as_ref().unwrap().clone().borrow_mut().memoized_state = Some(
+                    MemoizedState::Effect(push_effect(hook_flags, create, destroy, deps)),
+                );
+                return Ok(());
+            }
+        }
+    }
 
-    // Todo update when render
     unsafe {
-        hook_cloned.borrow_mut().memoized_state = process_update_queue(
-            base_state,
-            queue.clone(),
-            CURRENTLY_RENDERING_FIBER.clone().unwrap(),
+        CURRENTLY_RENDERING_FIBER
+            .as_ref()
+            .unwrap()
+            .clone()
+            .borrow_mut()
+            .flags |= fiber_flags;
+    }
+    hook.as_ref().unwrap().clone().borrow_mut().memoized_state =
+        Some(MemoizedState::Effect(push_effect(
+            HookEffectTags::HasEffect | hook_flags,
+            create,
+            destroy,
+            deps,
+        )));
+    Ok(())
+}
+
+fn effect_deps(deps: Value) -> Value {
+    // no deps means the effect runs after every render
+    if deps.is_nullish() {
+        return Value::null();
+    }
+    deps
+}
+
+fn mount_insertion_effect(create: Value, deps: Value) -> Result<Value, Value> {
+    record_hook_type_dev("useInsertionEffect");
+    mount_effect_impl(
+        Flags::Update,
+        HookEffectTags::Insertion,
+        create,
+        effect_deps(deps),
+    );
+    Ok(Value::undefined())
+}
+
+fn update_insertion_effect(create: Value, deps: Value) -> Result<Value, Value> {
+    record_hook_type_dev("useInsertionEffect");
+    update_effect_impl(
+        Flags::Update,
+        HookEffectTags::Insertion,
+        create,
+        effect_deps(deps),
+    )?;
+    Ok(Value::undefined())
+}
+
+// the hook keeps [value, deps], StrictMode calls create twice as it renders the component twice
+fn mount_memo(create: Value, deps: Value) -> Result<Value, Value> {
+    record_hook_type_dev("useMemo");
+    let hook = mount_work_in_progress_hook();
+    let next_deps = effect_deps(deps);
+    let next_value = create.call(&[])?;
+    hook.as_ref().unwrap().clone().borrow_mut().memoized_state =
+        Some(MemoizedState::Value(Value::from_list(vec![
+            next_value.clone(),
+            next_deps,
+        ])));
+    Ok(next_value)
+}
+
+fn update_memo(create: Value, deps: Value) -> Result<Value, Value> {
+    record_hook_type_dev("useMemo");
+    let hook = update_work_in_progress_hook()?;
+    let next_deps = effect_deps(deps);
+    let prev_state = hook
+        .as_ref()
+        .unwrap()
+        .borrow()
+        .memoized_state
+        .clone()
+        .and_then(|memoized_state| memoized_state.value())
+        .and_then(|value| value.as_list());
+    if let Some([prev_value, prev_deps]) = prev_state.as_deref() {
+        if are_hook_inputs_equal(&next_deps, prev_deps) {
+            return Ok(prev_value.clone());
+        }
+    }
+
+    let next_value = create.call(&[])?;
+    hook.as_ref().unwrap().clone().borrow_mut().memoized_state =
+        Some(MemoizedState::Value(Value::from_list(vec![
+            next_value.clone(),
+            next_deps,
+        ])));
+    Ok(next_value)
+}
+
+fn imperative_handle_effect(create: Value, _ref: Value) -> Value {
+    Value::function(move |_| {
+        if _ref.is_function() {
+            let inst = create.call(&[])?;
+            _ref.call(&[inst])?;
+            let ref_callback = _ref.clone();
+            return Ok(Value::function(move |_| {
+                ref_callback.call(&[Value::null()])
+            }));
+        }
+        if !_ref.is_nullish() {
+            let inst = create.call(&[])?;
+            _ref.set("current", inst)?;
+            let _ref = _ref.clone();
+            return Ok(Value::function(move |_| {
+                _ref.set("current", Value::null())?;
+                Ok(Value::undefined())
+            }));
+        }
+        Ok(Value::undefined())
+    })
+}
+
+fn imperative_handle_deps(_ref: &Value, deps: &Value) -> Value {
+    // the handle has to be set again when the ref itself changes
+    match deps.as_list() {
+        Some(mut deps) => {
+            deps.push(_ref.clone());
+            Value::from_list(deps)
+        }
+        None => Value::null(),
+    }
+}
+
+fn mount_imperative_handle(_ref: Value, create: Value, deps: Value) -> Result<Value, Value> {
+    record_hook_type_dev("useImperativeHandle");
+    let effect_deps = imperative_handle_deps(&_ref, &deps);
+    mount_effect_impl(
+        Flags::Update,
+        HookEffectTags::Layout,
+        imperative_handle_effect(create, _ref),
+        effect_deps,
+    );
+    Ok(Value::undefined())
+}
+
+fn update_imperative_handle(_ref: Value, create: Value, deps: Value) -> Result<Value, Value> {
+    record_hook_type_dev("useImperativeHandle");
+    let effect_deps = imperative_handle_deps(&_ref, &deps);
+    update_effect_impl(
+        Flags::Update,
+        HookEffectTags::Layout,
+        imperative_handle_effect(create, _ref),
+        effect_deps,
+    )?;
+    Ok(Value::undefined())
+}
+
+// the snapshot the committed tree was rendered with, shared by current and wip
+#[derive(Debug)]
+struct StoreInstance {
+    value: RefCell<Value>,
+    get_snapshot: RefCell<Value>,
+}
+
+impl ReactValue for StoreInstance {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_same(&self, other: &Value) -> bool {
+        other
+            .downcast_ref::<StoreInstance>()
+            .is_some_and(|other| std::ptr::eq(self, other))
+    }
+
+    fn keys(&self) -> Option<Vec<String>> {
+        Some(vec!["value".to_string(), "getSnapshot".to_string()])
+    }
+
+    fn get(&self, name: &str) -> Value {
+        match name {
+            "value" => self.value.borrow().clone(),
+            "getSnapshot" => self.get_snapshot.borrow().clone(),
+            _ => Value::undefined(),
+        }
+    }
+}
+
+fn store_instance(inst: &Value) -> &StoreInstance {
+    inst.downcast_ref::<StoreInstance>()
+        .expect("the second hook of useSyncExternalStore holds its store instance")
+}
+
+// the snapshot of server rendered content, optional, but a given one has to be a function
+// whose result is cached like the one of get_snapshot
+fn check_server_snapshot(get_server_snapshot: &Value) -> Result<(), Value> {
+    if get_server_snapshot.is_nullish() {
+        return Ok(());
+    }
+    if !get_server_snapshot.is_function() {
+        return Err(Value::from_string(format!(
+            "getServerSnapshot must be a function, received {:?}",
+            get_server_snapshot
+        )));
+    }
+    if __DEV__ && unsafe { !DID_WARN_UNCACHED_SERVER_SNAPSHOT } {
+        let server_snapshot = get_server_snapshot.call(&[])?;
+        if !server_snapshot.is_same(&get_server_snapshot.call(&[])?) {
+            unsafe { DID_WARN_UNCACHED_SERVER_SNAPSHOT = true };
+            error!(
+                Hooks,
+                "The result of getServerSnapshot should be cached to avoid an infinite loop"
+            );
+        }
+    }
+    Ok(())
+}
+
+fn mount_sync_external_store(
+    subscribe: Value,
+    get_snapshot: Value,
+    get_server_snapshot: Value,
+) -> Result<Value, Value> {
+    record_hook_type_dev("useSyncExternalStore");
+    check_server_snapshot(&get_server_snapshot)?;
+    let fiber = unsafe { CURRENTLY_RENDERING_FIBER.clone().unwrap() };
+
+    let hook = mount_work_in_progress_hook();
+    let next_snapshot = get_snapshot.call(&[])?;
+    if includes_only_non_urgent_lanes(unsafe { RENDER_LANE }) {
+        push_store_consistency_check(&fiber, &get_snapshot, &next_snapshot);
+    }
+    hook.as_ref().unwrap().clone().borrow_mut().memoized_state =
+        Some(MemoizedState::Value(next_snapshot.clone()));
+
+    let inst = Value::new(StoreInstance {
+        value: RefCell::new(next_snapshot.clone()),
+        get_snapshot: RefCell::new(get_snapshot.clone()),
+    });
+    let inst_hook = mount_work_in_progress_hook();
+    inst_hook
+        .as_ref()
+        .unwrap()
+        .clone()
+        .borrow_mut()
+        .memoized_state = Some(MemoizedState::Value(inst.clone()));
+
+    mount_effect_impl(
+        Flags::PassiveEffect,
+        HookEffectTags::Passive,
+        subscribe_to_store(&fiber, &inst, &subscribe),
+        Value::from_list(vec![subscribe.clone()]),
+    );
+
+    fiber.borrow_mut().flags |= Flags::PassiveEffect;
+    push_effect(
+        HookEffectTags::HasEffect | HookEffectTags::Passive,
+        update_store_instance(&fiber, &inst, &next_snapshot, &get_snapshot),
+        Value::undefined(),
+        Value::null(),
+    );
+
+    Ok(next_snapshot)
+}
+
+fn update_sync_external_store(
+    subscribe: Value,
+    get_snapshot: Value,
+    get_server_snapshot: Value,
+) -> Result<Value, Value> {
+    record_hook_type_dev("useSyncExternalStore");
+    check_server_snapshot(&get_server_snapshot)?;
+    let fiber = unsafe { CURRENTLY_RENDERING_FIBER.clone().unwrap() };
+
+    let hook = update_work_in_progress_hook()?;
+    let next_snapshot = get_snapshot.call(&[])?;
+    let prev_snapshot = hook
+        .as_ref()
+        .unwrap()
+        .clone()
+        .borrow()
+        .memoized_state
+        .clone()
+        .unwrap()
+        .value()
+        .unwrap();
+    let snapshot_changed = !prev_snapshot.is_same(&next_snapshot);
+    if snapshot_changed {
+        hook.as_ref().unwrap().clone().borrow_mut().memoized_state =
+            Some(MemoizedState::Value(next_snapshot.clone()));
+    }
+
+    let inst_hook = update_work_in_progress_hook()?;
+    let inst = inst_hook
+        .as_ref()
+        .unwrap()
+        .clone()
+        .borrow()
+        .memoized_state
+        .clone()
+        .unwrap()
+        .value()
+        .unwrap();
+
+    update_effect_impl(
+        Flags::PassiveEffect,
+        HookEffectTags::Passive,
+        subscribe_to_store(&fiber, &inst, &subscribe),
+        Value::from_list(vec![subscribe.clone()]),
+    )?;
+
+    let get_snapshot_changed = !store_instance(&inst)
+        .get_snapshot
+        .borrow()
+        .is_same(&get_snapshot);
+    if get_snapshot_changed || snapshot_changed {
+        fiber.borrow_mut().flags |= Flags::PassiveEffect;
+        push_effect(
+            HookEffectTags::HasEffect | HookEffectTags::Passive,
+            update_store_instance(&fiber, &inst, &next_snapshot, &get_snapshot),
+            Value::undefined(),
+            Value::null(),
         );
+
+        if includes_only_non_urgent_lanes(unsafe { RENDER_LANE }) {
+            push_store_consistency_check(&fiber, &get_snapshot, &next_snapshot);
+        }
     }
-    log!("memoized_state {:?}", hook_cloned.borrow().memoized_state);
 
-    Ok(vec![
-        hook.clone()
+    Ok(next_snapshot)
+}
+
+fn push_store_consistency_check(
+    fiber: &Rc<RefCell<FiberNode>>,
+    get_snapshot: &Value,
+    value: &Value,
+) {
+    // a time sliced render can tear if the store changes before the commit,
+    // the work loop checks these once the render is done
+    fiber.borrow_mut().flags |= Flags::StoreConsistency;
+    function_component_update_queue(fiber)
+        .borrow_mut()
+        .stores
+        .push(StoreConsistencyCheck {
+            value: value.clone(),
+            get_snapshot: get_snapshot.clone(),
+        });
+}
+
+fn subscribe_to_store(fiber: &Rc<RefCell<FiberNode>>, inst: &Value, subscribe: &Value) -> Value {
+    let fiber = fiber.clone();
+    let inst = inst.clone();
+    let subscribe = subscribe.clone();
+    Value::function(move |_| {
+        let fiber = fiber.clone();
+        let inst = inst.clone();
+        let handle_store_change = Value::function(move |_| {
+            if check_if_snapshot_changed(&inst) {
+                force_store_rerender(fiber.clone());
+            }
+            Ok(Value::undefined())
+        });
+        // the unsubscribe function returned by the store becomes the effect's destroy
+        subscribe.call(&[handle_store_change])
+    })
+}
+
+fn update_store_instance(
+    fiber: &Rc<RefCell<FiberNode>>,
+    inst: &Value,
+    next_snapshot: &Value,
+    get_snapshot: &Value,
+) -> Value {
+    let fiber = fiber.clone();
+    let inst = inst.clone();
+    let next_snapshot = next_snapshot.clone();
+    let get_snapshot = get_snapshot.clone();
+    Value::function(move |_| {
+        let store = store_instance(&inst);
+        *store.value.borrow_mut() = next_snapshot.clone();
+        *store.get_snapshot.borrow_mut() = get_snapshot.clone();
+        // the store may have changed between render and this effect
+        if check_if_snapshot_changed(&inst) {
+            force_store_rerender(fiber.clone());
+        }
+        Ok(Value::undefined())
+    })
+}
+
+fn check_if_snapshot_changed(inst: &Value) -> bool {
+    let store = store_instance(inst);
+    let latest_get_snapshot = store.get_snapshot.borrow().clone();
+    let prev_value = store.value.borrow().clone();
+    match latest_get_snapshot.call(&[]) {
+        Ok(next_value) => !prev_value.is_same(&next_value),
+        Err(_) => true,
+    }
+}
+
+fn force_store_rerender(fiber: Rc<RefCell<FiberNode>>) {
+    // external stores can't be time sliced without tearing, so always re-render with the sync lane
+    unsafe {
+        WORK_LOOP
+            .as_ref()
             .unwrap()
             .clone()
             .borrow()
-            .memoized_state
-            .clone()
-            .unwrap()
-            .js_value()
-            .unwrap()
-            .clone(),
-        queue.clone().unwrap().borrow().dispatch.clone().into(),
-    ])
+            .schedule_update_on_fiber(fiber, Lane::SyncLane);
+    }
+}
+
+fn mount_id() -> Result<Value, Value> {
+    record_hook_type_dev("useId");
+    let hook = mount_work_in_progress_hook();
+    let mut id = format!(":R{}", get_tree_id());
+
+    // more than one useId in the same component
+    let local_id = unsafe { LOCAL_ID_COUNTER };
+    unsafe {
+        LOCAL_ID_COUNTER += 1;
+    }
+    if local_id > 0 {
+        id += &format!("H{}", to_base_32(local_id));
+    }
+    id += ":";
+
+    let id = Value::from_string(id);
+    hook.as_ref().unwrap().clone().borrow_mut().memoized_state =
+        Some(MemoizedState::Value(id.clone()));
+    Ok(id)
+}
+
+fn update_id() -> Result<Value, Value> {
+    record_hook_type_dev("useId");
+    let hook = update_work_in_progress_hook()?;
+    // still counted, children mounting in this render need the same fork as on mount
+    unsafe {
+        LOCAL_ID_COUNTER += 1;
+    }
+    Ok(hook
+        .as_ref()
+        .unwrap()
+        .clone()
+        .borrow()
+        .memoized_state
+        .clone()
+        .unwrap()
+        .value()
+        .unwrap())
+}
+
+fn _use(usable: Value) -> Result<Value, Value> {
+    if is_thenable(&usable) {
+        unsafe {
+            let index = THENABLE_INDEX_COUNTER;
+            THENABLE_INDEX_COUNTER += 1;
+            let thenable_state = THENABLE_STATE.get_or_insert_with(Vec::new);
+            return track_used_thenable(thenable_state, &usable, index);
+        }
+    } else if usable.type_of().as_deref() == Some(REACT_CONTEXT_TYPE) {
+        return Ok(read_context(&usable));
+    }
+    Err(Value::from_string(format!(
+        "An unsupported type was passed to use(): {:?}",
+        usable
+    )))
 }
 
 fn dispatch_set_state(
     fiber: Rc<RefCell<FiberNode>>,
     update_queue: Rc<RefCell<UpdateQueue>>,
-    action: &JsValue,
+    action: Value,
 ) {
-    let update = create_update(action.clone());
+    let root = unsafe {
+        WORK_LOOP
+            .as_ref()
+            .unwrap()
+            .borrow()
+            .mark_update_lane_from_fiber_to_root(fiber.clone())
+    };
+    if root.is_none() {
+        warn_about_update_on_unmounted_fiber_in_dev(&fiber);
+        return;
+    }
+    let lane = request_update_lane();
+    let update = create_update(action, lane);
     enqueue_update(update_queue.clone(), update);
     unsafe {
         WORK_LOOP
@@ -289,6 +1369,6 @@ fn dispatch_set_state(
             .unwrap()
             .clone()
             .borrow()
-            .schedule_update_on_fiber(fiber.clone());
+            .schedule_update_on_fiber(fiber.clone(), lane);
     }
 }
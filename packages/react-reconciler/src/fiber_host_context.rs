@@ -0,0 +1,39 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    fiber::{FiberNode, StateNode},
+    HostConfig,
+};
+
+// the host context, e.g. the namespace in react-dom, that host elements are created in
+static mut HOST_CONTEXT_STACK: Vec<String> = vec![];
+
+pub fn get_host_context() -> String {
+    unsafe { HOST_CONTEXT_STACK.last().cloned().unwrap_or_default() }
+}
+
+// HostRoot and HostPortal start a new context from their container
+pub fn push_host_container(host_config: &Rc<dyn HostConfig>, fiber: Rc<RefCell<FiberNode>>) {
+    let state_node = fiber.borrow().state_node.clone().unwrap();
+    let container = match &*state_node {
+        StateNode::FiberRootNode(root) => root.borrow().container.clone(),
+        StateNode::Portal(container_info) => host_config.get_portal_container(container_info),
+        StateNode::Element(_) => panic!("push_host_container expects a container"),
+    };
+    let context = host_config.get_root_host_context(container);
+    unsafe { HOST_CONTEXT_STACK.push(context) };
+}
+
+pub fn pop_host_container() {
+    unsafe { HOST_CONTEXT_STACK.pop() };
+}
+
+pub fn push_host_context(host_config: &Rc<dyn HostConfig>, fiber: Rc<RefCell<FiberNode>>) {
+    let _type = fiber.borrow()._type.as_string().unwrap();
+    let context = host_config.get_child_host_context(&get_host_context(), &_type);
+    unsafe { HOST_CONTEXT_STACK.push(context) };
+}
+
+pub fn pop_host_context() {
+    unsafe { HOST_CONTEXT_STACK.pop() };
+}
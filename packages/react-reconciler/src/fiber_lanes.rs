@@ -0,0 +1,77 @@
+use bitflags::bitflags;
+use scheduler::Priority;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    // lives in the `react` package, whose startTransition sets it
+    fn requestCurrentTransition() -> bool;
+}
+
+// without javascript there is only the copy of react linked into this crate
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(non_snake_case)]
+fn requestCurrentTransition() -> bool {
+    react::current_batch_config::request_current_transition()
+}
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Lane: u8 {
+        const NoLane = 0b00000000;
+        const SyncLane = 0b00000001;
+        const TransitionLane = 0b00000010;
+        const DeferredLane = 0b00000100;
+    }
+}
+
+// the lane forced on updates regardless of a surrounding transition, see flushSync
+static mut CURRENT_UPDATE_LANE: Lane = Lane::NoLane;
+
+pub fn get_current_update_lane() -> Lane {
+    unsafe { CURRENT_UPDATE_LANE }
+}
+
+pub fn set_current_update_lane(lane: Lane) {
+    unsafe {
+        CURRENT_UPDATE_LANE = lane;
+    }
+}
+
+pub fn merge_lanes(lane_a: Lane, lane_b: Lane) -> Lane {
+    lane_a | lane_b
+}
+
+pub fn is_subset_of_lanes(set: Lane, subset: Lane) -> bool {
+    (set & subset) == subset
+}
+
+pub fn includes_only_non_urgent_lanes(lanes: Lane) -> bool {
+    !lanes.intersects(Lane::SyncLane)
+}
+
+pub fn get_highest_priority_lane(lanes: Lane) -> Lane {
+    // the lowest set bit has the highest priority
+    Lane::from_bits_truncate(lanes.bits() & lanes.bits().wrapping_neg())
+}
+
+pub fn request_update_lane() -> Lane {
+    let current_update_lane = get_current_update_lane();
+    if current_update_lane != Lane::NoLane {
+        return current_update_lane;
+    }
+    if requestCurrentTransition() {
+        return Lane::TransitionLane;
+    }
+    Lane::SyncLane
+}
+
+pub fn lanes_to_scheduler_priority(lanes: Lane) -> Priority {
+    let lane = get_highest_priority_lane(lanes);
+    if lane == Lane::SyncLane {
+        return Priority::ImmediatePriority;
+    }
+    Priority::NormalPriority
+}
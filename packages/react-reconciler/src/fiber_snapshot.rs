@@ -0,0 +1,236 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+
+use shared::REACT_ELEMENT_TYPE;
+use wasm_bindgen::JsValue;
+use web_sys::js_sys::{Array, Object, Reflect};
+
+use crate::fiber::{FiberNode, FiberRootNode, StateNode};
+use crate::fiber_hooks::inspect_hooks;
+use crate::get_component_name::{get_component_name_from_fiber, get_component_name_from_type};
+use crate::react_value::Value;
+use crate::work_tags::WorkTag;
+
+// host nodes by the address of their instance, an entry whose instance was dropped is stale
+type HostNodeIds = HashMap<usize, (Weak<dyn Any>, u32)>;
+
+static mut HOST_NODE_IDS: Option<HostNodeIds> = None;
+static mut NEXT_HOST_NODE_ID: u32 = 1;
+// how deep objects in props and state are copied into a snapshot
+static MAX_DEPTH: u32 = 4;
+
+fn host_node_ids() -> &'static mut HostNodeIds {
+    unsafe { HOST_NODE_IDS.get_or_insert_with(HashMap::new) }
+}
+
+pub fn get_fiber_id(fiber: &Rc<RefCell<FiberNode>>) -> u32 {
+    fiber.borrow().debug_id
+}
+
+// once per commit rather than on every lookup
+pub(crate) fn prune_host_node_ids() {
+    host_node_ids().retain(|_, (weak, _)| weak.strong_count() > 0);
+}
+
+pub fn get_host_node_id(instance: &Rc<dyn Any>) -> u32 {
+    let address = Rc::as_ptr(instance) as *const () as usize;
+    let ids = host_node_ids();
+    if let Some((weak, id)) = ids.get(&address) {
+        if weak.strong_count() > 0 {
+            return *id;
+        }
+    }
+    let id = unsafe {
+        let id = NEXT_HOST_NODE_ID;
+        NEXT_HOST_NODE_ID += 1;
+        id
+    };
+    ids.insert(address, (Rc::downgrade(instance), id));
+    id
+}
+
+// a copy of value that JSON.stringify can handle, e.g. functions become "ƒ onClick()"
+pub fn serialize_value(value: &Value, depth: u32) -> JsValue {
+    if value.is_nullish() {
+        return JsValue::null();
+    }
+    if let Some(string) = value.as_string() {
+        return JsValue::from_str(&string);
+    }
+    if let Some(number) = value.as_f64() {
+        return JsValue::from_f64(number);
+    }
+    if let Some(boolean) = value.as_bool() {
+        return JsValue::from_bool(boolean);
+    }
+    if value.is_function() {
+        let name = value.get("name").as_string().unwrap_or_default();
+        return JsValue::from_str(&format!("ƒ {}()", name));
+    }
+    if value.type_of().as_deref() == Some(REACT_ELEMENT_TYPE) {
+        let name =
+            get_component_name_from_type(&value.element_type()).unwrap_or("Unknown".to_string());
+        return JsValue::from_str(&format!("<{} />", name));
+    }
+    let list = value.as_list();
+    let keys = value.keys();
+    if list.is_none() && keys.is_none() {
+        return JsValue::from_str(&format!("{:?}", value));
+    }
+    if depth >= MAX_DEPTH {
+        return JsValue::from_str(if list.is_some() { "[…]" } else { "{…}" });
+    }
+    if let Some(list) = list {
+        return list
+            .iter()
+            .map(|item| serialize_value(item, depth + 1))
+            .collect::<Array>()
+            .into();
+    }
+    let object = Object::new();
+    for key in keys.unwrap_or_default() {
+        let item = serialize_value(&value.get(&key), depth + 1);
+        Reflect::set(&object, &key.into(), &item).expect("snapshot panic");
+    }
+    object.into()
+}
+
+// children that are elements show up as fibers of their own
+fn serialize_props(props: &Value) -> JsValue {
+    let Some(keys) = props.keys() else {
+        return JsValue::null();
+    };
+    let object = Object::new();
+    for key in keys {
+        let value = props.get(&key);
+        if key == "children" && value.as_string().is_none() && value.as_f64().is_none() {
+            continue;
+        }
+        Reflect::set(&object, &key.into(), &serialize_value(&value, 1)).expect("snapshot panic");
+    }
+    object.into()
+}
+
+fn serialize_hooks(fiber: &Rc<RefCell<FiberNode>>) -> JsValue {
+    inspect_hooks(fiber)
+        .iter()
+        .enumerate()
+        .map(|(index, hook)| {
+            let object = Object::new();
+            Reflect::set(&object, &"id".into(), &JsValue::from(index as u32))
+                .expect("snapshot panic");
+            Reflect::set(&object, &"kind".into(), &JsValue::from_str(hook.kind))
+                .expect("snapshot panic");
+            Reflect::set(&object, &"value".into(), &serialize_value(&hook.value, 1))
+                .expect("snapshot panic");
+            JsValue::from(object)
+        })
+        .collect::<Array>()
+        .into()
+}
+
+// everything about fiber but its children
+fn snapshot_node(fiber: &Rc<RefCell<FiberNode>>) -> Object {
+    let object = Object::new();
+    let set = |key: &str, value: &JsValue| {
+        Reflect::set(&object, &key.into(), value).expect("snapshot panic");
+    };
+    {
+        let node = fiber.borrow();
+        set("id", &JsValue::from(get_fiber_id(fiber)));
+        set("tag", &JsValue::from_str(&format!("{:?}", node.tag)));
+        set(
+            "type",
+            &get_component_name_from_fiber(&node)
+                .map(|name| JsValue::from_str(&name))
+                .unwrap_or(JsValue::null()),
+        );
+        set("key", &serialize_value(&node.key, 0));
+        let props = match node.tag {
+            // a HostText fiber has its text as props
+            WorkTag::HostText => {
+                let props = Object::new();
+                let content = node.memoized_props.text_content().unwrap_or_default();
+                Reflect::set(&props, &"content".into(), &content.into()).expect("snapshot panic");
+                props.into()
+            }
+            _ => serialize_props(&node.memoized_props),
+        };
+        set("props", &props);
+        let hooks = match node.tag {
+            WorkTag::FunctionComponent | WorkTag::ForwardRef => serialize_hooks(fiber),
+            _ => Array::new().into(),
+        };
+        set("hooks", &hooks);
+        set(
+            "flags",
+            &node
+                .flags
+                .iter_names()
+                .map(|(name, _)| JsValue::from_str(name))
+                .collect::<Array>()
+                .into(),
+        );
+        let host_node_id = match node.state_node.as_deref() {
+            Some(StateNode::Element(instance)) => JsValue::from(get_host_node_id(instance)),
+            _ => JsValue::null(),
+        };
+        set("hostNodeId", &host_node_id);
+    }
+    object
+}
+
+fn for_each_child(fiber: &Rc<RefCell<FiberNode>>, mut f: impl FnMut(&Rc<RefCell<FiberNode>>)) {
+    let mut child = fiber.borrow().child.clone();
+    while let Some(node) = child {
+        f(&node);
+        child = node.borrow().sibling.clone();
+    }
+}
+
+// one fiber and its subtree as a plain object
+pub fn snapshot_fiber(fiber: &Rc<RefCell<FiberNode>>) -> JsValue {
+    let object = snapshot_node(fiber);
+    let children = Array::new();
+    for_each_child(fiber, |child| {
+        children.push(&snapshot_fiber(child));
+    });
+    Reflect::set(&object, &"children".into(), &children).expect("snapshot panic");
+    object.into()
+}
+
+// one fiber as a plain object, its children are only listed by id
+pub fn inspect_fiber(fiber: &Rc<RefCell<FiberNode>>) -> JsValue {
+    let object = snapshot_node(fiber);
+    let children = Array::new();
+    for_each_child(fiber, |child| {
+        children.push(&JsValue::from(get_fiber_id(child)));
+    });
+    Reflect::set(&object, &"children".into(), &children).expect("snapshot panic");
+    object.into()
+}
+
+// the committed tree of root, from the HostRoot fiber down
+pub fn snapshot_root(root: &Rc<RefCell<FiberRootNode>>) -> JsValue {
+    let current = root.borrow().current.clone();
+    snapshot_fiber(&current)
+}
+
+// the fiber of the committed tree of root that has id
+pub fn find_fiber_by_id(
+    root: &Rc<RefCell<FiberRootNode>>,
+    id: u32,
+) -> Option<Rc<RefCell<FiberNode>>> {
+    let mut stack = vec![root.borrow().current.clone()];
+    while let Some(fiber) = stack.pop() {
+        if get_fiber_id(&fiber) == id {
+            return Some(fiber);
+        }
+        for_each_child(&fiber, |child| stack.push(child.clone()));
+    }
+    None
+}
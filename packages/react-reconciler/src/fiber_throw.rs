@@ -0,0 +1,83 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    fiber::{FiberNode, FiberRootNode},
+    fiber_flags::Flags,
+    fiber_hooks::WORK_LOOP,
+    fiber_lanes::Lane,
+    react_value::Value,
+    work_tags::WorkTag,
+};
+
+pub fn is_thenable(value: &Value) -> bool {
+    value.get("then").is_function()
+}
+
+fn get_suspense_boundary(unit_of_work: Rc<RefCell<FiberNode>>) -> Option<Rc<RefCell<FiberNode>>> {
+    let mut node = unit_of_work;
+    let mut parent = node.borrow()._return.clone();
+    while let Some(p) = parent {
+        // a boundary only catches what suspends inside its primary (offscreen) tree,
+        // anything thrown from the fallback goes to the next boundary up
+        if p.borrow().tag == WorkTag::SuspenseComponent
+            && node.borrow().tag == WorkTag::OffscreenComponent
+        {
+            return Some(p);
+        }
+        parent = p.borrow()._return.clone();
+        node = p;
+    }
+    None
+}
+
+pub fn throw_exception(
+    root: Rc<RefCell<FiberRootNode>>,
+    unit_of_work: Rc<RefCell<FiberNode>>,
+    value: Value,
+    lane: Lane,
+) {
+    if let Some(boundary) = get_suspense_boundary(unit_of_work) {
+        boundary.borrow_mut().flags |= Flags::ShouldCapture;
+    }
+    attach_ping_listener(root, value, lane);
+}
+
+fn attach_ping_listener(root: Rc<RefCell<FiberRootNode>>, wakeable: Value, lane: Lane) {
+    if root.borrow().ping_cache.contains(&wakeable) {
+        return;
+    }
+    root.borrow_mut().ping_cache.push(wakeable.clone());
+
+    let root_cloned = root.clone();
+    let wakeable_cloned = wakeable.clone();
+    let ping = Value::function(move |_| {
+        ping_suspended_root(root_cloned.clone(), &wakeable_cloned, lane);
+        Ok(Value::undefined())
+    });
+
+    wakeable
+        .call_method("then", &[ping.clone(), ping])
+        .expect("attach_ping_listener, then panic");
+}
+
+fn ping_suspended_root(root: Rc<RefCell<FiberRootNode>>, wakeable: &Value, lane: Lane) {
+    {
+        let mut root = root.borrow_mut();
+        root.ping_cache.retain(|cached| cached != wakeable);
+        if root.suspended_lanes.contains(lane) {
+            // the render that threw this wakeable never committed, retry it
+            root.mark_root_pinged(lane);
+        } else {
+            // a boundary committed its fallback, render again to reveal the primary tree
+            root.mark_root_updated(lane);
+        }
+    }
+    unsafe {
+        WORK_LOOP
+            .as_ref()
+            .unwrap()
+            .clone()
+            .borrow()
+            .ensure_root_is_scheduled(root);
+    }
+}
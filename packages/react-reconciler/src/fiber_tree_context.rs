@@ -0,0 +1,143 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::fiber::FiberNode;
+
+// Ids used by useId are derived from the path of the fiber in the tree. Every level that
+// renders a list of children (a "fork") adds the index of the child to the id, so the same
+// tree produces the same ids no matter in which order it's rendered.
+//
+// The id is a sequence of bits with a leading 1 marking where it starts. Each fork adds
+// just enough bits to hold the number of children at that level. Once the id no longer fits
+// in 30 bits the oldest bits are moved into the overflow string as base 32 characters.
+
+// saved (id, overflow, provider) of the enclosing level, popped when the provider completes
+type TreeContextFrame = (u32, String, Option<Rc<RefCell<FiberNode>>>);
+
+static mut FORK_STACK: Vec<(u32, Option<Rc<RefCell<FiberNode>>>)> = vec![];
+static mut TREE_FORK_PROVIDER: Option<Rc<RefCell<FiberNode>>> = None;
+static mut TREE_FORK_COUNT: u32 = 0;
+
+static mut ID_STACK: Vec<TreeContextFrame> = vec![];
+static mut TREE_CONTEXT_PROVIDER: Option<Rc<RefCell<FiberNode>>> = None;
+static mut TREE_CONTEXT_ID: u32 = 1;
+static mut TREE_CONTEXT_OVERFLOW: String = String::new();
+
+fn is_provider(provider: &Option<Rc<RefCell<FiberNode>>>, fiber: &Rc<RefCell<FiberNode>>) -> bool {
+    match provider {
+        Some(provider) => Rc::ptr_eq(provider, fiber),
+        None => false,
+    }
+}
+
+fn get_bit_length(number: u32) -> u32 {
+    32 - number.leading_zeros()
+}
+
+fn get_leading_bit(id: u32) -> u32 {
+    1 << (get_bit_length(id) - 1)
+}
+
+pub fn to_base_32(mut number: u32) -> String {
+    let digits = b"0123456789abcdefghijklmnopqrstuv";
+    let mut result = vec![];
+    loop {
+        result.push(digits[(number % 32) as usize]);
+        number /= 32;
+        if number == 0 {
+            break;
+        }
+    }
+    result.reverse();
+    String::from_utf8(result).unwrap()
+}
+
+pub fn push_tree_fork(work_in_progress: Rc<RefCell<FiberNode>>, total_children: u32) {
+    unsafe {
+        FORK_STACK.push((TREE_FORK_COUNT, TREE_FORK_PROVIDER.take()));
+        TREE_FORK_PROVIDER = Some(work_in_progress);
+        TREE_FORK_COUNT = total_children;
+    }
+}
+
+pub fn push_tree_id(work_in_progress: Rc<RefCell<FiberNode>>, total_children: u32, index: u32) {
+    unsafe {
+        ID_STACK.push((
+            TREE_CONTEXT_ID,
+            TREE_CONTEXT_OVERFLOW.clone(),
+            TREE_CONTEXT_PROVIDER.take(),
+        ));
+        TREE_CONTEXT_PROVIDER = Some(work_in_progress);
+
+        let base_id_with_leading_bit = TREE_CONTEXT_ID;
+        let base_overflow = TREE_CONTEXT_OVERFLOW.clone();
+        let base_length = get_bit_length(base_id_with_leading_bit) - 1;
+        let base_id = base_id_with_leading_bit & !(1 << base_length);
+
+        // 0 is not a valid slot, it would be indistinguishable from a missing level
+        let slot = index + 1;
+        let length = get_bit_length(total_children) + base_length;
+
+        if length > 30 {
+            // keep the overflow in multiples of 5 bits so each one maps to a base 32 character
+            let number_of_overflow_bits = base_length - (base_length % 5);
+            let new_overflow_bits = (1 << number_of_overflow_bits) - 1;
+            let new_overflow = to_base_32(base_id & new_overflow_bits);
+
+            let rest_of_base_id = base_id >> number_of_overflow_bits;
+            let rest_of_base_length = base_length - number_of_overflow_bits;
+            let rest_of_length = get_bit_length(total_children) + rest_of_base_length;
+            let rest_of_new_bits = slot << rest_of_base_length;
+            let id = rest_of_new_bits | rest_of_base_id;
+
+            TREE_CONTEXT_ID = (1 << rest_of_length) | id;
+            TREE_CONTEXT_OVERFLOW = new_overflow + &base_overflow;
+        } else {
+            let new_bits = slot << base_length;
+            let id = new_bits | base_id;
+
+            TREE_CONTEXT_ID = (1 << length) | id;
+            TREE_CONTEXT_OVERFLOW = base_overflow;
+        }
+    }
+}
+
+// A component that called useId forks the tree once more, so the ids of its children
+// can't collide with its own.
+pub fn push_materialized_tree_id(work_in_progress: Rc<RefCell<FiberNode>>) {
+    if work_in_progress.borrow()._return.is_some() {
+        let number_of_forks = 1;
+        let slot_index = 0;
+        push_tree_fork(work_in_progress.clone(), number_of_forks);
+        push_tree_id(work_in_progress, number_of_forks, slot_index);
+    }
+}
+
+pub fn pop_tree_context(work_in_progress: &Rc<RefCell<FiberNode>>) {
+    unsafe {
+        // a fiber can be the provider of more than one level
+        while is_provider(&TREE_FORK_PROVIDER, work_in_progress) {
+            let (tree_fork_count, tree_fork_provider) = FORK_STACK.pop().unwrap();
+            TREE_FORK_COUNT = tree_fork_count;
+            TREE_FORK_PROVIDER = tree_fork_provider;
+        }
+        while is_provider(&TREE_CONTEXT_PROVIDER, work_in_progress) {
+            let (tree_context_id, tree_context_overflow, tree_context_provider) =
+                ID_STACK.pop().unwrap();
+            TREE_CONTEXT_ID = tree_context_id;
+            TREE_CONTEXT_OVERFLOW = tree_context_overflow;
+            TREE_CONTEXT_PROVIDER = tree_context_provider;
+        }
+    }
+}
+
+pub fn get_forks_at_level() -> u32 {
+    unsafe { TREE_FORK_COUNT }
+}
+
+pub fn get_tree_id() -> String {
+    unsafe {
+        let id_with_leading_bit = TREE_CONTEXT_ID;
+        let id = id_with_leading_bit & !get_leading_bit(id_with_leading_bit);
+        to_base_32(id) + &TREE_CONTEXT_OVERFLOW
+    }
+}
@@ -0,0 +1,55 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    fiber::FiberNode,
+    fiber_context::pop_provider,
+    fiber_flags::Flags,
+    fiber_host_context::{pop_host_container, pop_host_context},
+    fiber_tree_context::pop_tree_context,
+    work_tags::WorkTag,
+};
+
+pub fn unwind_work(work_in_progress: Rc<RefCell<FiberNode>>) -> Option<Rc<RefCell<FiberNode>>> {
+    let tag = work_in_progress.borrow().tag.clone();
+    pop_tree_context(&work_in_progress);
+    match tag {
+        WorkTag::SuspenseComponent => {
+            let flags = work_in_progress.borrow().flags.clone();
+            if flags.contains(Flags::ShouldCapture) && !flags.contains(Flags::DidCapture) {
+                work_in_progress.borrow_mut().flags =
+                    (flags - Flags::ShouldCapture) | Flags::DidCapture;
+                return Some(work_in_progress);
+            }
+            None
+        }
+        WorkTag::ContextProvider => {
+            let context = work_in_progress.borrow()._type.get("_context");
+            pop_provider(&context);
+            None
+        }
+        WorkTag::HostRoot | WorkTag::HostPortal => {
+            pop_host_container();
+            None
+        }
+        WorkTag::HostComponent => {
+            pop_host_context();
+            None
+        }
+        _ => None,
+    }
+}
+
+// Restores the stacks pushed by a fiber whose render was abandoned.
+pub fn unwind_interrupted_work(interrupted_work: Rc<RefCell<FiberNode>>) {
+    let tag = interrupted_work.borrow().tag.clone();
+    pop_tree_context(&interrupted_work);
+    match tag {
+        WorkTag::ContextProvider => {
+            let context = interrupted_work.borrow()._type.get("_context");
+            pop_provider(&context);
+        }
+        WorkTag::HostRoot | WorkTag::HostPortal => pop_host_container(),
+        WorkTag::HostComponent => pop_host_context(),
+        _ => {}
+    }
+}
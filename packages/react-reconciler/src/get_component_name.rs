@@ -0,0 +1,75 @@
+use shared::{
+    REACT_CONTEXT_TYPE, REACT_FORWARD_REF_TYPE, REACT_LAZY_TYPE, REACT_PROVIDER_TYPE,
+    REACT_RUST_COMPONENT_TYPE,
+};
+
+use crate::fiber::FiberNode;
+use crate::react_value::Value;
+use crate::work_tags::WorkTag;
+
+fn get_function_name(function: &Value) -> Option<String> {
+    let display_name = function.get("displayName");
+    if let Some(display_name) = display_name.as_string() {
+        return Some(display_name);
+    }
+    if !function.is_function() {
+        return None;
+    }
+    function
+        .get("name")
+        .as_string()
+        .filter(|name| !name.is_empty())
+}
+
+// the name of an element type as devtools and warnings show it, e.g. "App" or "ForwardRef(Input)"
+pub fn get_component_name_from_type(_type: &Value) -> Option<String> {
+    if let Some(host_type) = _type.as_host_type() {
+        return Some(host_type);
+    }
+    if _type.is_function() {
+        return get_function_name(_type);
+    }
+    let type_of = _type.type_of()?;
+    if let Some(display_name) = _type.get("displayName").as_string() {
+        return Some(display_name);
+    }
+    if type_of == REACT_FORWARD_REF_TYPE {
+        return Some(match get_function_name(&_type.get("render")) {
+            Some(name) => format!("ForwardRef({})", name),
+            None => "ForwardRef".to_string(),
+        });
+    }
+    if type_of == REACT_PROVIDER_TYPE {
+        let context = _type.get("_context");
+        return Some(match context.get("displayName").as_string() {
+            Some(name) => format!("{}.Provider", name),
+            None => "Context.Provider".to_string(),
+        });
+    }
+    if type_of == REACT_CONTEXT_TYPE {
+        return Some("Context.Consumer".to_string());
+    }
+    if type_of == REACT_LAZY_TYPE {
+        return Some("Lazy".to_string());
+    }
+    if type_of == REACT_RUST_COMPONENT_TYPE {
+        return Some("RustComponent".to_string());
+    }
+    None
+}
+
+pub fn get_component_name_from_fiber(fiber: &FiberNode) -> Option<String> {
+    match fiber.tag {
+        WorkTag::HostRoot | WorkTag::HostText | WorkTag::Fragment => None,
+        WorkTag::HostPortal => Some("Portal".to_string()),
+        WorkTag::Mode => Some("StrictMode".to_string()),
+        WorkTag::Profiler => Some("Profiler".to_string()),
+        WorkTag::SuspenseComponent => Some("Suspense".to_string()),
+        WorkTag::OffscreenComponent => Some("Offscreen".to_string()),
+        WorkTag::LazyComponent => Some("Lazy".to_string()),
+        WorkTag::FunctionComponent
+        | WorkTag::ForwardRef
+        | WorkTag::ContextProvider
+        | WorkTag::HostComponent => get_component_name_from_type(&fiber._type),
+    }
+}
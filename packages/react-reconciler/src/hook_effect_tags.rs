@@ -0,0 +1,13 @@
+use bitflags::bitflags;
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct HookEffectTags: u8 {
+        const NoFlags = 0b0000;
+        // the effect has to run in this commit
+        const HasEffect = 0b0001;
+        const Insertion = 0b0010;
+        const Layout = 0b0100;
+        const Passive = 0b1000;
+    }
+}
@@ -1,30 +1,125 @@
+// the work loop keeps its state in static muts, like the module variables of React's reconciler.
+// it only runs on the one wasm thread, so they are never touched concurrently
+#![allow(static_mut_refs)]
+
 use std::{any::Any, cell::RefCell, rc::Rc};
 
 use fiber::{FiberNode, FiberRootNode, StateNode};
 use fiber_hooks::WORK_LOOP;
+use fiber_lanes::Lane;
+use react_value::Value;
 use update_queue::{create_update, create_update_queue, enqueue_update};
-use wasm_bindgen::prelude::*;
 use work_loop::WorkLoop;
 use work_tags::WorkTag;
 
+pub use profiler_timer::{set_profiler_clock, ProfilerClock};
+// elements, props and state, shared with react whose hooks take and return them
+pub use shared::react_value;
+
 mod begin_work;
 mod child_fiber;
 mod commit_work;
 mod complete_work;
 pub mod fiber;
+mod fiber_context;
+pub mod fiber_dev_tools_hook;
+mod fiber_dev_warnings;
 mod fiber_flags;
 mod fiber_hooks;
+mod fiber_host_context;
+mod fiber_lanes;
+pub mod fiber_snapshot;
+mod fiber_throw;
+mod fiber_tree_context;
+mod fiber_unwind_work;
+mod get_component_name;
+mod hook_effect_tags;
+mod profiler_timer;
+mod thenable;
+mod type_of_mode;
 mod update_queue;
 mod work_loop;
 mod work_tags;
 
 pub trait HostConfig {
     fn create_text_instance(&self, content: String) -> Rc<dyn Any>;
-    fn create_instance(&self, _type: String) -> Rc<dyn Any>;
+    // host_context is the one of the parent, see get_child_host_context
+    fn create_instance(&self, _type: String, host_context: &str) -> Rc<dyn Any>;
+    // the host context of the children of a root or portal container
+    fn get_root_host_context(&self, container: Rc<dyn Any>) -> String;
+    fn get_child_host_context(&self, parent_host_context: &str, _type: &str) -> String;
+    // what refs of host components point to
+    fn get_public_instance(&self, instance: Rc<dyn Any>) -> Value;
+    // the container a portal renders into, from the containerInfo given to createPortal
+    fn get_portal_container(&self, container_info: &Value) -> Rc<dyn Any>;
+    // the container the portal sits in within the react tree, e.g. for events to bubble up to
+    fn prepare_portal_mount(&self, portal_container: Rc<dyn Any>, parent_container: Rc<dyn Any>);
     fn append_initial_child(&self, parent: Rc<dyn Any>, child: Rc<dyn Any>);
+    // props are applied once the children are in place, e.g. a select needs its options
+    fn finalize_initial_children(&self, instance: Rc<dyn Any>, _type: String, props: &Value);
+    fn commit_update(
+        &self,
+        instance: Rc<dyn Any>,
+        _type: String,
+        old_props: &Value,
+        new_props: &Value,
+    );
     fn append_child_to_container(&self, child: Rc<dyn Any>, parent: Rc<dyn Any>);
+    fn insert_child_to_container(
+        &self,
+        child: Rc<dyn Any>,
+        container: Rc<dyn Any>,
+        before: Rc<dyn Any>,
+    );
     fn remove_child(&self, child: Rc<dyn Any>, container: Rc<dyn Any>);
+    // the mutations of a commit are done, e.g. for a host that paints the whole tree at once
+    fn reset_after_commit(&self, container: Rc<dyn Any>);
+    // clears what the host put into the node, before it gets children again
+    fn reset_text_content(&self, instance: Rc<dyn Any>);
     fn commit_text_update(&self, text_instance: Rc<dyn Any>, content: String);
+    fn hide_instance(&self, instance: Rc<dyn Any>);
+    fn unhide_instance(&self, instance: Rc<dyn Any>);
+    fn hide_text_instance(&self, text_instance: Rc<dyn Any>);
+    fn unhide_text_instance(&self, text_instance: Rc<dyn Any>, content: String);
+    // hosts that never change a node once it is built return Some, then the tree is
+    // committed through PersistentHostConfig instead of the mutation methods above
+    fn persistent(&self) -> Option<&dyn PersistentHostConfig> {
+        None
+    }
+}
+
+// Persistent mode, for hosts whose nodes are immutable, e.g. native UI snapshot trees.
+// A changed node is cloned along with its ancestors, and every root or portal whose
+// children changed gets a complete new child set in the commit. The mutation methods of
+// HostConfig (append_child_to_container, remove_child, commit_update, commit_text_update,
+// reset_text_content and the hide ones) are not called in this mode.
+pub trait PersistentHostConfig {
+    // a copy of instance with new_props, with the children of instance when keep_children,
+    // without any otherwise, they are appended to the copy with append_initial_child
+    fn clone_instance(
+        &self,
+        instance: Rc<dyn Any>,
+        _type: String,
+        old_props: &Value,
+        new_props: &Value,
+        keep_children: bool,
+    ) -> Rc<dyn Any>;
+    // copies of the top level nodes of a hidden Offscreen subtree, e.g. of a Suspense fallback
+    fn clone_hidden_instance(
+        &self,
+        instance: Rc<dyn Any>,
+        _type: String,
+        props: &Value,
+    ) -> Rc<dyn Any>;
+    fn clone_hidden_text_instance(
+        &self,
+        text_instance: Rc<dyn Any>,
+        content: String,
+    ) -> Rc<dyn Any>;
+    fn create_container_child_set(&self, container: Rc<dyn Any>) -> Rc<dyn Any>;
+    fn append_child_to_container_child_set(&self, child_set: Rc<dyn Any>, child: Rc<dyn Any>);
+    // the commit of a child set, the container shows new_children from now on
+    fn replace_container_children(&self, container: Rc<dyn Any>, new_children: Rc<dyn Any>);
 }
 
 pub struct Reconciler {
@@ -39,8 +134,8 @@ impl Reconciler {
     pub fn create_container(&self, container: Rc<dyn Any>) -> Rc<RefCell<FiberRootNode>> {
         let host_root_fiber = Rc::new(RefCell::new(FiberNode::new(
             WorkTag::HostRoot,
-            JsValue::null(),
-            JsValue::null(),
+            Value::null(),
+            Value::null(),
         )));
         host_root_fiber.clone().borrow_mut().update_queue = Some(create_update_queue());
 
@@ -53,9 +148,9 @@ impl Reconciler {
         root.clone()
     }
 
-    pub fn update_container(&self, element: JsValue, root: Rc<RefCell<FiberRootNode>>) -> JsValue {
+    pub fn update_container(&self, element: Value, root: Rc<RefCell<FiberRootNode>>) -> Value {
         let host_root_fiber = Rc::clone(&root).borrow().current.clone();
-        let update = create_update(element.clone());
+        let update = create_update(element.clone(), Lane::SyncLane);
         enqueue_update(
             host_root_fiber.borrow().update_queue.clone().unwrap(),
             update,
@@ -68,7 +163,16 @@ impl Reconciler {
         work_loop
             .clone()
             .borrow()
-            .schedule_update_on_fiber(host_root_fiber);
+            .schedule_update_on_fiber(host_root_fiber, Lane::SyncLane);
         element.clone()
     }
 }
+
+pub fn flush_sync<T>(callback: impl FnOnce() -> T) -> T {
+    let work_loop = unsafe { WORK_LOOP.clone() };
+    match work_loop {
+        Some(work_loop) => work_loop.borrow().flush_sync(callback),
+        // nothing rendered yet, so there is nothing to flush
+        None => callback(),
+    }
+}
@@ -0,0 +1,69 @@
+use std::{cell::RefCell, rc::Rc};
+
+use scheduler::unstable_now;
+
+use crate::fiber::FiberNode;
+
+// where Profiler timings come from, replaceable so tests get deterministic durations
+pub trait ProfilerClock {
+    fn now(&self) -> f64;
+}
+
+static mut PROFILER_CLOCK: Option<Rc<dyn ProfilerClock>> = None;
+static mut PROFILER_START_TIME: f64 = -1.0;
+static mut COMMIT_TIME: f64 = 0.0;
+
+pub fn set_profiler_clock(clock: Rc<dyn ProfilerClock>) {
+    unsafe {
+        PROFILER_CLOCK = Some(clock);
+    }
+}
+
+fn now() -> f64 {
+    unsafe {
+        match PROFILER_CLOCK.clone() {
+            Some(clock) => clock.now(),
+            None => unstable_now(),
+        }
+    }
+}
+
+pub fn get_commit_time() -> f64 {
+    unsafe { COMMIT_TIME }
+}
+
+pub fn record_commit_time() {
+    unsafe {
+        COMMIT_TIME = now();
+    }
+}
+
+pub fn start_profiler_timer(fiber: Rc<RefCell<FiberNode>>) {
+    let time = now();
+    unsafe {
+        PROFILER_START_TIME = time;
+    }
+    if fiber.borrow().actual_start_time < 0.0 {
+        fiber.borrow_mut().actual_start_time = time;
+    }
+}
+
+pub fn stop_profiler_timer_if_running_and_record_delta(
+    fiber: Rc<RefCell<FiberNode>>,
+    override_base_time: bool,
+) {
+    unsafe {
+        if PROFILER_START_TIME < 0.0 {
+            return;
+        }
+        let elapsed_time = now() - PROFILER_START_TIME;
+        PROFILER_START_TIME = -1.0;
+
+        let mut fiber = fiber.borrow_mut();
+        fiber.actual_duration += elapsed_time;
+        // only the time spent in begin_work counts as the cost of rendering the fiber itself
+        if override_base_time {
+            fiber.self_base_duration = elapsed_time;
+        }
+    }
+}
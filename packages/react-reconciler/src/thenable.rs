@@ -0,0 +1,76 @@
+use crate::react_value::Value;
+
+fn set_status(thenable: &Value, status: &str) {
+    thenable
+        .set("status", Value::from_string(status))
+        .expect("status panic");
+}
+
+fn noop() -> Value {
+    Value::function(|_| Ok(Value::undefined()))
+}
+
+fn listen(thenable: &Value, on_fulfilled: Value, on_rejected: Value) {
+    thenable
+        .call_method("then", &[on_fulfilled, on_rejected])
+        .expect("thenable then panic");
+}
+
+fn get_status(thenable: &Value) -> Option<String> {
+    thenable.get("status").as_string()
+}
+
+// Records the outcome of the thenable on the thenable itself (status/value/reason),
+// so the component can read it synchronously once it renders again.
+pub fn track_used_thenable(
+    thenable_state: &mut Vec<Value>,
+    thenable: &Value,
+    index: usize,
+) -> Result<Value, Value> {
+    let mut thenable = thenable.clone();
+    match thenable_state.get(index) {
+        None => thenable_state.push(thenable.clone()),
+        Some(previous) => {
+            if previous != &thenable {
+                // the component created a new thenable during the re-render,
+                // keep using the one it suspended on and drop the new one
+                listen(&thenable, noop(), noop());
+                thenable = previous.clone();
+            }
+        }
+    }
+
+    match get_status(&thenable).as_deref() {
+        Some("fulfilled") => return Ok(thenable.get("value")),
+        Some("rejected") => return Err(thenable.get("reason")),
+        Some("pending") => {}
+        _ => {
+            set_status(&thenable, "pending");
+
+            let fulfilled_thenable = thenable.clone();
+            let on_fulfilled = Value::function(move |args| {
+                if get_status(&fulfilled_thenable).as_deref() == Some("pending") {
+                    set_status(&fulfilled_thenable, "fulfilled");
+                    let value = args.first().cloned().unwrap_or(Value::undefined());
+                    fulfilled_thenable.set("value", value).expect("value panic");
+                }
+                Ok(Value::undefined())
+            });
+            let rejected_thenable = thenable.clone();
+            let on_rejected = Value::function(move |args| {
+                if get_status(&rejected_thenable).as_deref() == Some("pending") {
+                    set_status(&rejected_thenable, "rejected");
+                    let reason = args.first().cloned().unwrap_or(Value::undefined());
+                    rejected_thenable
+                        .set("reason", reason)
+                        .expect("reason panic");
+                }
+                Ok(Value::undefined())
+            });
+            listen(&thenable, on_fulfilled, on_rejected);
+        }
+    }
+
+    // throw the thenable itself, the work loop unwinds to the nearest suspense boundary
+    Err(thenable)
+}
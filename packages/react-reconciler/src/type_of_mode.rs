@@ -0,0 +1,13 @@
+use bitflags::bitflags;
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct TypeOfMode: u8 {
+        const NoMode = 0b00000000;
+        // set for everything below a Profiler element
+        const ProfileMode = 0b00000010;
+        // set for everything below a StrictMode element
+        const StrictLegacyMode = 0b00001000;
+        const StrictEffectsMode = 0b00010000;
+    }
+}
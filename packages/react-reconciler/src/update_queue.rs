@@ -1,81 +1,199 @@
+use std::fmt::{Debug, Formatter};
 use std::{cell::RefCell, rc::Rc};
 
-use shared::log;
-use wasm_bindgen::*;
-use web_sys::js_sys::Function;
+use shared::error;
 
-use crate::fiber::{FiberNode, MemoizedState};
+use crate::fiber::MemoizedState;
+use crate::fiber_hooks::{Effect, StoreConsistencyCheck};
+use crate::fiber_lanes::{is_subset_of_lanes, Lane};
+use crate::react_value::{StateValue, Value};
 
-#[derive(Clone, Debug)]
-pub struct UpdateAction;
-
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct Update {
-    pub action: Option<JsValue>,
+    pub action: Option<StateValue>,
+    pub lane: Lane,
+    pub next: Option<Rc<RefCell<Update>>>,
+}
+
+impl Debug for Update {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // `next` is circular, so it is left out
+        f.debug_struct("Update")
+            .field("action", &self.action)
+            .field("lane", &self.lane)
+            .finish()
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct UpdateType {
-    pub pending: Option<Update>,
+    pub pending: Option<Rc<RefCell<Update>>>,
 }
 
 #[derive(Clone, Debug)]
 pub struct UpdateQueue {
     pub shared: UpdateType,
-    pub dispatch: Option<Function>,
+    // HostRoot, the updates a render took from pending, kept until a render commits so a render
+    // that is thrown away, e.g. one that suspended outside of any boundary, does not lose them
+    pub base_queue: Option<Rc<RefCell<Update>>>,
+    pub dispatch: Option<Value>,
+    // only used by function components
+    pub last_effect: Option<Rc<RefCell<Effect>>>,
+    pub stores: Vec<StoreConsistencyCheck>,
+}
+
+pub struct ReturnOfProcessUpdateQueue {
+    pub memoized_state: Option<MemoizedState>,
+    pub base_state: Option<MemoizedState>,
+    pub base_queue: Option<Rc<RefCell<Update>>>,
 }
 
-pub fn create_update(action: JsValue) -> Update {
+pub fn create_update(action: StateValue, lane: Lane) -> Update {
     Update {
         action: Some(action),
+        lane,
+        next: None,
     }
 }
 
 pub fn enqueue_update(update_queue: Rc<RefCell<UpdateQueue>>, update: Update) {
-    update_queue.borrow_mut().shared.pending = Option::from(update)
+    let update = Rc::new(RefCell::new(update));
+    let pending = update_queue.borrow().shared.pending.clone();
+    match pending {
+        None => {
+            update.borrow_mut().next = Some(update.clone());
+        }
+        Some(pending) => {
+            update.borrow_mut().next = pending.borrow().next.clone();
+            pending.borrow_mut().next = Some(update.clone());
+        }
+    }
+    // pending always points to the last update, pending.next is the first one
+    update_queue.borrow_mut().shared.pending = Some(update);
+}
+
+// one circular list of the updates of first and then those of second, both point to their last
+pub fn merge_update_lists(
+    first: Option<Rc<RefCell<Update>>>,
+    second: Option<Rc<RefCell<Update>>>,
+) -> Option<Rc<RefCell<Update>>> {
+    match (first, second) {
+        (Some(first), Some(second)) => {
+            let first_head = first.borrow().next.clone();
+            let second_head = second.borrow().next.clone();
+            first.borrow_mut().next = second_head;
+            second.borrow_mut().next = first_head;
+            Some(second)
+        }
+        (first, second) => second.or(first),
+    }
 }
 
 pub fn create_update_queue() -> Rc<RefCell<UpdateQueue>> {
     Rc::new(RefCell::new(UpdateQueue {
         shared: UpdateType { pending: None },
+        base_queue: None,
         dispatch: None,
+        last_effect: None,
+        stores: vec![],
     }))
 }
 
+// an updater function that throws fails the render, like an error in the component would
+fn basic_state_reducer(
+    state: Option<MemoizedState>,
+    action: &StateValue,
+) -> Result<Option<MemoizedState>, Value> {
+    let state = match state {
+        Some(MemoizedState::Value(state)) => state,
+        // a first update has nothing to pass to a function action
+        None => Value::undefined(),
+        Some(_) => {
+            error!(Render, "process_update_queue, base_state is not a Value");
+            return Ok(None);
+        }
+    };
+    // setState(action), an action that is a function gets the previous state
+    let new_state = match action.is_function() {
+        true => action.call(&[state])?,
+        false => action.clone(),
+    };
+    Ok(Some(MemoizedState::Value(new_state)))
+}
+
 pub fn process_update_queue(
-    mut base_state: Option<MemoizedState>,
-    update_queue: Option<Rc<RefCell<UpdateQueue>>>,
-    fiber: Rc<RefCell<FiberNode>>,
-) -> Option<MemoizedState> {
-    if update_queue.is_some() {
-        let update_queue = update_queue.clone().unwrap().clone();
-        let pending = update_queue.borrow().shared.pending.clone();
-        update_queue.borrow_mut().shared.pending = None;
-        if pending.is_some() {
-            let action = pending.unwrap().action;
-            match action {
-                None => {}
-                Some(action) => {
-                    let f = action.dyn_ref::<Function>();
-                    base_state = match f {
-                        None => Some(MemoizedState::JsValue(action.clone())),
-                        Some(f) => {
-                            if let MemoizedState::JsValue(base_state) = base_state.as_ref().unwrap()
-                            {
-                                Some(MemoizedState::JsValue(
-                                    f.call1(&JsValue::null(), base_state).unwrap(),
-                                ))
-                            } else {
-                                log!("process_update_queue, base_state is not JsValue");
-                                None
-                            }
-                        }
-                    };
+    base_state: Option<MemoizedState>,
+    pending_update: Option<Rc<RefCell<Update>>>,
+    render_lane: Lane,
+) -> Result<ReturnOfProcessUpdateQueue, Value> {
+    let mut result = ReturnOfProcessUpdateQueue {
+        memoized_state: base_state.clone(),
+        base_state: base_state.clone(),
+        base_queue: None,
+    };
+
+    if let Some(pending_update) = pending_update {
+        let first = pending_update.borrow().next.clone().unwrap();
+        let mut pending = first.clone();
+
+        let mut new_state = base_state.clone();
+        let mut new_base_state = base_state.clone();
+        let mut new_base_queue_first: Option<Rc<RefCell<Update>>> = None;
+        let mut new_base_queue_last: Option<Rc<RefCell<Update>>> = None;
+
+        loop {
+            let (action, update_lane) = {
+                let update = pending.borrow();
+                (update.action.clone(), update.lane)
+            };
+
+            if !is_subset_of_lanes(render_lane, update_lane) {
+                // not enough priority, keep the update for a later render
+                let clone = Rc::new(RefCell::new(create_update(
+                    action.clone().unwrap_or(Value::undefined()),
+                    update_lane,
+                )));
+                match new_base_queue_last.clone() {
+                    None => {
+                        new_base_queue_first = Some(clone.clone());
+                        new_base_state = new_state.clone();
+                    }
+                    Some(last) => {
+                        last.borrow_mut().next = Some(clone.clone());
+                    }
+                }
+                new_base_queue_last = Some(clone);
+            } else {
+                if let Some(last) = new_base_queue_last.clone() {
+                    // once an update is skipped every later one has to be replayed as well
+                    let clone = Rc::new(RefCell::new(create_update(
+                        action.clone().unwrap_or(Value::undefined()),
+                        Lane::NoLane,
+                    )));
+                    last.borrow_mut().next = Some(clone.clone());
+                    new_base_queue_last = Some(clone);
                 }
+                if let Some(action) = action.as_ref() {
+                    new_state = basic_state_reducer(new_state, action)?;
+                }
+            }
+
+            let next = pending.borrow().next.clone().unwrap();
+            if Rc::ptr_eq(&next, &first) {
+                break;
             }
+            pending = next;
         }
-    } else {
-        log!("{:?} process_update_queue, update_queue is empty", fiber)
+
+        match new_base_queue_last.clone() {
+            None => new_base_state = new_state.clone(),
+            Some(last) => last.borrow_mut().next = new_base_queue_first,
+        }
+
+        result.memoized_state = new_state;
+        result.base_state = new_base_state;
+        result.base_queue = new_base_queue_last;
     }
-    base_state
+
+    Ok(result)
 }
@@ -1,19 +1,60 @@
 use std::{cell::RefCell, rc::Rc};
 
-use shared::log;
-use wasm_bindgen::JsValue;
+use scheduler::{
+    unstable_cancel_callback, unstable_schedule_callback, unstable_should_yield_to_host, Priority,
+};
+use shared::{debug, error, trace, __DEV__};
 
 use crate::{
     begin_work::begin_work,
-    commit_work::CommitWork,
+    commit_work::{
+        commit_hook_effect_list_destroy, commit_hook_effect_list_mount,
+        commit_hook_effect_list_unmount, CommitWork,
+    },
     complete_work::CompleteWork,
-    fiber::{FiberNode, FiberRootNode, StateNode},
-    fiber_flags::get_mutation_mask,
+    fiber::{FiberNode, FiberRootNode, PendingPassiveEffects, StateNode},
+    fiber_dev_tools_hook::on_commit_root,
+    fiber_flags::{get_layout_mask, get_mutation_mask, get_passive_mask, Flags},
+    fiber_hooks::WORK_LOOP,
+    fiber_lanes::{
+        get_current_update_lane, lanes_to_scheduler_priority, set_current_update_lane, Lane,
+    },
+    fiber_throw::{is_thenable, throw_exception},
+    fiber_unwind_work::{unwind_interrupted_work, unwind_work},
+    hook_effect_tags::HookEffectTags,
+    profiler_timer::{
+        record_commit_time, start_profiler_timer, stop_profiler_timer_if_running_and_record_delta,
+    },
+    react_value::Value,
+    type_of_mode::TypeOfMode,
     work_tags::WorkTag,
     HostConfig,
 };
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RootExitStatus {
+    InProgress,
+    Completed,
+    DidNotComplete,
+    // an error was thrown and there are no error boundaries to catch it
+    Errored,
+}
+
 static mut WORK_IN_PROGRESS: Option<Rc<RefCell<FiberNode>>> = None;
+static mut WORK_IN_PROGRESS_ROOT: Option<Rc<RefCell<FiberRootNode>>> = None;
+static mut WORK_IN_PROGRESS_ROOT_RENDER_LANE: Lane = Lane::NoLane;
+static mut WORK_IN_PROGRESS_ROOT_EXIT_STATUS: RootExitStatus = RootExitStatus::InProgress;
+static mut WORK_IN_PROGRESS_DEFERRED_LANE: Lane = Lane::NoLane;
+static mut ROOT_DOES_HAVE_PASSIVE_EFFECTS: bool = false;
+// roots with pending lanes, flushSync looks here for sync work that is left
+static mut SCHEDULED_ROOTS: Vec<Rc<RefCell<FiberRootNode>>> = vec![];
+
+pub(crate) fn request_deferred_lane() -> Lane {
+    unsafe {
+        WORK_IN_PROGRESS_DEFERRED_LANE = Lane::DeferredLane;
+        WORK_IN_PROGRESS_DEFERRED_LANE
+    }
+}
 
 pub struct WorkLoop {
     complete_work: CompleteWork,
@@ -26,11 +67,12 @@ impl WorkLoop {
         }
     }
 
-    pub fn schedule_update_on_fiber(&self, fiber: Rc<RefCell<FiberNode>>) {
+    pub fn schedule_update_on_fiber(&self, fiber: Rc<RefCell<FiberNode>>, lane: Lane) {
         let root = self.mark_update_lane_from_fiber_to_root(fiber);
         if root.is_none() {
             return;
         }
+        root.clone().unwrap().borrow_mut().mark_root_updated(lane);
         self.ensure_root_is_scheduled(root.unwrap());
     }
 
@@ -61,7 +103,7 @@ impl WorkLoop {
         if fiber_node.tag == WorkTag::HostRoot {
             if let Some(state_node) = fiber_node.state_node.clone() {
                 if let StateNode::FiberRootNode(fiber_root_node) = &*(state_node.clone()) {
-                    return Some(Rc::clone(&fiber_root_node));
+                    return Some(Rc::clone(fiber_root_node));
                 }
             }
         }
@@ -69,26 +111,201 @@ impl WorkLoop {
         None
     }
 
-    fn ensure_root_is_scheduled(&self, root: Rc<RefCell<FiberRootNode>>) {
-        self.perform_sync_work_on_root(root);
+    pub(crate) fn ensure_root_is_scheduled(&self, root: Rc<RefCell<FiberRootNode>>) {
+        let update_lane = root.borrow().get_next_lane();
+        unsafe {
+            SCHEDULED_ROOTS.retain(|scheduled_root| !Rc::ptr_eq(scheduled_root, &root));
+            if update_lane != Lane::NoLane {
+                SCHEDULED_ROOTS.push(root.clone());
+            }
+        }
+        let existing_callback_node = root.borrow().callback_node;
+        if update_lane == Lane::NoLane {
+            if let Some(existing_callback_node) = existing_callback_node {
+                unstable_cancel_callback(existing_callback_node);
+            }
+            root.borrow_mut().callback_node = None;
+            root.borrow_mut().callback_priority = Lane::NoLane;
+            return;
+        }
+
+        let cur_priority = update_lane;
+        let prev_priority = root.borrow().callback_priority;
+        if cur_priority == prev_priority {
+            // the task already scheduled for this lane picks up the new update
+            return;
+        }
+
+        if let Some(existing_callback_node) = existing_callback_node {
+            unstable_cancel_callback(existing_callback_node);
+        }
+        root.borrow_mut().callback_node = None;
+        root.borrow_mut().callback_priority = Lane::NoLane;
+
+        if update_lane == Lane::SyncLane {
+            // flushed right away, a transition render in progress is thrown away by prepare_fresh_stack
+            self.perform_sync_work_on_root(root, update_lane);
+        } else {
+            let root_cloned = root.clone();
+            let callback_node = unstable_schedule_callback(
+                lanes_to_scheduler_priority(update_lane),
+                Box::new(move |did_timeout| unsafe {
+                    WORK_LOOP
+                        .as_ref()
+                        .unwrap()
+                        .clone()
+                        .borrow()
+                        .perform_concurrent_work_on_root(root_cloned.clone(), did_timeout)
+                }),
+            );
+            root.borrow_mut().callback_node = Some(callback_node);
+            root.borrow_mut().callback_priority = cur_priority;
+        }
+    }
+
+    pub fn flush_sync<T>(&self, callback: impl FnOnce() -> T) -> T {
+        // updates inside the callback get the sync lane, even within a transition
+        let previous_update_lane = get_current_update_lane();
+        set_current_update_lane(Lane::SyncLane);
+        let result = callback();
+        set_current_update_lane(previous_update_lane);
+
+        self.flush_sync_work_on_all_roots();
+        result
+    }
+
+    fn flush_sync_work_on_all_roots(&self) {
+        let roots = unsafe { SCHEDULED_ROOTS.clone() };
+        for root in roots {
+            let next_lane = root.borrow().get_next_lane();
+            if next_lane == Lane::SyncLane {
+                self.perform_sync_work_on_root(root, next_lane);
+            }
+        }
+    }
+
+    fn perform_sync_work_on_root(&self, root: Rc<RefCell<FiberRootNode>>, lane: Lane) {
+        let pending_passive_effects = root.borrow().pending_passive_effects.clone();
+        if self.flush_passive_effects(pending_passive_effects)
+            && root.borrow().get_next_lane() != lane
+        {
+            // the effects rendered this lane already or scheduled something more urgent
+            self.ensure_root_is_scheduled(root);
+            return;
+        }
+
+        let exit_status = self.render_root(root.clone(), lane, false);
+        match exit_status {
+            // suspended outside of any boundary, keep the current tree and wait for a ping
+            RootExitStatus::DidNotComplete => root.borrow_mut().mark_root_suspended(lane),
+            // the current tree stays on screen, rendering the lane again would throw again
+            RootExitStatus::Errored => root.borrow_mut().mark_root_finished(lane, Lane::NoLane),
+            _ => self.finish_render(root.clone(), lane),
+        }
+        self.ensure_root_is_scheduled(root);
+    }
+
+    fn perform_concurrent_work_on_root(
+        &self,
+        root: Rc<RefCell<FiberRootNode>>,
+        did_timeout: bool,
+    ) -> bool {
+        // effects of the previous commit have to run before the next render
+        let cur_callback_node = root.borrow().callback_node;
+        let pending_passive_effects = root.borrow().pending_passive_effects.clone();
+        if self.flush_passive_effects(pending_passive_effects)
+            && root.borrow().callback_node != cur_callback_node
+        {
+            return false;
+        }
+
+        let lane = root.borrow().get_next_lane();
+        if lane == Lane::NoLane {
+            return false;
+        }
+
+        let should_time_slice = !did_timeout && lane != Lane::SyncLane;
+        let mut exit_status = self.render_root(root.clone(), lane, should_time_slice);
+        if exit_status == RootExitStatus::Completed {
+            let finished_work = root.borrow().current.borrow().alternate.clone().unwrap();
+            if !is_render_consistent_with_external_stores(finished_work) {
+                // a store changed while this render yielded, render again without yielding
+                exit_status = self.render_root(root.clone(), lane, false);
+            }
+        }
+
+        match exit_status {
+            RootExitStatus::InProgress => {
+                // yielded to the host, keep going in this task unless a more urgent lane took over
+                self.ensure_root_is_scheduled(root.clone());
+                return root.borrow().callback_node == cur_callback_node;
+            }
+            RootExitStatus::DidNotComplete => {
+                root.borrow_mut().mark_root_suspended(lane);
+            }
+            RootExitStatus::Errored => {
+                root.borrow_mut().mark_root_finished(lane, Lane::NoLane);
+            }
+            RootExitStatus::Completed => {
+                self.finish_render(root.clone(), lane);
+            }
+        }
+
+        root.borrow_mut().callback_node = None;
+        root.borrow_mut().callback_priority = Lane::NoLane;
+        self.ensure_root_is_scheduled(root);
+        false
     }
 
-    fn perform_sync_work_on_root(&self, root: Rc<RefCell<FiberRootNode>>) {
-        self.prepare_fresh_stack(Rc::clone(&root));
+    fn render_root(
+        &self,
+        root: Rc<RefCell<FiberRootNode>>,
+        lane: Lane,
+        should_time_slice: bool,
+    ) -> RootExitStatus {
+        let is_same_render = unsafe {
+            WORK_IN_PROGRESS_ROOT_RENDER_LANE == lane
+                && WORK_IN_PROGRESS_ROOT
+                    .as_ref()
+                    .is_some_and(|wip_root| Rc::ptr_eq(wip_root, &root))
+        };
+        if !is_same_render {
+            self.prepare_fresh_stack(Rc::clone(&root), lane);
+        }
 
         loop {
-            match self.work_loop() {
+            let result = if should_time_slice {
+                self.work_loop_concurrent()
+            } else {
+                self.work_loop_sync()
+            };
+            match result {
                 Ok(_) => {
                     break;
                 }
-                Err(e) => unsafe {
-                    log!("work_loop error {:?}", e);
-                    WORK_IN_PROGRESS = None;
-                },
+                Err(e) => self.handle_throw(root.clone(), e),
             };
         }
 
-        log!("{:?}", *root.clone().borrow());
+        unsafe {
+            if should_time_slice && WORK_IN_PROGRESS.is_some() {
+                return RootExitStatus::InProgress;
+            }
+
+            WORK_IN_PROGRESS_ROOT = None;
+            WORK_IN_PROGRESS_ROOT_RENDER_LANE = Lane::NoLane;
+            if WORK_IN_PROGRESS_ROOT_EXIT_STATUS == RootExitStatus::DidNotComplete
+                || WORK_IN_PROGRESS_ROOT_EXIT_STATUS == RootExitStatus::Errored
+            {
+                return WORK_IN_PROGRESS_ROOT_EXIT_STATUS.clone();
+            }
+            WORK_IN_PROGRESS_ROOT_EXIT_STATUS = RootExitStatus::Completed;
+            RootExitStatus::Completed
+        }
+    }
+
+    fn finish_render(&self, root: Rc<RefCell<FiberRootNode>>, lane: Lane) {
+        trace!(Render, "{:?}", *root.clone().borrow());
 
         let finished_work = root
             .clone()
@@ -99,8 +316,64 @@ impl WorkLoop {
             .alternate
             .clone();
 
+        let spawned_lane = unsafe { WORK_IN_PROGRESS_DEFERRED_LANE };
         root.clone().borrow_mut().finished_work = finished_work;
-        self.commit_root(root);
+        self.commit_root(root.clone());
+        root.borrow_mut().mark_root_finished(lane, spawned_lane);
+    }
+
+    fn handle_throw(&self, root: Rc<RefCell<FiberRootNode>>, thrown_value: Value) {
+        let unit_of_work = unsafe { WORK_IN_PROGRESS.clone() };
+        if unit_of_work.is_none() || !is_thenable(&thrown_value) {
+            // there are no error boundaries, the error unwinds to the root and the whole
+            // render is thrown away instead of committing a partial tree
+            error!(
+                Render,
+                "Uncaught error, the update was not committed: {:?}", thrown_value
+            );
+            let mut interrupted_work = unit_of_work;
+            while let Some(fiber) = interrupted_work {
+                unwind_interrupted_work(fiber.clone());
+                interrupted_work = fiber.borrow()._return.clone();
+            }
+            unsafe {
+                WORK_IN_PROGRESS_ROOT_EXIT_STATUS = RootExitStatus::Errored;
+                WORK_IN_PROGRESS = None;
+            }
+            return;
+        }
+
+        let unit_of_work = unit_of_work.unwrap();
+        throw_exception(root, unit_of_work.clone(), thrown_value, unsafe {
+            WORK_IN_PROGRESS_ROOT_RENDER_LANE
+        });
+        self.unwind_unit_of_work(unit_of_work);
+    }
+
+    fn unwind_unit_of_work(&self, unit_of_work: Rc<RefCell<FiberNode>>) {
+        let mut incomplete_work = Some(unit_of_work);
+        while let Some(fiber) = incomplete_work {
+            if let Some(next) = unwind_work(fiber.clone()) {
+                // render the boundary again, this time with its fallback
+                unsafe {
+                    WORK_IN_PROGRESS = Some(next);
+                }
+                return;
+            }
+
+            let return_fiber = fiber.borrow()._return.clone();
+            if let Some(return_fiber) = return_fiber.clone() {
+                let mut return_fiber = return_fiber.borrow_mut();
+                return_fiber.deletions = None;
+                return_fiber.subtree_flags = Flags::NoFlags;
+            }
+            incomplete_work = return_fiber;
+        }
+
+        unsafe {
+            WORK_IN_PROGRESS_ROOT_EXIT_STATUS = RootExitStatus::DidNotComplete;
+            WORK_IN_PROGRESS = None;
+        }
     }
 
     fn commit_root(&self, root: Rc<RefCell<FiberRootNode>>) {
@@ -111,29 +384,130 @@ impl WorkLoop {
 
         let finished_work = cloned.borrow().finished_work.clone().unwrap();
         cloned.borrow_mut().finished_work = None;
+        record_commit_time();
+        // the HostRoot rendered every update it took, the skipped ones are back in pending
+        if let Some(update_queue) = finished_work.borrow().update_queue.as_ref() {
+            update_queue.borrow_mut().base_queue = None;
+        }
 
+        let effect_mask = get_mutation_mask() | get_layout_mask() | get_passive_mask();
         let subtree_has_effect =
-            get_mutation_mask().contains(finished_work.clone().borrow().subtree_flags.clone());
-        let root_has_effect =
-            get_mutation_mask().contains(finished_work.clone().borrow().flags.clone());
+            effect_mask.intersects(finished_work.clone().borrow().subtree_flags.clone());
+        let root_has_effect = effect_mask.intersects(finished_work.clone().borrow().flags.clone());
+
+        let finished_flags =
+            finished_work.borrow().flags.clone() | finished_work.borrow().subtree_flags.clone();
+        if get_passive_mask().intersects(finished_flags)
+            && !unsafe { ROOT_DOES_HAVE_PASSIVE_EFFECTS }
+        {
+            unsafe {
+                ROOT_DOES_HAVE_PASSIVE_EFFECTS = true;
+            }
+            let root_cloned = root.clone();
+            unstable_schedule_callback(
+                Priority::NormalPriority,
+                Box::new(move |_| unsafe {
+                    ROOT_DOES_HAVE_PASSIVE_EFFECTS = false;
+                    let pending_passive_effects =
+                        root_cloned.borrow().pending_passive_effects.clone();
+                    WORK_LOOP
+                        .as_ref()
+                        .unwrap()
+                        .clone()
+                        .borrow()
+                        .flush_passive_effects(pending_passive_effects);
+                    false
+                }),
+            );
+        }
 
         let commit_work = &mut CommitWork::new(self.complete_work.host_config.clone());
         if subtree_has_effect || root_has_effect {
-            commit_work.commit_mutation_effects(finished_work.clone());
+            commit_work.commit_mutation_effects(finished_work.clone(), root.clone());
+            let container = cloned.borrow().container.clone();
+            self.complete_work.host_config.reset_after_commit(container);
             cloned.borrow_mut().current = finished_work.clone();
+            // layout effects and refs see the tree that was just committed
+            commit_work.commit_layout_effects(finished_work.clone());
         } else {
             cloned.borrow_mut().current = finished_work.clone();
         }
+
+        if __DEV__ {
+            commit_work.commit_double_invoke_effects_in_dev(
+                finished_work.clone(),
+                root.clone(),
+                false,
+            );
+        }
+
+        on_commit_root(root);
+    }
+
+    fn flush_passive_effects(
+        &self,
+        pending_passive_effects: Rc<RefCell<PendingPassiveEffects>>,
+    ) -> bool {
+        // taken out first, effects can trigger renders that commit new ones
+        let unmount = std::mem::take(&mut pending_passive_effects.borrow_mut().unmount);
+        let update = std::mem::take(&mut pending_passive_effects.borrow_mut().update);
+        let remount = std::mem::take(&mut pending_passive_effects.borrow_mut().remount);
+        let did_flush_passive_effect = !unmount.is_empty() || !update.is_empty();
+
+        for effect in unmount {
+            commit_hook_effect_list_unmount(HookEffectTags::Passive, effect);
+        }
+        for effect in update.iter() {
+            commit_hook_effect_list_destroy(
+                HookEffectTags::Passive | HookEffectTags::HasEffect,
+                effect.clone(),
+            );
+        }
+        for effect in update {
+            commit_hook_effect_list_mount(
+                HookEffectTags::Passive | HookEffectTags::HasEffect,
+                effect,
+            );
+        }
+        // StrictMode, as if the components were unmounted and mounted again
+        for effect in remount.iter() {
+            commit_hook_effect_list_destroy(
+                HookEffectTags::Passive | HookEffectTags::HasEffect,
+                effect.clone(),
+            );
+        }
+        for effect in remount {
+            commit_hook_effect_list_mount(
+                HookEffectTags::Passive | HookEffectTags::HasEffect,
+                effect,
+            );
+        }
+
+        did_flush_passive_effect
     }
 
-    fn prepare_fresh_stack(&self, root: Rc<RefCell<FiberRootNode>>) {
+    fn prepare_fresh_stack(&self, root: Rc<RefCell<FiberRootNode>>, lane: Lane) {
         let root = Rc::clone(&root);
         unsafe {
+            // a render interrupted by a more urgent lane leaves its pushed stacks behind
+            if let Some(work_in_progress) = WORK_IN_PROGRESS.clone() {
+                let mut interrupted_work = work_in_progress.borrow()._return.clone();
+                while let Some(fiber) = interrupted_work {
+                    unwind_interrupted_work(fiber.clone());
+                    interrupted_work = fiber.borrow()._return.clone();
+                }
+            }
+
+            WORK_IN_PROGRESS_ROOT = Some(root.clone());
+            WORK_IN_PROGRESS_ROOT_RENDER_LANE = lane;
+            WORK_IN_PROGRESS_ROOT_EXIT_STATUS = RootExitStatus::InProgress;
+            WORK_IN_PROGRESS_DEFERRED_LANE = Lane::NoLane;
             WORK_IN_PROGRESS = Some(FiberNode::create_work_in_progress(
                 root.borrow().current.clone(),
-                JsValue::null(),
+                Value::null(),
             ));
-            log!(
+            debug!(
+                Render,
                 "prepare_fresh_stack {:?} {:?}",
                 WORK_IN_PROGRESS.clone().unwrap().clone().borrow()._type,
                 WORK_IN_PROGRESS
@@ -146,7 +520,7 @@ impl WorkLoop {
         }
     }
 
-    fn work_loop(&self) -> Result<(), JsValue> {
+    fn work_loop_sync(&self) -> Result<(), Value> {
         unsafe {
             while WORK_IN_PROGRESS.is_some() {
                 self.perform_unit_of_work(WORK_IN_PROGRESS.clone().unwrap())?;
@@ -155,15 +529,35 @@ impl WorkLoop {
         Ok(())
     }
 
-    fn perform_unit_of_work(&self, fiber: Rc<RefCell<FiberNode>>) -> Result<(), JsValue> {
-        let next = begin_work(fiber.clone())?;
+    fn work_loop_concurrent(&self) -> Result<(), Value> {
+        unsafe {
+            while WORK_IN_PROGRESS.is_some() && !unstable_should_yield_to_host() {
+                self.perform_unit_of_work(WORK_IN_PROGRESS.clone().unwrap())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn perform_unit_of_work(&self, fiber: Rc<RefCell<FiberNode>>) -> Result<(), Value> {
+        let is_profiling = fiber.borrow().mode.contains(TypeOfMode::ProfileMode);
+        if is_profiling {
+            start_profiler_timer(fiber.clone());
+        }
+        let next = begin_work(
+            fiber.clone(),
+            unsafe { WORK_IN_PROGRESS_ROOT_RENDER_LANE },
+            &self.complete_work.host_config,
+        );
+        if is_profiling {
+            stop_profiler_timer_if_running_and_record_delta(fiber.clone(), true);
+        }
+        let next = next?;
         let pending_props = { fiber.clone().borrow().pending_props.clone() };
         fiber.clone().borrow_mut().memoized_props = pending_props;
 
-        if next.is_none() {
-            self.complete_unit_of_work(fiber.clone());
-        } else {
-            unsafe { WORK_IN_PROGRESS = Some(next.unwrap()) };
+        match next {
+            None => self.complete_unit_of_work(fiber.clone()),
+            Some(next) => unsafe { WORK_IN_PROGRESS = Some(next) },
         }
         Ok(())
     }
@@ -171,9 +565,18 @@ impl WorkLoop {
     fn complete_unit_of_work(&self, fiber: Rc<RefCell<FiberNode>>) {
         let mut node: Option<Rc<RefCell<FiberNode>>> = Some(fiber);
         loop {
-            let next = self
-                .complete_work
-                .complete_work(node.clone().unwrap().clone());
+            let completed_work = node.clone().unwrap();
+            let is_profiling = completed_work
+                .borrow()
+                .mode
+                .contains(TypeOfMode::ProfileMode);
+            if is_profiling {
+                start_profiler_timer(completed_work.clone());
+            }
+            let next = self.complete_work.complete_work(completed_work.clone());
+            if is_profiling {
+                stop_profiler_timer_if_running_and_record_delta(completed_work, false);
+            }
 
             if next.is_some() {
                 unsafe {
@@ -185,7 +588,7 @@ impl WorkLoop {
             let sibling = node.clone().unwrap().clone().borrow().sibling.clone();
             if sibling.is_some() {
                 unsafe {
-                    WORK_IN_PROGRESS = next.clone();
+                    WORK_IN_PROGRESS = sibling.clone();
                 }
                 return;
             }
@@ -206,3 +609,44 @@ impl WorkLoop {
         }
     }
 }
+
+fn is_render_consistent_with_external_stores(finished_work: Rc<RefCell<FiberNode>>) -> bool {
+    let mut node = finished_work.clone();
+    loop {
+        if node.borrow().flags.contains(Flags::StoreConsistency) {
+            let update_queue = node.borrow().update_queue.clone();
+            if let Some(update_queue) = update_queue {
+                let stores = update_queue.borrow().stores.clone();
+                for check in stores {
+                    match check.get_snapshot.call(&[]) {
+                        Ok(snapshot) if snapshot.is_same(&check.value) => {}
+                        _ => return false,
+                    }
+                }
+            }
+        }
+
+        let child = node.borrow().child.clone();
+        if let Some(child) = child.filter(|_| {
+            node.borrow()
+                .subtree_flags
+                .contains(Flags::StoreConsistency)
+        }) {
+            node = child;
+            continue;
+        }
+
+        if Rc::ptr_eq(&node, &finished_work) {
+            return true;
+        }
+        while node.borrow().sibling.is_none() {
+            let _return = node.borrow()._return.clone();
+            match _return {
+                Some(_return) if !Rc::ptr_eq(&_return, &finished_work) => node = _return,
+                _ => return true,
+            }
+        }
+        let sibling = node.borrow().sibling.clone().unwrap();
+        node = sibling;
+    }
+}
@@ -2,6 +2,15 @@
 pub enum WorkTag {
     FunctionComponent = 0,
     HostRoot = 3,
+    HostPortal = 4,
     HostComponent = 5,
     HostText = 6,
+    Fragment = 7,
+    Mode = 8,
+    ContextProvider = 10,
+    ForwardRef = 11,
+    Profiler = 12,
+    SuspenseComponent = 13,
+    LazyComponent = 16,
+    OffscreenComponent = 22,
 }
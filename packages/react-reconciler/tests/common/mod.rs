@@ -0,0 +1,668 @@
+// Shared by the test binaries, each of them uses only part of it.
+#![allow(dead_code)]
+
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    rc::Rc,
+    sync::{Mutex, MutexGuard},
+};
+
+use react_reconciler::react_value::{ReactValue, Value};
+use react_reconciler::{fiber::FiberRootNode, HostConfig, PersistentHostConfig, Reconciler};
+use shared::logger::{set_log_sink, LogRecord};
+use shared::{REACT_ELEMENT_TYPE, REACT_PORTAL_TYPE};
+
+// the reconciler keeps its state in statics, so renders must not overlap
+static RENDER_LOCK: Mutex<()> = Mutex::new(());
+
+pub fn lock() -> MutexGuard<'static, ()> {
+    RENDER_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// what the packages log while it lives, the console gets the records again once it is dropped
+pub struct Logs(Rc<RefCell<Vec<String>>>);
+
+impl Logs {
+    pub fn capture() -> Self {
+        let messages = Rc::new(RefCell::new(vec![]));
+        let pushed = messages.clone();
+        set_log_sink(Some(Box::new(move |record: &LogRecord| {
+            pushed.borrow_mut().push(record.message.to_string());
+        })));
+        Logs(messages)
+    }
+
+    pub fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.0.borrow_mut())
+    }
+}
+
+impl Drop for Logs {
+    fn drop(&mut self) {
+        set_log_sink(None);
+    }
+}
+
+// An object as javascript would pass it, e.g. an element or its props, the properties keep
+// the order they were set in.
+#[derive(Debug, Default)]
+pub struct Object(RefCell<Vec<(String, Value)>>);
+
+impl ReactValue for Object {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_same(&self, other: &Value) -> bool {
+        other
+            .downcast_ref::<Object>()
+            .is_some_and(|other| std::ptr::eq(self, other))
+    }
+
+    fn keys(&self) -> Option<Vec<String>> {
+        Some(self.0.borrow().iter().map(|(key, _)| key.clone()).collect())
+    }
+
+    fn get(&self, name: &str) -> Value {
+        self.0
+            .borrow()
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+            .unwrap_or(Value::undefined())
+    }
+
+    fn set(&self, name: &str, value: Value) -> Result<(), Value> {
+        let mut properties = self.0.borrow_mut();
+        match properties.iter_mut().find(|(key, _)| key == name) {
+            Some((_, old_value)) => *old_value = value,
+            None => properties.push((name.to_string(), value)),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct List(Vec<Value>);
+
+impl ReactValue for List {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_same(&self, other: &Value) -> bool {
+        other
+            .downcast_ref::<List>()
+            .is_some_and(|other| std::ptr::eq(self, other))
+    }
+
+    fn as_list(&self) -> Option<Vec<Value>> {
+        Some(self.0.clone())
+    }
+}
+
+pub fn object(properties: Vec<(&str, Value)>) -> Value {
+    Value::new(Object(RefCell::new(
+        properties
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    )))
+}
+
+pub fn list(items: Vec<Value>) -> Value {
+    Value::new(List(items))
+}
+
+pub fn text(content: &str) -> Value {
+    Value::from_string(content)
+}
+
+pub fn element_with_ref(_type: Value, key: Option<&str>, _ref: Value, props: Value) -> Value {
+    object(vec![
+        ("$$typeof", text(REACT_ELEMENT_TYPE)),
+        ("type", _type),
+        ("key", key.map_or(Value::null(), text)),
+        ("ref", _ref),
+        ("props", props),
+    ])
+}
+
+pub fn element(_type: Value, key: Option<&str>, props: Vec<(&str, Value)>) -> Value {
+    element_with_ref(_type, key, Value::null(), object(props))
+}
+
+// <tag {...props}>{children}</tag>
+pub fn host(tag: &str, mut props: Vec<(&str, Value)>, children: Vec<Value>) -> Value {
+    props.push(("children", list(children)));
+    element(text(tag), None, props)
+}
+
+pub fn portal(children: Value, container: &Rc<RefCell<Node>>) -> Value {
+    object(vec![
+        ("$$typeof", text(REACT_PORTAL_TYPE)),
+        ("key", Value::null()),
+        ("children", children),
+        ("containerInfo", Value::new(HostNode(container.clone()))),
+    ])
+}
+
+// a function component, it gets its props
+pub fn component(render: impl Fn(&Value) -> Result<Value, Value> + 'static) -> Value {
+    Value::function(move |args| render(&args[0]))
+}
+
+// a thenable that a component can throw to suspend, resolved by the test
+pub struct Deferred {
+    pub thenable: Value,
+    listeners: Rc<RefCell<Vec<Value>>>,
+}
+
+impl Deferred {
+    pub fn new() -> Self {
+        let listeners: Rc<RefCell<Vec<Value>>> = Rc::new(RefCell::new(vec![]));
+        let then_listeners = listeners.clone();
+        let then = Value::function(move |args| {
+            then_listeners.borrow_mut().push(args[0].clone());
+            Ok(Value::undefined())
+        });
+        Deferred {
+            thenable: object(vec![("then", then)]),
+            listeners,
+        }
+    }
+
+    pub fn resolve(&self) {
+        let listeners = std::mem::take(&mut *self.listeners.borrow_mut());
+        for listener in listeners {
+            listener.call(&[Value::undefined()]).unwrap();
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Node {
+    pub tag: String,
+    pub props: Value,
+    pub text: Option<String>,
+    pub hidden: bool,
+    pub children: Vec<Rc<RefCell<Node>>>,
+}
+
+impl Node {
+    pub fn new(tag: &str) -> Rc<RefCell<Node>> {
+        Rc::new(RefCell::new(Node {
+            tag: tag.to_string(),
+            props: Value::null(),
+            text: None,
+            hidden: false,
+            children: vec![],
+        }))
+    }
+
+    fn from_instance(instance: &Rc<dyn Any>) -> Rc<RefCell<Node>> {
+        instance.clone().downcast::<RefCell<Node>>().unwrap()
+    }
+
+    // e.g. <div id="a">text</div>, hidden nodes are left out
+    pub fn to_html(&self) -> String {
+        if self.hidden {
+            return String::new();
+        }
+        if let Some(text) = &self.text {
+            return text.clone();
+        }
+        let mut attributes = String::new();
+        for key in self.props.keys().unwrap_or_default() {
+            let value = self.props.get(&key);
+            if let Some(value) = value.text_content() {
+                if key != "children" {
+                    attributes.push_str(&format!(" {}=\"{}\"", key, value));
+                }
+            }
+        }
+        format!(
+            "<{}{}>{}</{}>",
+            self.tag,
+            attributes,
+            self.children_html(),
+            self.tag
+        )
+    }
+
+    pub fn children_html(&self) -> String {
+        self.children
+            .iter()
+            .map(|child| child.borrow().to_html())
+            .collect()
+    }
+}
+
+// a node handed to react, e.g. what a ref points to or the container of a portal
+#[derive(Debug)]
+pub struct HostNode(pub Rc<RefCell<Node>>);
+
+impl ReactValue for HostNode {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_same(&self, other: &Value) -> bool {
+        other
+            .downcast_ref::<HostNode>()
+            .is_some_and(|other| Rc::ptr_eq(&self.0, &other.0))
+    }
+}
+
+fn remove(children: &mut Vec<Rc<RefCell<Node>>>, child: &Rc<RefCell<Node>>) {
+    children.retain(|node| !Rc::ptr_eq(node, child));
+}
+
+// a host that keeps its nodes in memory and changes them in place
+#[derive(Default)]
+pub struct MemoryHost {
+    pub commits: RefCell<u32>,
+}
+
+impl HostConfig for MemoryHost {
+    fn create_text_instance(&self, content: String) -> Rc<dyn Any> {
+        let node = Node::new("#text");
+        node.borrow_mut().text = Some(content);
+        node
+    }
+
+    fn create_instance(&self, _type: String, _host_context: &str) -> Rc<dyn Any> {
+        Node::new(&_type)
+    }
+
+    fn get_root_host_context(&self, _container: Rc<dyn Any>) -> String {
+        String::new()
+    }
+
+    fn get_child_host_context(&self, parent_host_context: &str, _type: &str) -> String {
+        parent_host_context.to_string()
+    }
+
+    fn get_public_instance(&self, instance: Rc<dyn Any>) -> Value {
+        Value::new(HostNode(Node::from_instance(&instance)))
+    }
+
+    fn get_portal_container(&self, container_info: &Value) -> Rc<dyn Any> {
+        container_info.downcast_ref::<HostNode>().unwrap().0.clone()
+    }
+
+    fn prepare_portal_mount(&self, _portal_container: Rc<dyn Any>, _parent_container: Rc<dyn Any>) {
+    }
+
+    fn append_initial_child(&self, parent: Rc<dyn Any>, child: Rc<dyn Any>) {
+        let parent = Node::from_instance(&parent);
+        let child = Node::from_instance(&child);
+        let mut parent = parent.borrow_mut();
+        remove(&mut parent.children, &child);
+        parent.children.push(child);
+    }
+
+    fn finalize_initial_children(&self, instance: Rc<dyn Any>, _type: String, props: &Value) {
+        Node::from_instance(&instance).borrow_mut().props = props.clone();
+    }
+
+    fn commit_update(
+        &self,
+        instance: Rc<dyn Any>,
+        _type: String,
+        _old_props: &Value,
+        new_props: &Value,
+    ) {
+        Node::from_instance(&instance).borrow_mut().props = new_props.clone();
+    }
+
+    fn append_child_to_container(&self, child: Rc<dyn Any>, parent: Rc<dyn Any>) {
+        self.append_initial_child(parent, child)
+    }
+
+    fn insert_child_to_container(
+        &self,
+        child: Rc<dyn Any>,
+        container: Rc<dyn Any>,
+        before: Rc<dyn Any>,
+    ) {
+        let container = Node::from_instance(&container);
+        let child = Node::from_instance(&child);
+        let before = Node::from_instance(&before);
+        let mut container = container.borrow_mut();
+        remove(&mut container.children, &child);
+        let index = container
+            .children
+            .iter()
+            .position(|node| Rc::ptr_eq(node, &before))
+            .unwrap();
+        container.children.insert(index, child);
+    }
+
+    fn remove_child(&self, child: Rc<dyn Any>, container: Rc<dyn Any>) {
+        let container = Node::from_instance(&container);
+        remove(
+            &mut container.borrow_mut().children,
+            &Node::from_instance(&child),
+        );
+    }
+
+    fn reset_after_commit(&self, _container: Rc<dyn Any>) {
+        *self.commits.borrow_mut() += 1;
+    }
+
+    fn reset_text_content(&self, instance: Rc<dyn Any>) {
+        Node::from_instance(&instance).borrow_mut().children.clear();
+    }
+
+    fn commit_text_update(&self, text_instance: Rc<dyn Any>, content: String) {
+        Node::from_instance(&text_instance).borrow_mut().text = Some(content);
+    }
+
+    fn hide_instance(&self, instance: Rc<dyn Any>) {
+        Node::from_instance(&instance).borrow_mut().hidden = true;
+    }
+
+    fn unhide_instance(&self, instance: Rc<dyn Any>) {
+        Node::from_instance(&instance).borrow_mut().hidden = false;
+    }
+
+    fn hide_text_instance(&self, text_instance: Rc<dyn Any>) {
+        self.hide_instance(text_instance);
+    }
+
+    fn unhide_text_instance(&self, text_instance: Rc<dyn Any>, content: String) {
+        self.commit_text_update(text_instance.clone(), content);
+        self.unhide_instance(text_instance);
+    }
+}
+
+pub struct TestRoot {
+    pub host: Rc<MemoryHost>,
+    pub container: Rc<RefCell<Node>>,
+    pub reconciler: Reconciler,
+    pub root: Rc<RefCell<FiberRootNode>>,
+}
+
+impl TestRoot {
+    pub fn new() -> Self {
+        let host = Rc::new(MemoryHost::default());
+        let container = Node::new("root");
+        let reconciler = Reconciler::new(host.clone());
+        let root = reconciler.create_container(container.clone());
+        TestRoot {
+            host,
+            container,
+            reconciler,
+            root,
+        }
+    }
+
+    pub fn render(&self, element: Value) {
+        self.reconciler.update_container(element, self.root.clone());
+    }
+
+    pub fn html(&self) -> String {
+        self.container.borrow().children_html()
+    }
+}
+
+// A node of PersistentHost, it can't change once it has been committed to a container.
+#[derive(Debug)]
+pub struct FrozenNode {
+    pub tag: String,
+    pub props: Value,
+    pub text: Option<String>,
+    pub hidden: bool,
+    children: RefCell<Vec<Rc<FrozenNode>>>,
+    committed: Cell<bool>,
+}
+
+impl FrozenNode {
+    fn new(tag: &str, props: Value, text: Option<String>, hidden: bool) -> Rc<FrozenNode> {
+        Rc::new(FrozenNode {
+            tag: tag.to_string(),
+            props,
+            text,
+            hidden,
+            children: RefCell::new(vec![]),
+            committed: Cell::new(false),
+        })
+    }
+
+    fn from_instance(instance: &Rc<dyn Any>) -> Rc<FrozenNode> {
+        instance.clone().downcast::<FrozenNode>().unwrap()
+    }
+
+    pub fn children(&self) -> Vec<Rc<FrozenNode>> {
+        self.children.borrow().clone()
+    }
+
+    fn commit(&self) {
+        self.committed.set(true);
+        for child in self.children.borrow().iter() {
+            child.commit();
+        }
+    }
+
+    pub fn to_html(&self) -> String {
+        if self.hidden {
+            return String::new();
+        }
+        if let Some(text) = &self.text {
+            return text.clone();
+        }
+        let children: String = self
+            .children
+            .borrow()
+            .iter()
+            .map(|child| child.to_html())
+            .collect();
+        format!("<{}>{}</{}>", self.tag, children, self.tag)
+    }
+}
+
+pub type ChildSet = RefCell<Vec<Rc<FrozenNode>>>;
+
+// a host whose nodes are immutable, every change reaches it as clones and new child sets
+#[derive(Default)]
+pub struct PersistentHost {
+    pub container: Rc<ChildSet>,
+    pub clones: Cell<u32>,
+}
+
+impl PersistentHost {
+    pub fn children(&self) -> Vec<Rc<FrozenNode>> {
+        self.container.borrow().clone()
+    }
+
+    pub fn html(&self) -> String {
+        self.container
+            .borrow()
+            .iter()
+            .map(|child| child.to_html())
+            .collect()
+    }
+}
+
+impl HostConfig for PersistentHost {
+    fn create_text_instance(&self, content: String) -> Rc<dyn Any> {
+        FrozenNode::new("#text", Value::null(), Some(content), false)
+    }
+
+    fn create_instance(&self, _type: String, _host_context: &str) -> Rc<dyn Any> {
+        FrozenNode::new(&_type, Value::null(), None, false)
+    }
+
+    fn get_root_host_context(&self, _container: Rc<dyn Any>) -> String {
+        String::new()
+    }
+
+    fn get_child_host_context(&self, parent_host_context: &str, _type: &str) -> String {
+        parent_host_context.to_string()
+    }
+
+    fn get_public_instance(&self, _instance: Rc<dyn Any>) -> Value {
+        Value::null()
+    }
+
+    fn get_portal_container(&self, _container_info: &Value) -> Rc<dyn Any> {
+        unreachable!("PersistentHost has no portals")
+    }
+
+    fn prepare_portal_mount(&self, _portal_container: Rc<dyn Any>, _parent_container: Rc<dyn Any>) {
+    }
+
+    fn append_initial_child(&self, parent: Rc<dyn Any>, child: Rc<dyn Any>) {
+        let parent = FrozenNode::from_instance(&parent);
+        assert!(!parent.committed.get(), "appended to a committed node");
+        parent
+            .children
+            .borrow_mut()
+            .push(FrozenNode::from_instance(&child));
+    }
+
+    fn finalize_initial_children(&self, _instance: Rc<dyn Any>, _type: String, _props: &Value) {}
+
+    fn commit_update(
+        &self,
+        _instance: Rc<dyn Any>,
+        _type: String,
+        _old_props: &Value,
+        _new_props: &Value,
+    ) {
+        unreachable!("commit_update in persistent mode")
+    }
+
+    fn append_child_to_container(&self, _child: Rc<dyn Any>, _parent: Rc<dyn Any>) {
+        unreachable!("append_child_to_container in persistent mode")
+    }
+
+    fn insert_child_to_container(
+        &self,
+        _child: Rc<dyn Any>,
+        _container: Rc<dyn Any>,
+        _before: Rc<dyn Any>,
+    ) {
+        unreachable!("insert_child_to_container in persistent mode")
+    }
+
+    fn remove_child(&self, _child: Rc<dyn Any>, _container: Rc<dyn Any>) {
+        unreachable!("remove_child in persistent mode")
+    }
+
+    fn reset_after_commit(&self, _container: Rc<dyn Any>) {}
+
+    fn reset_text_content(&self, _instance: Rc<dyn Any>) {
+        unreachable!("reset_text_content in persistent mode")
+    }
+
+    fn commit_text_update(&self, _text_instance: Rc<dyn Any>, _content: String) {
+        unreachable!("commit_text_update in persistent mode")
+    }
+
+    fn hide_instance(&self, _instance: Rc<dyn Any>) {
+        unreachable!("hide_instance in persistent mode")
+    }
+
+    fn unhide_instance(&self, _instance: Rc<dyn Any>) {
+        unreachable!("unhide_instance in persistent mode")
+    }
+
+    fn hide_text_instance(&self, _text_instance: Rc<dyn Any>) {
+        unreachable!("hide_text_instance in persistent mode")
+    }
+
+    fn unhide_text_instance(&self, _text_instance: Rc<dyn Any>, _content: String) {
+        unreachable!("unhide_text_instance in persistent mode")
+    }
+
+    fn persistent(&self) -> Option<&dyn PersistentHostConfig> {
+        Some(self)
+    }
+}
+
+impl PersistentHostConfig for PersistentHost {
+    fn clone_instance(
+        &self,
+        instance: Rc<dyn Any>,
+        _type: String,
+        _old_props: &Value,
+        new_props: &Value,
+        keep_children: bool,
+    ) -> Rc<dyn Any> {
+        self.clones.set(self.clones.get() + 1);
+        let instance = FrozenNode::from_instance(&instance);
+        let clone = FrozenNode::new(&_type, new_props.clone(), None, instance.hidden);
+        if keep_children {
+            *clone.children.borrow_mut() = instance.children();
+        }
+        clone
+    }
+
+    fn clone_hidden_instance(
+        &self,
+        instance: Rc<dyn Any>,
+        _type: String,
+        props: &Value,
+    ) -> Rc<dyn Any> {
+        let instance = FrozenNode::from_instance(&instance);
+        let clone = FrozenNode::new(&_type, props.clone(), None, true);
+        *clone.children.borrow_mut() = instance.children();
+        clone
+    }
+
+    fn clone_hidden_text_instance(
+        &self,
+        _text_instance: Rc<dyn Any>,
+        content: String,
+    ) -> Rc<dyn Any> {
+        FrozenNode::new("#text", Value::null(), Some(content), true)
+    }
+
+    fn create_container_child_set(&self, _container: Rc<dyn Any>) -> Rc<dyn Any> {
+        Rc::new(ChildSet::default())
+    }
+
+    fn append_child_to_container_child_set(&self, child_set: Rc<dyn Any>, child: Rc<dyn Any>) {
+        let child_set = child_set.downcast::<ChildSet>().unwrap();
+        child_set
+            .borrow_mut()
+            .push(FrozenNode::from_instance(&child));
+    }
+
+    fn replace_container_children(&self, container: Rc<dyn Any>, new_children: Rc<dyn Any>) {
+        let container = container.downcast::<ChildSet>().unwrap();
+        let new_children = new_children.downcast::<ChildSet>().unwrap();
+        for child in new_children.borrow().iter() {
+            child.commit();
+        }
+        *container.borrow_mut() = new_children.take();
+    }
+}
+
+pub struct PersistentRoot {
+    pub host: Rc<PersistentHost>,
+    pub reconciler: Reconciler,
+    pub root: Rc<RefCell<FiberRootNode>>,
+}
+
+impl PersistentRoot {
+    pub fn new() -> Self {
+        let host = Rc::new(PersistentHost::default());
+        let reconciler = Reconciler::new(host.clone());
+        let root = reconciler.create_container(host.container.clone());
+        PersistentRoot {
+            host,
+            reconciler,
+            root,
+        }
+    }
+
+    pub fn render(&self, element: Value) {
+        self.reconciler.update_container(element, self.root.clone());
+    }
+}
@@ -0,0 +1,67 @@
+// The development warnings of the reconciler and the component stacks they end with.
+mod common;
+
+use common::{element, host, list, lock, object, text, Logs, TestRoot};
+use react_reconciler::react_value::Value;
+
+// <li>{content}</li> as jsx creates it, with a _store that react validates once
+fn li(key: Option<&str>, content: &str) -> Value {
+    let li = element(text("li"), key, vec![("children", text(content))]);
+    li.set(
+        "_store",
+        object(vec![("validated", Value::from_bool(false))]),
+    )
+    .unwrap();
+    li
+}
+
+#[test]
+fn warns_about_duplicate_keys() {
+    let _lock = lock();
+    let logs = Logs::capture();
+    let root = TestRoot::new();
+    root.render(host(
+        "ul",
+        vec![],
+        vec![list(vec![li(Some("a"), "1"), li(Some("a"), "2")])],
+    ));
+    let logs = logs.take();
+    assert_eq!(logs.len(), 1);
+    assert!(logs[0].starts_with("Encountered two children with the same key, `a`."));
+    assert!(logs[0].ends_with("\n    in li\n    in ul"));
+}
+
+#[test]
+fn warns_about_a_missing_key_once_per_element() {
+    let _lock = lock();
+    let logs = Logs::capture();
+    let root = TestRoot::new();
+    let items = list(vec![li(None, "1"), li(Some("b"), "2")]);
+    root.render(host("ul", vec![], vec![items.clone()]));
+    let first = logs.take();
+    assert_eq!(first.len(), 1);
+    assert!(first[0].starts_with("Each child in a list should have a unique \"key\" prop."));
+    assert!(first[0].ends_with("\n    in li\n    in ul"));
+
+    root.render(host("ul", vec![], vec![items]));
+    assert_eq!(logs.take(), Vec::<String>::new());
+    assert_eq!(root.html(), "<ul><li>1</li><li>2</li></ul>");
+}
+
+#[test]
+fn warns_about_objects_as_children_and_skips_them() {
+    let _lock = lock();
+    let logs = Logs::capture();
+    let root = TestRoot::new();
+    root.render(host(
+        "div",
+        vec![],
+        vec![text("a"), object(vec![("x", text("1")), ("y", text("2"))])],
+    ));
+    assert_eq!(root.html(), "<div>a</div>");
+    let logs = logs.take();
+    assert_eq!(logs.len(), 1);
+    assert!(logs[0]
+        .starts_with("Objects are not valid as a React child (found: object with keys {x, y})."));
+    assert!(logs[0].ends_with("\n    in div"));
+}
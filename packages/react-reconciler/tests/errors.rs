@@ -0,0 +1,73 @@
+// Errors thrown while rendering, there are no error boundaries so they unwind to the root.
+mod common;
+
+use std::{cell::Cell, rc::Rc};
+
+use common::{component, element, host, lock, text, Logs, TestRoot};
+use react_reconciler::react_value::Value;
+
+// a component that throws boom, counting its renders
+fn thrower(renders: &Rc<Cell<u32>>) -> Value {
+    let renders = renders.clone();
+    component(move |_| {
+        renders.set(renders.get() + 1);
+        Err(text("boom"))
+    })
+}
+
+#[test]
+fn keeps_the_committed_tree_when_a_component_throws() {
+    let _lock = lock();
+    let logs = Logs::capture();
+    let root = TestRoot::new();
+    let renders = Rc::new(Cell::new(0));
+    root.render(host("div", vec![], vec![text("a")]));
+    assert_eq!(*root.host.commits.borrow(), 1);
+
+    root.render(host(
+        "div",
+        vec![],
+        vec![text("b"), element(thrower(&renders), None, vec![])],
+    ));
+    assert_eq!(root.html(), "<div>a</div>");
+    assert_eq!(*root.host.commits.borrow(), 1);
+    // the lane is dropped instead of rendered again
+    assert_eq!(renders.get(), 1);
+    let logs = logs.take();
+    assert_eq!(logs.len(), 1);
+    assert!(logs[0].starts_with("Uncaught error, the update was not committed"));
+    assert!(logs[0].contains("boom"));
+}
+
+#[test]
+fn commits_nothing_when_the_first_render_throws() {
+    let _lock = lock();
+    let _logs = Logs::capture();
+    let root = TestRoot::new();
+    let renders = Rc::new(Cell::new(0));
+    root.render(host(
+        "div",
+        vec![],
+        vec![
+            host("span", vec![], vec![text("before")]),
+            element(thrower(&renders), None, vec![]),
+        ],
+    ));
+    assert_eq!(root.html(), "");
+    assert_eq!(*root.host.commits.borrow(), 0);
+}
+
+#[test]
+fn renders_the_next_update_after_an_error() {
+    let _lock = lock();
+    let _logs = Logs::capture();
+    let root = TestRoot::new();
+    let renders = Rc::new(Cell::new(0));
+    root.render(host("div", vec![], vec![text("a")]));
+    root.render(element(thrower(&renders), None, vec![]));
+    assert_eq!(root.html(), "<div>a</div>");
+
+    root.render(host("div", vec![], vec![text("c")]));
+    assert_eq!(root.html(), "<div>c</div>");
+    assert_eq!(renders.get(), 1);
+}
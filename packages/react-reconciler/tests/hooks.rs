@@ -0,0 +1,266 @@
+// Hooks called from rust components through react::hooks, without javascript.
+mod common;
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use common::{component, element, list, lock, text, Logs, TestRoot};
+use react::hooks::{
+    use_insertion_effect, use_memo, use_state, use_sync_external_store, use_transition,
+};
+use react_reconciler::react_value::Value;
+
+#[test]
+fn keeps_the_state_of_use_state_across_updates() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let set_count: Rc<RefCell<Option<Value>>> = Rc::new(RefCell::new(None));
+
+    let setter = set_count.clone();
+    let counter = component(move |_| {
+        let (count, set) = use_state(Value::from_f64(0.0))?;
+        *setter.borrow_mut() = Some(set);
+        Ok(count)
+    });
+    root.render(element(counter, None, vec![]));
+    assert_eq!(root.html(), "0");
+
+    let set = set_count.borrow().clone().unwrap();
+    set.call(&[Value::from_f64(1.0)]).unwrap();
+    assert_eq!(root.html(), "1");
+
+    // an updater function gets the previous state
+    let increment = Value::function(|args| Ok(Value::from_f64(args[0].as_f64().unwrap() + 1.0)));
+    set.call(&[increment]).unwrap();
+    assert_eq!(root.html(), "2");
+    // the same setter every render
+    assert!(set.is_same(set_count.borrow().as_ref().unwrap()));
+}
+
+#[test]
+fn recomputes_use_memo_when_its_deps_change() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let computed = Rc::new(Cell::new(0));
+
+    let count = computed.clone();
+    let doubled = component(move |props| {
+        let value = props.get("value");
+        let count = count.clone();
+        let input = value.clone();
+        use_memo(
+            Value::function(move |_| {
+                count.set(count.get() + 1);
+                Ok(Value::from_f64(input.as_f64().unwrap() * 2.0))
+            }),
+            list(vec![value]),
+        )
+    });
+    let render = |value: f64| {
+        root.render(element(
+            doubled.clone(),
+            None,
+            vec![("value", Value::from_f64(value))],
+        ))
+    };
+    render(2.0);
+    render(2.0);
+    assert_eq!(root.html(), "4");
+    assert_eq!(computed.get(), 1);
+
+    render(3.0);
+    assert_eq!(root.html(), "6");
+    assert_eq!(computed.get(), 2);
+}
+
+#[test]
+fn runs_insertion_effects_and_their_cleanup() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let log = Rc::new(RefCell::new(vec![]));
+
+    let effect_log = log.clone();
+    let styled = component(move |props| {
+        let name = props.get("name").as_string().unwrap();
+        let create_log = effect_log.clone();
+        let create_name = name.clone();
+        use_insertion_effect(
+            Value::function(move |_| {
+                create_log
+                    .borrow_mut()
+                    .push(format!("insert {}", create_name));
+                let destroy_log = create_log.clone();
+                let destroy_name = create_name.clone();
+                Ok(Value::function(move |_| {
+                    destroy_log
+                        .borrow_mut()
+                        .push(format!("remove {}", destroy_name));
+                    Ok(Value::undefined())
+                }))
+            }),
+            list(vec![Value::from_string(name.clone())]),
+        )?;
+        Ok(Value::from_string(name))
+    });
+    let render = |name: &str| {
+        root.render(element(styled.clone(), None, vec![("name", text(name))]));
+    };
+    render("a");
+    render("a");
+    assert_eq!(*log.borrow(), vec!["insert a"]);
+
+    render("b");
+    root.render(Value::null());
+    assert_eq!(
+        *log.borrow(),
+        vec!["insert a", "remove a", "insert b", "remove b"]
+    );
+}
+
+#[test]
+fn re_renders_when_an_external_store_changes() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let store = Rc::new(Cell::new(1.0));
+    let listeners: Rc<RefCell<Vec<Value>>> = Rc::new(RefCell::new(vec![]));
+
+    let subscribed = listeners.clone();
+    let subscribe = Value::function(move |args| {
+        subscribed.borrow_mut().push(args[0].clone());
+        Ok(Value::undefined())
+    });
+    let value = store.clone();
+    let get_snapshot = Value::function(move |_| Ok(Value::from_f64(value.get())));
+    let reader =
+        component(move |_| use_sync_external_store(subscribe.clone(), get_snapshot.clone(), None));
+    root.render(element(reader, None, vec![]));
+    assert_eq!(root.html(), "1");
+
+    // the subscription is a passive effect
+    scheduler::unstable_flush_all();
+    assert_eq!(listeners.borrow().len(), 1);
+
+    store.set(2.0);
+    let listener = listeners.borrow()[0].clone();
+    listener.call(&[]).unwrap();
+    assert_eq!(root.html(), "2");
+}
+
+#[test]
+fn renders_the_update_of_a_transition_later() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let start: Rc<RefCell<Option<(Value, Value)>>> = Rc::new(RefCell::new(None));
+
+    let captured = start.clone();
+    let tabs = component(move |_| {
+        let (tab, set_tab) = use_state(text("home"))?;
+        let (is_pending, start_transition) = use_transition()?;
+        *captured.borrow_mut() = Some((set_tab, start_transition));
+        let pending = if is_pending.as_bool() == Some(true) {
+            " (pending)"
+        } else {
+            ""
+        };
+        Ok(Value::from_string(format!(
+            "{}{}",
+            tab.as_string().unwrap(),
+            pending
+        )))
+    });
+    root.render(element(tabs, None, vec![]));
+
+    let (set_tab, start_transition) = start.borrow().clone().unwrap();
+    let scope = Value::function(move |_| set_tab.call(&[text("settings")]));
+    start_transition.call(&[scope]).unwrap();
+    assert_eq!(root.html(), "home (pending)");
+
+    scheduler::unstable_flush_all();
+    assert_eq!(root.html(), "settings");
+}
+
+#[test]
+fn fails_a_hook_called_outside_of_a_component() {
+    let _lock = lock();
+    let logs = Logs::capture();
+    let error = use_state(Value::undefined()).unwrap_err();
+    assert!(error.as_string().unwrap().starts_with("Invalid hook call."));
+    assert_eq!(logs.take().len(), 1);
+}
+
+#[test]
+fn checks_the_server_snapshot_of_an_external_store() {
+    let _lock = lock();
+    let logs = Logs::capture();
+    let root = TestRoot::new();
+    let subscribe = Value::function(|_| Ok(Value::undefined()));
+    let get_snapshot = Value::function(|_| Ok(Value::from_f64(1.0)));
+    let store_reader = |get_server_snapshot: Value| {
+        let subscribe = subscribe.clone();
+        let get_snapshot = get_snapshot.clone();
+        component(move |_| {
+            use_sync_external_store(
+                subscribe.clone(),
+                get_snapshot.clone(),
+                Some(get_server_snapshot.clone()),
+            )
+        })
+    };
+
+    // a new list every call
+    let uncached = Value::function(|_| Ok(list(vec![])));
+    root.render(element(store_reader(uncached), None, vec![]));
+    assert_eq!(root.html(), "1");
+    assert_eq!(
+        logs.take(),
+        vec!["The result of getServerSnapshot should be cached to avoid an infinite loop"]
+    );
+
+    root.render(Value::null());
+    root.render(element(store_reader(text("snapshot")), None, vec![]));
+    assert_eq!(root.html(), "");
+    let logs = logs.take();
+    assert_eq!(logs.len(), 1);
+    assert!(logs[0].contains("getServerSnapshot must be a function"));
+    // the passive effects of the first store are flushed for the roots of later tests
+    scheduler::unstable_flush_all();
+}
+
+#[test]
+fn recomputes_use_memo_when_its_deps_are_not_a_list_or_change_size() {
+    let _lock = lock();
+    let logs = Logs::capture();
+    let root = TestRoot::new();
+    let computed = Rc::new(Cell::new(0));
+
+    let count = computed.clone();
+    let memo = component(move |props| {
+        let count = count.clone();
+        use_memo(
+            Value::function(move |_| {
+                count.set(count.get() + 1);
+                Ok(Value::from_f64(count.get() as f64))
+            }),
+            props.get("deps"),
+        )
+    });
+    let render = |deps: Value| root.render(element(memo.clone(), None, vec![("deps", deps)]));
+
+    render(text("a"));
+    render(text("a"));
+    assert_eq!(computed.get(), 2);
+    let warnings = logs.take();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0]
+        .starts_with("useMemo received a final argument that is not an array (instead, received"));
+
+    render(list(vec![text("a")]));
+    render(list(vec![text("a"), text("b")]));
+    assert_eq!(computed.get(), 4);
+    let warnings = logs.take();
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[1]
+        .starts_with("The final argument passed to useMemo changed size between renders."));
+}
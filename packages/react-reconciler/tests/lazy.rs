@@ -0,0 +1,131 @@
+// Lazy components, the _init of react::lazy is stood in for by a function of the test.
+mod common;
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use common::{component, element, host, lock, object, text, Deferred, Logs, TestRoot};
+use react_reconciler::react_value::Value;
+use shared::{REACT_FORWARD_REF_TYPE, REACT_LAZY_TYPE, REACT_SUSPENSE_TYPE};
+
+// a lazy type whose module is pending until loaded is set and deferred resolved
+struct Lazy {
+    lazy_type: Value,
+    deferred: Rc<Deferred>,
+    loaded: Rc<RefCell<Option<Value>>>,
+    inits: Rc<Cell<u32>>,
+}
+
+impl Lazy {
+    fn new() -> Self {
+        let deferred = Rc::new(Deferred::new());
+        let loaded: Rc<RefCell<Option<Value>>> = Rc::new(RefCell::new(None));
+        let inits = Rc::new(Cell::new(0));
+
+        let thenable = deferred.thenable.clone();
+        let init_loaded = loaded.clone();
+        let init_count = inits.clone();
+        let init = Value::function(move |_| {
+            init_count.set(init_count.get() + 1);
+            match init_loaded.borrow().clone() {
+                Some(component) => Ok(component),
+                None => Err(thenable.clone()),
+            }
+        });
+        Lazy {
+            lazy_type: object(vec![
+                ("$$typeof", text(REACT_LAZY_TYPE)),
+                ("_payload", object(vec![])),
+                ("_init", init),
+            ]),
+            deferred,
+            loaded,
+            inits,
+        }
+    }
+
+    fn load(&self, component: Value) {
+        *self.loaded.borrow_mut() = Some(component);
+        self.deferred.resolve();
+    }
+}
+
+fn suspense(children: Value) -> Value {
+    element(
+        text(REACT_SUSPENSE_TYPE),
+        None,
+        vec![("fallback", text("loading")), ("children", children)],
+    )
+}
+
+#[test]
+fn suspends_until_the_component_loads() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let lazy = Lazy::new();
+    root.render(suspense(element(
+        lazy.lazy_type.clone(),
+        None,
+        vec![("name", text("a"))],
+    )));
+    assert_eq!(root.html(), "loading");
+
+    lazy.load(component(|props| {
+        Ok(host("b", vec![], vec![props.get("name")]))
+    }));
+    assert_eq!(root.html(), "<b>a</b>");
+}
+
+#[test]
+fn reuses_the_resolved_component_on_updates() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let lazy = Lazy::new();
+    let app = |name: &str| {
+        suspense(element(
+            lazy.lazy_type.clone(),
+            None,
+            vec![("name", text(name))],
+        ))
+    };
+    root.render(app("a"));
+    lazy.load(component(|props| Ok(props.get("name"))));
+    assert_eq!(root.html(), "a");
+    let inits = lazy.inits.get();
+
+    root.render(app("b"));
+    assert_eq!(root.html(), "b");
+    assert_eq!(lazy.inits.get(), inits);
+}
+
+#[test]
+fn resolves_to_a_forward_ref() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let lazy = Lazy::new();
+    root.render(suspense(element(lazy.lazy_type.clone(), None, vec![])));
+
+    let render = Value::function(|_| Ok(host("input", vec![], vec![])));
+    lazy.load(object(vec![
+        ("$$typeof", text(REACT_FORWARD_REF_TYPE)),
+        ("render", render),
+    ]));
+    assert_eq!(root.html(), "<input></input>");
+}
+
+#[test]
+fn does_not_commit_a_module_that_is_not_a_component() {
+    let _lock = lock();
+    let logs = Logs::capture();
+    let root = TestRoot::new();
+    let lazy = Lazy::new();
+    root.render(suspense(element(lazy.lazy_type.clone(), None, vec![])));
+
+    lazy.load(text("not a component"));
+    assert_eq!(root.html(), "loading");
+    let logs = logs.take();
+    assert_eq!(logs.len(), 1);
+    assert!(logs[0].contains("Element type is invalid"));
+}
@@ -0,0 +1,130 @@
+// Renders driven from rust with the values of common, without JsValue or javascript.
+mod common;
+
+use std::{cell::RefCell, rc::Rc};
+
+use common::{component, element, host, list, lock, object, text, TestRoot};
+use react_reconciler::fiber::{FiberNode, StateNode};
+use react_reconciler::fiber_snapshot::{get_fiber_id, get_host_node_id};
+use react_reconciler::react_value::Value;
+use shared::REACT_PROVIDER_TYPE;
+
+#[test]
+fn mounts_host_components_and_text() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    root.render(host(
+        "div",
+        vec![("id", text("app"))],
+        vec![text("count: "), Value::from_f64(1.0)],
+    ));
+    assert_eq!(root.html(), "<div id=\"app\">count: 1</div>");
+}
+
+#[test]
+fn updates_in_place() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let label = component(|props| {
+        Ok(host(
+            "span",
+            vec![("title", props.get("title"))],
+            vec![props.get("title")],
+        ))
+    });
+    root.render(element(label.clone(), None, vec![("title", text("a"))]));
+    let span = root.container.borrow().children[0].clone();
+
+    root.render(element(label, None, vec![("title", text("b"))]));
+    assert_eq!(root.html(), "<span title=\"b\">b</span>");
+    assert!(Rc::ptr_eq(&span, &root.container.borrow().children[0]));
+}
+
+#[test]
+fn moves_keyed_children() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let items = |keys: &[&str]| {
+        host(
+            "ul",
+            vec![],
+            vec![list(
+                keys.iter()
+                    .map(|key| element(text("li"), Some(key), vec![("children", text(key))]))
+                    .collect(),
+            )],
+        )
+    };
+    root.render(items(&["a", "b", "c"]));
+    let ul = root.container.borrow().children[0].clone();
+    let c = ul.borrow().children[2].clone();
+
+    root.render(items(&["c", "a"]));
+    assert_eq!(root.html(), "<ul><li>c</li><li>a</li></ul>");
+    assert!(Rc::ptr_eq(&c, &ul.borrow().children[0]));
+}
+
+#[test]
+fn unmounts_the_tree() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    root.render(host("div", vec![], vec![text("gone")]));
+    root.render(Value::null());
+    assert_eq!(root.html(), "");
+}
+
+#[test]
+fn reads_context_from_the_nearest_provider() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let context = object(vec![("_currentValue", text("default"))]);
+    let provider = object(vec![
+        ("$$typeof", text(REACT_PROVIDER_TYPE)),
+        ("_context", context.clone()),
+    ]);
+    let read_context = context.clone();
+    let consumer = component(move |_| Ok(read_context.get("_currentValue")));
+
+    root.render(host(
+        "div",
+        vec![],
+        vec![
+            element(consumer.clone(), None, vec![]),
+            element(
+                provider,
+                None,
+                vec![
+                    ("value", text(" provided")),
+                    ("children", element(consumer, None, vec![])),
+                ],
+            ),
+        ],
+    ));
+    assert_eq!(root.html(), "<div>default provided</div>");
+    assert_eq!(context.get("_currentValue"), text("default"));
+}
+
+#[test]
+fn keeps_the_ids_of_fibers_and_host_nodes_across_renders() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let first_child = || root.root.borrow().current.borrow().child.clone().unwrap();
+    let host_node = |fiber: &Rc<RefCell<FiberNode>>| match fiber.borrow().state_node.as_deref() {
+        Some(StateNode::Element(instance)) => get_host_node_id(instance),
+        _ => unreachable!(),
+    };
+
+    root.render(host("p", vec![("id", text("a"))], vec![]));
+    let first = first_child();
+    root.render(host("p", vec![("id", text("b"))], vec![]));
+    let second = first_child();
+    // the fibers are alternates of each other
+    assert!(!Rc::ptr_eq(&first, &second));
+    assert_eq!(get_fiber_id(&first), get_fiber_id(&second));
+    assert_eq!(host_node(&first), host_node(&second));
+
+    root.render(element(text("p"), Some("new"), vec![]));
+    let third = first_child();
+    assert_ne!(get_fiber_id(&third), get_fiber_id(&second));
+    assert_ne!(host_node(&third), host_node(&second));
+}
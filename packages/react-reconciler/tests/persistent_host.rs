@@ -0,0 +1,107 @@
+// Renders into PersistentHost, whose nodes never change once committed.
+mod common;
+
+use std::{cell::Cell, rc::Rc};
+
+use common::{component, element, host, list, lock, text, Deferred, PersistentRoot};
+use react_reconciler::react_value::Value;
+use shared::REACT_SUSPENSE_TYPE;
+
+#[test]
+fn mounts_a_new_tree() {
+    let _lock = lock();
+    let root = PersistentRoot::new();
+    root.render(host(
+        "div",
+        vec![],
+        vec![host("span", vec![], vec![text("a")]), text("b")],
+    ));
+    assert_eq!(root.host.html(), "<div><span>a</span>b</div>");
+    assert_eq!(root.host.clones.get(), 0);
+}
+
+#[test]
+fn clones_the_changed_path_and_shares_the_rest() {
+    let _lock = lock();
+    let root = PersistentRoot::new();
+    // the same element, so react bails out of it
+    let same = host("p", vec![], vec![text("same")]);
+    let tree = |label: &str| {
+        host(
+            "div",
+            vec![],
+            vec![same.clone(), host("span", vec![], vec![text(label)])],
+        )
+    };
+    root.render(tree("a"));
+    let old_div = root.host.children()[0].clone();
+    let old_p = old_div.children()[0].clone();
+
+    root.render(tree("b"));
+    let new_div = root.host.children()[0].clone();
+    assert_eq!(root.host.html(), "<div><p>same</p><span>b</span></div>");
+    // the committed tree is left as it was
+    assert_eq!(old_div.to_html(), "<div><p>same</p><span>a</span></div>");
+    assert!(!Rc::ptr_eq(&old_div, &new_div));
+    assert!(Rc::ptr_eq(&old_p, &new_div.children()[0]));
+}
+
+#[test]
+fn leaves_deleted_nodes_out_of_the_next_child_set() {
+    let _lock = lock();
+    let root = PersistentRoot::new();
+    let items: Vec<Value> = ["a", "b", "c"]
+        .iter()
+        .map(|key| element(text("li"), Some(key), vec![("children", text(key))]))
+        .collect();
+    root.render(list(items.clone()));
+    let old_children = root.host.children();
+
+    root.render(list(vec![items[0].clone(), items[2].clone()]));
+    assert_eq!(root.host.html(), "<li>a</li><li>c</li>");
+    assert!(Rc::ptr_eq(&old_children[0], &root.host.children()[0]));
+    assert!(Rc::ptr_eq(&old_children[2], &root.host.children()[1]));
+
+    root.render(Value::null());
+    assert_eq!(root.host.html(), "");
+}
+
+#[test]
+fn hides_and_unhides_suspended_children_with_clones() {
+    let _lock = lock();
+    let root = PersistentRoot::new();
+    let deferred = Rc::new(Deferred::new());
+    let suspended = Rc::new(Cell::new(false));
+
+    let thenable = deferred.thenable.clone();
+    let is_suspended = suspended.clone();
+    let data = component(move |_| match is_suspended.get() {
+        true => Err(thenable.clone()),
+        false => Ok(host("b", vec![], vec![text("data")])),
+    });
+    let app = || {
+        element(
+            text(REACT_SUSPENSE_TYPE),
+            None,
+            vec![
+                ("fallback", text("loading")),
+                ("children", element(data.clone(), None, vec![])),
+            ],
+        )
+    };
+    root.render(app());
+    assert_eq!(root.host.html(), "<b>data</b>");
+    let shown = root.host.children()[0].clone();
+
+    suspended.set(true);
+    root.render(app());
+    assert_eq!(root.host.html(), "loading");
+    let hidden = root.host.children()[0].clone();
+    assert!(hidden.hidden);
+    assert!(!shown.hidden);
+
+    suspended.set(false);
+    deferred.resolve();
+    assert_eq!(root.host.html(), "<b>data</b>");
+    assert_eq!(root.host.children().len(), 1);
+}
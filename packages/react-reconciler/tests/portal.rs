@@ -0,0 +1,69 @@
+// Portals rendered from rust into containers of the in-memory host.
+mod common;
+
+use std::{cell::RefCell, rc::Rc};
+
+use common::{host, lock, portal, text, Node, TestRoot};
+use react_reconciler::react_value::Value;
+
+#[test]
+fn renders_portals_into_their_container() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let modal = Node::new("modal");
+    root.render(host(
+        "div",
+        vec![],
+        vec![portal(host("p", vec![], vec![text("inside")]), &modal)],
+    ));
+    assert_eq!(root.html(), "<div></div>");
+    assert_eq!(modal.borrow().children_html(), "<p>inside</p>");
+
+    root.render(Value::null());
+    assert_eq!(modal.borrow().children_html(), "");
+}
+
+#[test]
+fn updates_the_children_of_a_portal_in_place() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let modal = Node::new("modal");
+    let render = |content: &str| {
+        root.render(host(
+            "div",
+            vec![],
+            vec![portal(
+                host("p", vec![("title", text(content))], vec![text(content)]),
+                &modal,
+            )],
+        ))
+    };
+    render("a");
+    let p = modal.borrow().children[0].clone();
+
+    render("b");
+    assert_eq!(modal.borrow().children_html(), "<p title=\"b\">b</p>");
+    assert!(Rc::ptr_eq(&p, &modal.borrow().children[0]));
+}
+
+#[test]
+fn moves_the_children_when_the_container_changes() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let first = Node::new("first");
+    let second = Node::new("second");
+    let render = |container: &Rc<RefCell<Node>>| {
+        root.render(host(
+            "div",
+            vec![],
+            vec![portal(host("p", vec![], vec![text("moved")]), container)],
+        ))
+    };
+    render(&first);
+    assert_eq!(first.borrow().children_html(), "<p>moved</p>");
+
+    render(&second);
+    assert_eq!(first.borrow().children_html(), "");
+    assert_eq!(second.borrow().children_html(), "<p>moved</p>");
+    assert_eq!(root.html(), "<div></div>");
+}
@@ -0,0 +1,226 @@
+// Profiler timings with a fake clock that only components advance.
+mod common;
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use common::{component, element, host, lock, text, Deferred, TestRoot};
+use react_reconciler::{react_value::Value, set_profiler_clock, ProfilerClock};
+use shared::{REACT_PROFILER_TYPE, REACT_SUSPENSE_TYPE};
+
+#[derive(Default)]
+struct FakeClock(Cell<f64>);
+
+impl FakeClock {
+    fn advance(&self, ms: f64) {
+        self.0.set(self.0.get() + ms);
+    }
+}
+
+impl ProfilerClock for FakeClock {
+    fn now(&self) -> f64 {
+        self.0.get()
+    }
+}
+
+// id, phase, actualDuration, baseDuration, startTime, commitTime
+type Render = (String, String, f64, f64, f64, f64);
+
+struct Recorder {
+    renders: Rc<RefCell<Vec<Render>>>,
+    on_render: Value,
+}
+
+impl Recorder {
+    fn new() -> Self {
+        let renders = Rc::new(RefCell::new(vec![]));
+        let pushed = renders.clone();
+        let on_render = Value::function(move |args| {
+            pushed.borrow_mut().push((
+                args[0].as_string().unwrap(),
+                args[1].as_string().unwrap(),
+                args[2].as_f64().unwrap(),
+                args[3].as_f64().unwrap(),
+                args[4].as_f64().unwrap(),
+                args[5].as_f64().unwrap(),
+            ));
+            Ok(Value::undefined())
+        });
+        Recorder { renders, on_render }
+    }
+
+    fn take(&self) -> Vec<Render> {
+        std::mem::take(&mut *self.renders.borrow_mut())
+    }
+}
+
+fn profiler(id: &str, recorder: &Recorder, children: Value) -> Value {
+    element(
+        text(REACT_PROFILER_TYPE),
+        None,
+        vec![
+            ("id", text(id)),
+            ("onRender", recorder.on_render.clone()),
+            ("children", children),
+        ],
+    )
+}
+
+fn render(id: &str, phase: &str, durations: (f64, f64), start: f64, commit: f64) -> Render {
+    (
+        id.to_string(),
+        phase.to_string(),
+        durations.0,
+        durations.1,
+        start,
+        commit,
+    )
+}
+
+// a component that takes ms to render
+fn slow(clock: &Rc<FakeClock>, ms: f64) -> Value {
+    let clock = clock.clone();
+    component(move |props| {
+        clock.advance(ms);
+        Ok(props.get("children"))
+    })
+}
+
+#[test]
+fn reports_the_phase_and_durations_of_nested_profilers() {
+    let _lock = lock();
+    let clock = Rc::new(FakeClock::default());
+    set_profiler_clock(clock.clone());
+    let root = TestRoot::new();
+    let recorder = Recorder::new();
+    let three = slow(&clock, 3.0);
+    let four = slow(&clock, 4.0);
+    let app = || {
+        profiler(
+            "outer",
+            &recorder,
+            host(
+                "div",
+                vec![],
+                vec![
+                    element(three.clone(), None, vec![("children", text("a"))]),
+                    profiler(
+                        "inner",
+                        &recorder,
+                        element(four.clone(), None, vec![("children", text("b"))]),
+                    ),
+                ],
+            ),
+        )
+    };
+
+    root.render(app());
+    assert_eq!(root.html(), "<div>ab</div>");
+    // children commit their layout effects first
+    assert_eq!(
+        recorder.take(),
+        [
+            render("inner", "mount", (4.0, 4.0), 3.0, 7.0),
+            render("outer", "mount", (7.0, 7.0), 0.0, 7.0),
+        ]
+    );
+
+    root.render(app());
+    assert_eq!(
+        recorder.take(),
+        [
+            render("inner", "update", (4.0, 4.0), 10.0, 14.0),
+            render("outer", "update", (7.0, 7.0), 7.0, 14.0),
+        ]
+    );
+}
+
+#[test]
+fn fires_a_nested_profiler_whose_siblings_have_no_layout_effects() {
+    let _lock = lock();
+    let clock = Rc::new(FakeClock::default());
+    set_profiler_clock(clock.clone());
+    let root = TestRoot::new();
+    let recorder = Recorder::new();
+    let two = slow(&clock, 2.0);
+    // the same elements each time, the siblings end up without flags on the update
+    let before = host("p", vec![], vec![text("before")]);
+    let after = host("p", vec![], vec![text("after")]);
+    let app = || {
+        host(
+            "div",
+            vec![],
+            vec![
+                before.clone(),
+                host(
+                    "section",
+                    vec![],
+                    vec![profiler(
+                        "nested",
+                        &recorder,
+                        element(two.clone(), None, vec![]),
+                    )],
+                ),
+                after.clone(),
+            ],
+        )
+    };
+
+    root.render(app());
+    assert_eq!(
+        recorder.take(),
+        [render("nested", "mount", (2.0, 2.0), 0.0, 2.0)]
+    );
+
+    root.render(app());
+    assert_eq!(
+        recorder.take(),
+        [render("nested", "update", (2.0, 2.0), 2.0, 4.0)]
+    );
+}
+
+#[test]
+fn fires_when_a_suspense_boundary_below_retries() {
+    let _lock = lock();
+    let clock = Rc::new(FakeClock::default());
+    set_profiler_clock(clock.clone());
+    let root = TestRoot::new();
+    let recorder = Recorder::new();
+    let deferred = Rc::new(Deferred::new());
+    let resolved = Rc::new(Cell::new(false));
+
+    let thenable = deferred.thenable.clone();
+    let is_resolved = resolved.clone();
+    let data_clock = clock.clone();
+    let data = component(move |_| {
+        data_clock.advance(1.0);
+        match is_resolved.get() {
+            true => Ok(text("data")),
+            false => Err(thenable.clone()),
+        }
+    });
+    root.render(profiler(
+        "app",
+        &recorder,
+        element(
+            text(REACT_SUSPENSE_TYPE),
+            None,
+            vec![
+                ("fallback", text("loading")),
+                ("children", element(data, None, vec![])),
+            ],
+        ),
+    ));
+    assert_eq!(root.html(), "loading");
+    assert_eq!(recorder.take().len(), 1);
+
+    resolved.set(true);
+    deferred.resolve();
+    assert_eq!(root.html(), "data");
+    let renders = recorder.take();
+    assert_eq!(renders.len(), 1);
+    assert_eq!(renders[0].0, "app");
+    assert_eq!(renders[0].1, "update");
+}
@@ -0,0 +1,57 @@
+// Refs on host components and forwardRef, rendered from rust with the values of common.
+mod common;
+
+use common::{element_with_ref, lock, object, text, TestRoot};
+use react_reconciler::react_value::Value;
+use shared::REACT_FORWARD_REF_TYPE;
+
+#[test]
+fn attaches_and_detaches_refs() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let ref_object = object(vec![("current", Value::null())]);
+    root.render(element_with_ref(
+        text("input"),
+        None,
+        ref_object.clone(),
+        object(vec![]),
+    ));
+    let input = root.container.borrow().children[0].clone();
+    assert_eq!(
+        ref_object.get("current"),
+        Value::new(common::HostNode(input))
+    );
+
+    root.render(Value::null());
+    assert!(ref_object.get("current").is_nullish());
+}
+
+#[test]
+fn passes_the_ref_to_forward_ref() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let render = Value::function(|args| {
+        Ok(element_with_ref(
+            text("button"),
+            None,
+            args[1].clone(),
+            object(vec![]),
+        ))
+    });
+    let forward_ref = object(vec![
+        ("$$typeof", text(REACT_FORWARD_REF_TYPE)),
+        ("render", render),
+    ]);
+    let ref_object = object(vec![("current", Value::null())]);
+    root.render(element_with_ref(
+        forward_ref,
+        None,
+        ref_object.clone(),
+        object(vec![]),
+    ));
+    let button = root.container.borrow().children[0].clone();
+    assert_eq!(
+        ref_object.get("current"),
+        Value::new(common::HostNode(button))
+    );
+}
@@ -0,0 +1,140 @@
+// Elements and components built with react::element, reconciled as native values.
+mod common;
+
+use std::{cell::RefCell, rc::Rc};
+
+use common::{lock, Logs, TestRoot};
+use react::element::{Component, Element, Props};
+use react::hooks::use_state;
+use react::rsx;
+use react_reconciler::react_value::Value;
+
+fn greeting(props: &Props) -> Result<Element, Value> {
+    let name = props.get_string("name").unwrap_or_default();
+    Ok(rsx!(span[format!("hello {}", name)]))
+}
+
+fn counter(props: &Props) -> Result<Element, Value> {
+    let (count, _) = use_state(props.get("initial"))?;
+    Ok(Element::host("span")
+        .child(count.as_f64().unwrap_or_default().to_string())
+        .into())
+}
+
+struct Badge;
+
+impl Component for Badge {
+    const ID: &'static str = "Badge";
+
+    fn render(props: &Props) -> Result<Element, Value> {
+        Ok(Element::host("b").child(props.children()).into())
+    }
+}
+
+#[test]
+fn renders_a_rust_component_with_its_props() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    root.render(rsx!(div { className: "row" }[rsx!(@greeting { name: "ada" })]).into());
+    assert_eq!(
+        root.html(),
+        "<div className=\"row\"><span>hello ada</span></div>"
+    );
+}
+
+#[test]
+fn renders_a_component_impl_with_its_children() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    root.render(Element::of::<Badge>().child("new").into());
+    assert_eq!(root.html(), "<b>new</b>");
+}
+
+#[test]
+fn keeps_the_state_of_a_component_with_the_same_id() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    root.render(
+        Element::component("counter", counter)
+            .prop("initial", 1)
+            .into(),
+    );
+    root.render(
+        Element::component("counter", counter)
+            .prop("initial", 5)
+            .into(),
+    );
+    assert_eq!(root.html(), "<span>1</span>");
+
+    // another id is another component, even with the same render function
+    root.render(
+        Element::component("other", counter)
+            .prop("initial", 5)
+            .into(),
+    );
+    assert_eq!(root.html(), "<span>5</span>");
+}
+
+#[test]
+fn calls_a_handler_added_with_on() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let clicks = Rc::new(RefCell::new(vec![]));
+    let pushed = clicks.clone();
+    root.render(
+        Element::host("button")
+            .on("onClick", move |event| {
+                pushed.borrow_mut().push(event.as_string().unwrap())
+            })
+            .into(),
+    );
+
+    let button = root.container.borrow().children[0].clone();
+    let on_click = button.borrow().props.get("onClick");
+    on_click.call(&[Value::from("click")]).unwrap();
+    assert_eq!(*clicks.borrow(), vec!["click"]);
+}
+
+#[test]
+fn warns_only_for_the_unkeyed_elements_of_a_list() {
+    let _lock = lock();
+    let logs = Logs::capture();
+    let root = TestRoot::new();
+
+    // children added one by one have a fixed position
+    root.render(Element::host("p").child("a").child(rsx!(b["b"])).into());
+    assert_eq!(root.html(), "<p>a<b>b</b></p>");
+    assert!(logs.take().is_empty());
+
+    let items = vec![1, 2]
+        .into_iter()
+        .map(|item| Element::from(Element::host("li").key(item).child(item.to_string())));
+    root.render(Element::host("ul").child(items.collect::<Vec<_>>()).into());
+    assert_eq!(root.html(), "<ul><li>1</li><li>2</li></ul>");
+    assert!(logs.take().is_empty());
+
+    let items = vec![rsx!(li["1"]), rsx!(li["2"])];
+    root.render(Element::host("ol").child(items).into());
+    let warnings = logs.take();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("unique \"key\" prop"));
+}
+
+#[test]
+fn keeps_the_committed_tree_when_a_rust_component_fails() {
+    let _lock = lock();
+    let logs = Logs::capture();
+    let root = TestRoot::new();
+    root.render(Element::host("p").child("before").into());
+    root.render(
+        Element::host("p")
+            .child(Element::component("broken", |_| {
+                Err(Value::from_string("broken"))
+            }))
+            .into(),
+    );
+    assert_eq!(root.html(), "<p>before</p>");
+    let logs = logs.take();
+    assert_eq!(logs.len(), 1);
+    assert!(logs[0].contains("broken"));
+}
@@ -0,0 +1,67 @@
+// StrictMode renders the components below it twice in development.
+mod common;
+
+use std::{cell::Cell, rc::Rc};
+
+use common::{component, element, host, lock, text, TestRoot};
+use react_reconciler::react_value::Value;
+use shared::REACT_STRICT_MODE_TYPE;
+
+fn counted(renders: &Rc<Cell<u32>>, content: &str) -> Value {
+    let renders = renders.clone();
+    let content = text(content);
+    component(move |_| {
+        renders.set(renders.get() + 1);
+        Ok(content.clone())
+    })
+}
+
+#[test]
+fn renders_components_below_strict_mode_twice() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let strict_renders = Rc::new(Cell::new(0));
+    let loose_renders = Rc::new(Cell::new(0));
+    let strict = counted(&strict_renders, "strict");
+    let loose = counted(&loose_renders, "loose");
+    let app = || {
+        host(
+            "div",
+            vec![],
+            vec![
+                element(loose.clone(), None, vec![]),
+                element(
+                    text(REACT_STRICT_MODE_TYPE),
+                    None,
+                    vec![("children", element(strict.clone(), None, vec![]))],
+                ),
+            ],
+        )
+    };
+
+    root.render(app());
+    assert_eq!(root.html(), "<div>loosestrict</div>");
+    assert_eq!((loose_renders.get(), strict_renders.get()), (1, 2));
+
+    root.render(app());
+    assert_eq!((loose_renders.get(), strict_renders.get()), (2, 4));
+}
+
+#[test]
+fn commits_what_the_second_render_returned() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let renders = Rc::new(Cell::new(0));
+    let counter = renders.clone();
+    // impure, each call returns something else
+    let impure = component(move |_| {
+        counter.set(counter.get() + 1);
+        Ok(Value::from_f64(counter.get() as f64))
+    });
+    root.render(element(
+        text(REACT_STRICT_MODE_TYPE),
+        None,
+        vec![("children", element(impure, None, vec![]))],
+    ));
+    assert_eq!(root.html(), "2");
+}
@@ -0,0 +1,115 @@
+// Suspense boundaries rendered from rust with the values of common.
+mod common;
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use common::{component, element, host, lock, text, Deferred, TestRoot};
+use shared::REACT_SUSPENSE_TYPE;
+
+#[test]
+fn shows_the_fallback_until_the_thenable_resolves() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let deferred = Rc::new(Deferred::new());
+    let resolved = Rc::new(Cell::new(false));
+
+    let thenable = deferred.thenable.clone();
+    let is_resolved = resolved.clone();
+    let data = component(move |_| match is_resolved.get() {
+        true => Ok(text("data")),
+        false => Err(thenable.clone()),
+    });
+    root.render(element(
+        text(REACT_SUSPENSE_TYPE),
+        None,
+        vec![
+            ("fallback", text("loading")),
+            ("children", element(data, None, vec![])),
+        ],
+    ));
+    assert_eq!(root.html(), "loading");
+
+    resolved.set(true);
+    deferred.resolve();
+    assert_eq!(root.html(), "data");
+}
+
+#[test]
+fn hides_the_shown_children_when_they_suspend_again() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let deferred = Rc::new(RefCell::new(Deferred::new()));
+    let suspended = Rc::new(Cell::new(false));
+
+    let thenable = deferred.clone();
+    let is_suspended = suspended.clone();
+    let data = component(move |_| match is_suspended.get() {
+        true => Err(thenable.borrow().thenable.clone()),
+        false => Ok(host("p", vec![], vec![text("data")])),
+    });
+    let render = || {
+        root.render(element(
+            text(REACT_SUSPENSE_TYPE),
+            None,
+            vec![
+                ("fallback", text("loading")),
+                ("children", element(data.clone(), None, vec![])),
+            ],
+        ))
+    };
+    render();
+    assert_eq!(root.html(), "<p>data</p>");
+    let shown = root.container.borrow().children[0].clone();
+
+    suspended.set(true);
+    render();
+    // the primary children stay mounted but hidden, next to the fallback
+    assert_eq!(root.html(), "loading");
+    assert!(shown.borrow().hidden);
+    assert!(root
+        .container
+        .borrow()
+        .children
+        .iter()
+        .any(|node| Rc::ptr_eq(node, &shown)));
+
+    suspended.set(false);
+    deferred.borrow().resolve();
+    scheduler::unstable_flush_all();
+    assert_eq!(root.html(), "<p>data</p>");
+    // the same node is shown again rather than a new one
+    assert!(!shown.borrow().hidden);
+    assert!(Rc::ptr_eq(&root.container.borrow().children[0], &shown));
+}
+
+#[test]
+fn retries_a_render_that_suspended_outside_of_a_boundary_once_pinged() {
+    let _lock = lock();
+    let root = TestRoot::new();
+    let deferred = Rc::new(Deferred::new());
+    let resolved = Rc::new(Cell::new(false));
+
+    root.render(text("before"));
+    let thenable = deferred.thenable.clone();
+    let is_resolved = resolved.clone();
+    let data = component(move |_| match is_resolved.get() {
+        true => Ok(text("data")),
+        false => Err(thenable.clone()),
+    });
+    root.render(element(data, None, vec![]));
+    // nothing to show a fallback, the current tree stays and the lane waits for a ping
+    assert_eq!(root.html(), "before");
+    assert_eq!(*root.host.commits.borrow(), 1);
+    assert!(!root.root.borrow().suspended_lanes.is_empty());
+    assert!(root.root.borrow().pinged_lanes.is_empty());
+
+    resolved.set(true);
+    deferred.resolve();
+    scheduler::unstable_flush_all();
+    assert_eq!(root.html(), "data");
+    assert!(root.root.borrow().suspended_lanes.is_empty());
+    assert!(root.root.borrow().pending_lanes.is_empty());
+}
@@ -0,0 +1,280 @@
+use std::fmt::Write;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Style {
+    // SGR parameters, e.g. "31" or "38;2;255;0;0"
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub inverse: bool,
+}
+
+impl Style {
+    fn to_sgr(&self) -> String {
+        let mut params = vec!["0".to_string()];
+        if self.bold {
+            params.push("1".to_string());
+        }
+        if self.dim {
+            params.push("2".to_string());
+        }
+        if self.italic {
+            params.push("3".to_string());
+        }
+        if self.underline {
+            params.push("4".to_string());
+        }
+        if self.inverse {
+            params.push("7".to_string());
+        }
+        if let Some(fg) = &self.fg {
+            params.push(fg.clone());
+        }
+        if let Some(bg) = &self.bg {
+            params.push(bg.clone());
+        }
+        format!("\x1b[{}m", params.join(";"))
+    }
+}
+
+static COLORS: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+// a color name or #rrggbb, as the SGR parameter for the foreground or background
+pub fn parse_color(color: &str, background: bool) -> Option<String> {
+    let base = if background { 40 } else { 30 };
+    if let Some(index) = COLORS.iter().position(|name| *name == color) {
+        return Some((base + index).to_string());
+    }
+    if color == "gray" || color == "grey" {
+        return Some((base + 60).to_string());
+    }
+    if let Some(name) = color.strip_suffix("Bright") {
+        if let Some(index) = COLORS.iter().position(|c| *c == name) {
+            return Some((base + 60 + index).to_string());
+        }
+    }
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(format!(
+        "{};2;{};{};{}",
+        base + 8,
+        channel(0)?,
+        channel(2)?,
+        channel(4)?
+    ))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            ch: ' ',
+            style: Style::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Copy, PartialEq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        Rect {
+            x,
+            y,
+            width: (right - x).max(0),
+            height: (bottom - y).max(0),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Buffer {
+    pub width: i32,
+    pub height: i32,
+    cells: Vec<Cell>,
+}
+
+impl Buffer {
+    pub fn new(width: i32, height: i32) -> Self {
+        Buffer {
+            width,
+            height,
+            cells: vec![Cell::default(); (width * height).max(0) as usize],
+        }
+    }
+
+    pub fn area(&self) -> Rect {
+        Rect {
+            x: 0,
+            y: 0,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> &Cell {
+        &self.cells[(y * self.width + x) as usize]
+    }
+
+    // cells outside of clip are left alone
+    pub fn set(&mut self, x: i32, y: i32, ch: char, style: &Style, clip: &Rect) {
+        if !clip.contains(x, y) || !self.area().contains(x, y) {
+            return;
+        }
+        let cell = &mut self.cells[(y * self.width + x) as usize];
+        cell.ch = ch;
+        cell.style = style.clone();
+    }
+
+    pub fn set_str(&mut self, x: i32, y: i32, content: &str, style: &Style, clip: &Rect) {
+        for (i, ch) in content.chars().enumerate() {
+            self.set(x + i as i32, y, ch, style, clip);
+        }
+    }
+
+    pub fn fill(&mut self, rect: &Rect, style: &Style, clip: &Rect) {
+        for y in rect.y..rect.y + rect.height {
+            for x in rect.x..rect.x + rect.width {
+                self.set(x, y, ' ', style, clip);
+            }
+        }
+    }
+
+    // the ANSI output that turns what prev shows into this buffer, only changed cells are written
+    pub fn diff(&self, prev: Option<&Buffer>) -> String {
+        let mut output = String::new();
+        let prev = match prev {
+            Some(prev) if prev.width == self.width && prev.height == self.height => Some(prev),
+            _ => {
+                output.push_str("\x1b[2J");
+                None
+            }
+        };
+
+        let mut current_style: Option<&Style> = None;
+        for y in 0..self.height {
+            let mut cursor_x: Option<i32> = None;
+            for x in 0..self.width {
+                let cell = self.get(x, y);
+                if prev.is_some_and(|prev| prev.get(x, y) == cell) {
+                    cursor_x = None;
+                    continue;
+                }
+                if cursor_x != Some(x) {
+                    write!(output, "\x1b[{};{}H", y + 1, x + 1).unwrap();
+                }
+                if current_style != Some(&cell.style) {
+                    output.push_str(&cell.style.to_sgr());
+                    current_style = Some(&cell.style);
+                }
+                output.push(cell.ch);
+                cursor_x = Some(x + 1);
+            }
+        }
+        if current_style.is_some() {
+            output.push_str("\x1b[0m");
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(rows: &[&str]) -> Buffer {
+        let mut buffer = Buffer::new(rows[0].chars().count() as i32, rows.len() as i32);
+        let area = buffer.area();
+        for (y, row) in rows.iter().enumerate() {
+            buffer.set_str(0, y as i32, row, &Style::default(), &area);
+        }
+        buffer
+    }
+
+    #[test]
+    fn clears_and_writes_everything_without_a_previous_buffer() {
+        assert_eq!(
+            buffer(&["ab", "cd"]).diff(None),
+            "\x1b[2J\x1b[1;1H\x1b[0mab\x1b[2;1Hcd\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn writes_only_the_changed_cells() {
+        let prev = buffer(&["abcd", "efgh"]);
+        let mut next = prev.clone();
+        let area = next.area();
+        let bold = Style {
+            bold: true,
+            ..Style::default()
+        };
+        next.set(1, 0, 'X', &bold, &area);
+        next.set(2, 0, 'Y', &bold, &area);
+        next.set(3, 1, 'Z', &Style::default(), &area);
+        assert_eq!(
+            next.diff(Some(&prev)),
+            "\x1b[1;2H\x1b[0;1mXY\x1b[2;4H\x1b[0mZ\x1b[0m"
+        );
+        assert_eq!(next.diff(Some(&next)), "");
+    }
+
+    #[test]
+    fn clears_when_the_size_changes() {
+        let prev = buffer(&["ab"]);
+        assert!(buffer(&["abc"]).diff(Some(&prev)).starts_with("\x1b[2J"));
+    }
+
+    #[test]
+    fn clips_cells_outside_of_the_clip() {
+        let mut buffer = Buffer::new(4, 1);
+        let clip = Rect {
+            x: 1,
+            y: 0,
+            width: 2,
+            height: 1,
+        };
+        buffer.set_str(0, 0, "abcd", &Style::default(), &clip);
+        assert_eq!(buffer.get(0, 0).ch, ' ');
+        assert_eq!(buffer.get(1, 0).ch, 'b');
+        assert_eq!(buffer.get(3, 0).ch, ' ');
+    }
+
+    #[test]
+    fn parses_colors() {
+        assert_eq!(parse_color("red", false).as_deref(), Some("31"));
+        assert_eq!(parse_color("blueBright", true).as_deref(), Some("104"));
+        assert_eq!(parse_color("gray", false).as_deref(), Some("90"));
+        assert_eq!(
+            parse_color("#ff8000", false).as_deref(),
+            Some("38;2;255;128;0")
+        );
+        assert_eq!(parse_color("#ff80", false), None);
+    }
+}
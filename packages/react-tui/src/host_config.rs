@@ -0,0 +1,121 @@
+use std::{any::Any, cell::RefCell, rc::Rc};
+
+use react_reconciler::{react_value::Value, HostConfig};
+use shared::warn;
+
+use crate::layout::paint_root;
+use crate::tui_node::{TuiNode, TuiNodeKind};
+
+pub struct TuiHostConfig;
+
+impl HostConfig for TuiHostConfig {
+    fn create_text_instance(&self, content: String) -> Rc<dyn Any> {
+        Rc::new(RefCell::new(TuiNode::new(TuiNodeKind::RawText(content))))
+    }
+
+    fn create_instance(&self, _type: String, _host_context: &str) -> Rc<dyn Any> {
+        let kind = match _type.as_str() {
+            "text" => TuiNodeKind::Text,
+            "box" => TuiNodeKind::Box,
+            _ => {
+                warn!(
+                    Host,
+                    "unknown tui element <{}>, rendering it as <box>", _type
+                );
+                TuiNodeKind::Box
+            }
+        };
+        Rc::new(RefCell::new(TuiNode::new(kind)))
+    }
+
+    fn get_root_host_context(&self, _container: Rc<dyn Any>) -> String {
+        "".to_string()
+    }
+
+    fn get_child_host_context(&self, parent_host_context: &str, _type: &str) -> String {
+        parent_host_context.to_string()
+    }
+
+    // terminal nodes are not exposed to js
+    fn get_public_instance(&self, _instance: Rc<dyn Any>) -> Value {
+        Value::null()
+    }
+
+    fn get_portal_container(&self, _container_info: &Value) -> Rc<dyn Any> {
+        panic!("react-tui does not support portals")
+    }
+
+    fn prepare_portal_mount(&self, _portal_container: Rc<dyn Any>, _parent_container: Rc<dyn Any>) {
+    }
+
+    fn append_initial_child(&self, parent: Rc<dyn Any>, child: Rc<dyn Any>) {
+        let parent = TuiNode::from_instance(&parent);
+        let child = TuiNode::from_instance(&child);
+        parent.borrow_mut().append_child(child);
+    }
+
+    fn finalize_initial_children(&self, instance: Rc<dyn Any>, _type: String, props: &Value) {
+        TuiNode::from_instance(&instance).borrow_mut().props = props.clone();
+    }
+
+    fn commit_update(
+        &self,
+        instance: Rc<dyn Any>,
+        _type: String,
+        _old_props: &Value,
+        new_props: &Value,
+    ) {
+        TuiNode::from_instance(&instance).borrow_mut().props = new_props.clone();
+    }
+
+    fn append_child_to_container(&self, child: Rc<dyn Any>, parent: Rc<dyn Any>) {
+        self.append_initial_child(parent, child)
+    }
+
+    fn insert_child_to_container(
+        &self,
+        child: Rc<dyn Any>,
+        container: Rc<dyn Any>,
+        before: Rc<dyn Any>,
+    ) {
+        let parent = TuiNode::from_instance(&container);
+        let child = TuiNode::from_instance(&child);
+        let before = TuiNode::from_instance(&before);
+        parent.borrow_mut().insert_before(child, &before);
+    }
+
+    fn remove_child(&self, child: Rc<dyn Any>, container: Rc<dyn Any>) {
+        let parent = TuiNode::from_instance(&container);
+        let child = TuiNode::from_instance(&child);
+        parent.borrow_mut().remove_child(&child);
+    }
+
+    // the whole screen is painted once per commit, only the cells that changed get written
+    fn reset_after_commit(&self, container: Rc<dyn Any>) {
+        paint_root(&TuiNode::from_instance(&container));
+    }
+
+    fn reset_text_content(&self, _instance: Rc<dyn Any>) {}
+
+    fn commit_text_update(&self, text_instance: Rc<dyn Any>, content: String) {
+        let text_instance = TuiNode::from_instance(&text_instance);
+        text_instance.borrow_mut().kind = TuiNodeKind::RawText(content);
+    }
+
+    fn hide_instance(&self, instance: Rc<dyn Any>) {
+        TuiNode::from_instance(&instance).borrow_mut().hidden = true;
+    }
+
+    fn unhide_instance(&self, instance: Rc<dyn Any>) {
+        TuiNode::from_instance(&instance).borrow_mut().hidden = false;
+    }
+
+    fn hide_text_instance(&self, text_instance: Rc<dyn Any>) {
+        self.hide_instance(text_instance);
+    }
+
+    fn unhide_text_instance(&self, text_instance: Rc<dyn Any>, content: String) {
+        self.commit_text_update(text_instance.clone(), content);
+        self.unhide_instance(text_instance);
+    }
+}
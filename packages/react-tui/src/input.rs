@@ -0,0 +1,156 @@
+use std::{cell::RefCell, rc::Rc};
+
+use react_reconciler::react_value::Value;
+use wasm_bindgen::prelude::*;
+use web_sys::js_sys::{Object, Reflect};
+
+use crate::tui_node::{TuiNode, TuiNodeKind};
+
+pub struct Key {
+    // "return", "up", "escape", ... or the character typed
+    pub name: String,
+    pub ctrl: bool,
+    pub shift: bool,
+}
+
+// what a chunk of raw mode stdin stands for, a chunk holds one key press
+pub fn parse_key(data: &str) -> Key {
+    let named = |name: &str| Key {
+        name: name.to_string(),
+        ctrl: false,
+        shift: false,
+    };
+    match data {
+        "\r" | "\n" => named("return"),
+        "\t" => named("tab"),
+        "\x1b[Z" => Key {
+            shift: true,
+            ..named("tab")
+        },
+        "\x7f" | "\x08" => named("backspace"),
+        "\x1b" => named("escape"),
+        "\x1b[A" | "\x1bOA" => named("up"),
+        "\x1b[B" | "\x1bOB" => named("down"),
+        "\x1b[C" | "\x1bOC" => named("right"),
+        "\x1b[D" | "\x1bOD" => named("left"),
+        "\x1b[H" | "\x1b[1~" => named("home"),
+        "\x1b[F" | "\x1b[4~" => named("end"),
+        "\x1b[3~" => named("delete"),
+        "\x1b[5~" => named("pageup"),
+        "\x1b[6~" => named("pagedown"),
+        _ => {
+            let mut chars = data.chars();
+            match (chars.next(), chars.next()) {
+                // ctrl+a is \x01 and so on
+                (Some(ch), None) if (ch as u32) >= 1 && (ch as u32) <= 26 => Key {
+                    name: char::from_u32(ch as u32 + 'a' as u32 - 1)
+                        .unwrap()
+                        .to_string(),
+                    ctrl: true,
+                    shift: false,
+                },
+                (Some(ch), None) => Key {
+                    name: ch.to_lowercase().to_string(),
+                    ctrl: false,
+                    shift: ch.is_uppercase(),
+                },
+                _ => named(""),
+            }
+        }
+    }
+}
+
+fn create_key_event(data: &str, key: &Key) -> JsValue {
+    let event = Object::new();
+    // the text typed, empty for keys that do not type anything
+    let input = if key.ctrl || data.starts_with('\x1b') || data.chars().all(char::is_control) {
+        ""
+    } else {
+        data
+    };
+    Reflect::set(&event, &"input".into(), &JsValue::from_str(input)).expect("input panic");
+    Reflect::set(&event, &"key".into(), &JsValue::from_str(&key.name)).expect("key panic");
+    Reflect::set(&event, &"ctrl".into(), &JsValue::from_bool(key.ctrl)).expect("ctrl panic");
+    Reflect::set(&event, &"shift".into(), &JsValue::from_bool(key.shift)).expect("shift panic");
+    event.into()
+}
+
+fn collect_listeners(node: &TuiNode, listeners: &mut Vec<Value>) {
+    if node.hidden {
+        return;
+    }
+    if let TuiNodeKind::Box | TuiNodeKind::Text = node.kind {
+        let listener = node.props.get("onKeyPress");
+        if listener.is_function() {
+            listeners.push(listener);
+        }
+    }
+    for child in node.children.iter() {
+        collect_listeners(&child.borrow(), listeners);
+    }
+}
+
+// calls every onKeyPress in tree order, updates they schedule are committed before returning
+pub fn dispatch_input(root: &Rc<RefCell<TuiNode>>, data: &str) -> Result<(), JsValue> {
+    let key = parse_key(data);
+    let event = Value::from(create_key_event(data, &key));
+    let mut listeners = vec![];
+    collect_listeners(&root.borrow(), &mut listeners);
+    if listeners.is_empty() {
+        return Ok(());
+    }
+
+    react_reconciler::flush_sync(|| -> Result<(), Value> {
+        for listener in listeners.iter() {
+            listener.call(std::slice::from_ref(&event))?;
+        }
+        Ok(())
+    })
+    .map_err(JsValue::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(data: &str) -> (String, bool, bool) {
+        let key = parse_key(data);
+        (key.name, key.ctrl, key.shift)
+    }
+
+    #[test]
+    fn parses_ctrl_letters() {
+        assert_eq!(key("\x01"), ("a".to_string(), true, false));
+        assert_eq!(key("\x03"), ("c".to_string(), true, false));
+        assert_eq!(key("\x1a"), ("z".to_string(), true, false));
+    }
+
+    #[test]
+    fn parses_escape_sequences() {
+        assert_eq!(key("\x1b[A").0, "up");
+        assert_eq!(key("\x1bOB").0, "down");
+        assert_eq!(key("\x1b[C").0, "right");
+        assert_eq!(key("\x1b[1~").0, "home");
+        assert_eq!(key("\x1b[3~").0, "delete");
+        assert_eq!(key("\x1b[6~").0, "pagedown");
+        assert_eq!(key("\x1b"), ("escape".to_string(), false, false));
+        // not a key this knows
+        assert_eq!(key("\x1b[99~").0, "");
+    }
+
+    #[test]
+    fn tells_tab_and_shift_tab_apart_from_ctrl_letters() {
+        // tab, return and backspace are ctrl+i, ctrl+m and ctrl+h on the wire
+        assert_eq!(key("\t"), ("tab".to_string(), false, false));
+        assert_eq!(key("\x1b[Z"), ("tab".to_string(), false, true));
+        assert_eq!(key("\r"), ("return".to_string(), false, false));
+        assert_eq!(key("\x08"), ("backspace".to_string(), false, false));
+    }
+
+    #[test]
+    fn parses_typed_characters() {
+        assert_eq!(key("q"), ("q".to_string(), false, false));
+        assert_eq!(key("Q"), ("q".to_string(), false, true));
+        assert_eq!(key("é"), ("é".to_string(), false, false));
+    }
+}
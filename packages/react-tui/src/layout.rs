@@ -0,0 +1,539 @@
+use std::{cell::RefCell, rc::Rc};
+
+use react_reconciler::react_value::Value;
+use wasm_bindgen::JsValue;
+
+use crate::buffer::{parse_color, Buffer, Rect, Style};
+use crate::tui_node::{TuiNode, TuiNodeKind};
+
+fn get_number(props: &Value, name: &str) -> Option<i32> {
+    props.get(name).as_f64().map(|value| value as i32)
+}
+
+fn get_string(props: &Value, name: &str) -> Option<String> {
+    props.get(name).as_string()
+}
+
+// truthy as in javascript, e.g. <text bold> or <text bold={1}>
+fn get_flag(props: &Value, name: &str) -> bool {
+    let value = props.get(name);
+    if let Some(flag) = value.as_bool() {
+        return flag;
+    }
+    if let Some(number) = value.as_f64() {
+        return number != 0.0 && !number.is_nan();
+    }
+    if let Some(string) = value.as_string() {
+        return !string.is_empty();
+    }
+    !value.is_nullish()
+}
+
+#[derive(PartialEq)]
+enum Justify {
+    Start,
+    Center,
+    End,
+    SpaceBetween,
+}
+
+#[derive(PartialEq)]
+enum Align {
+    Stretch,
+    Start,
+    Center,
+    End,
+}
+
+// top left, top right, bottom left, bottom right, horizontal, vertical
+type BorderChars = [char; 6];
+
+struct BoxStyle {
+    row: bool,
+    width: Option<i32>,
+    height: Option<i32>,
+    padding_x: i32,
+    padding_y: i32,
+    gap: i32,
+    border: Option<BorderChars>,
+    border_style: Style,
+    background: Option<Style>,
+    justify: Justify,
+    align: Align,
+}
+
+impl BoxStyle {
+    fn from_props(props: &Value) -> Self {
+        let padding = get_number(props, "padding").unwrap_or(0);
+        let border = match get_string(props, "borderStyle").as_deref() {
+            Some("single") => Some(['┌', '┐', '└', '┘', '─', '│']),
+            Some("double") => Some(['╔', '╗', '╚', '╝', '═', '║']),
+            Some("round") => Some(['╭', '╮', '╰', '╯', '─', '│']),
+            Some("bold") => Some(['┏', '┓', '┗', '┛', '━', '┃']),
+            _ => None,
+        };
+        let background = get_string(props, "backgroundColor").map(|color| Style {
+            bg: parse_color(&color, true),
+            ..Style::default()
+        });
+        BoxStyle {
+            // like flexbox, children are laid out in a row unless asked otherwise
+            row: get_string(props, "flexDirection").as_deref() != Some("column"),
+            width: get_number(props, "width"),
+            height: get_number(props, "height"),
+            padding_x: get_number(props, "paddingX").unwrap_or(padding),
+            padding_y: get_number(props, "paddingY").unwrap_or(padding),
+            gap: get_number(props, "gap").unwrap_or(0),
+            border,
+            border_style: Style {
+                fg: get_string(props, "borderColor").and_then(|color| parse_color(&color, false)),
+                bg: background.as_ref().and_then(|style| style.bg.clone()),
+                ..Style::default()
+            },
+            background,
+            justify: match get_string(props, "justifyContent").as_deref() {
+                Some("center") => Justify::Center,
+                Some("flex-end") => Justify::End,
+                Some("space-between") => Justify::SpaceBetween,
+                _ => Justify::Start,
+            },
+            align: match get_string(props, "alignItems").as_deref() {
+                Some("flex-start") => Align::Start,
+                Some("center") => Align::Center,
+                Some("flex-end") => Align::End,
+                _ => Align::Stretch,
+            },
+        }
+    }
+
+    fn root() -> Self {
+        BoxStyle::from_props(&Value::null()).column()
+    }
+
+    fn column(mut self) -> Self {
+        self.row = false;
+        self
+    }
+
+    // the space padding and border take up on each axis
+    fn inset(&self) -> (i32, i32) {
+        let border = if self.border.is_some() { 2 } else { 0 };
+        (self.padding_x * 2 + border, self.padding_y * 2 + border)
+    }
+}
+
+fn text_style(props: &Value, parent: &Style) -> Style {
+    let mut style = parent.clone();
+    if let Some(color) = get_string(props, "color") {
+        style.fg = parse_color(&color, false);
+    }
+    if let Some(color) = get_string(props, "backgroundColor") {
+        style.bg = parse_color(&color, true);
+    }
+    style.bold |= get_flag(props, "bold");
+    style.dim |= get_flag(props, "dimColor");
+    style.italic |= get_flag(props, "italic");
+    style.underline |= get_flag(props, "underline");
+    style.inverse |= get_flag(props, "inverse");
+    style
+}
+
+fn measure_text(content: &str) -> (i32, i32) {
+    if content.is_empty() {
+        return (0, 0);
+    }
+    let lines: Vec<&str> = content.split('\n').collect();
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count() as i32)
+        .max()
+        .unwrap_or(0);
+    (width, lines.len() as i32)
+}
+
+fn visible_children(node: &TuiNode) -> Vec<Rc<RefCell<TuiNode>>> {
+    node.children
+        .iter()
+        .filter(|child| !child.borrow().hidden)
+        .cloned()
+        .collect()
+}
+
+// the size a node needs, before its parent hands out the space left over
+fn measure(node: &TuiNode) -> (i32, i32) {
+    if node.hidden {
+        return (0, 0);
+    }
+    let style = match &node.kind {
+        TuiNodeKind::RawText(_) | TuiNodeKind::Text => return measure_text(&node.text_content()),
+        TuiNodeKind::Root(_) => BoxStyle::root(),
+        TuiNodeKind::Box => BoxStyle::from_props(&node.props),
+    };
+
+    let children = visible_children(node);
+    let mut main = 0;
+    let mut cross = 0;
+    for child in children.iter() {
+        let (width, height) = measure(&child.borrow());
+        let (child_main, child_cross) = if style.row {
+            (width, height)
+        } else {
+            (height, width)
+        };
+        main += child_main;
+        cross = cross.max(child_cross);
+    }
+    if children.len() > 1 {
+        main += style.gap * (children.len() as i32 - 1);
+    }
+
+    let (inset_x, inset_y) = style.inset();
+    let (width, height) = if style.row {
+        (main + inset_x, cross + inset_y)
+    } else {
+        (cross + inset_x, main + inset_y)
+    };
+    (style.width.unwrap_or(width), style.height.unwrap_or(height))
+}
+
+fn paint_border(
+    buffer: &mut Buffer,
+    rect: &Rect,
+    border: &BorderChars,
+    style: &Style,
+    clip: &Rect,
+) {
+    if rect.width < 2 || rect.height < 2 {
+        return;
+    }
+    let right = rect.x + rect.width - 1;
+    let bottom = rect.y + rect.height - 1;
+    buffer.set(rect.x, rect.y, border[0], style, clip);
+    buffer.set(right, rect.y, border[1], style, clip);
+    buffer.set(rect.x, bottom, border[2], style, clip);
+    buffer.set(right, bottom, border[3], style, clip);
+    for x in rect.x + 1..right {
+        buffer.set(x, rect.y, border[4], style, clip);
+        buffer.set(x, bottom, border[4], style, clip);
+    }
+    for y in rect.y + 1..bottom {
+        buffer.set(rect.x, y, border[5], style, clip);
+        buffer.set(right, y, border[5], style, clip);
+    }
+}
+
+fn paint_box(node: &TuiNode, style: &BoxStyle, rect: &Rect, buffer: &mut Buffer, clip: &Rect) {
+    if let Some(background) = &style.background {
+        buffer.fill(rect, background, clip);
+    }
+    if let Some(border) = &style.border {
+        paint_border(buffer, rect, border, &style.border_style, clip);
+    }
+
+    let (inset_x, inset_y) = style.inset();
+    let inner = Rect {
+        x: rect.x + inset_x / 2,
+        y: rect.y + inset_y / 2,
+        width: (rect.width - inset_x).max(0),
+        height: (rect.height - inset_y).max(0),
+    };
+    let inner_clip = clip.intersect(&inner);
+
+    let children = visible_children(node);
+    if children.is_empty() {
+        return;
+    }
+    let (available_main, available_cross) = if style.row {
+        (inner.width, inner.height)
+    } else {
+        (inner.height, inner.width)
+    };
+
+    let sizes: Vec<(i32, i32)> = children
+        .iter()
+        .map(|child| {
+            let (width, height) = measure(&child.borrow());
+            if style.row {
+                (width, height)
+            } else {
+                (height, width)
+            }
+        })
+        .collect();
+    let mut mains: Vec<i32> = sizes.iter().map(|(main, _)| *main).collect();
+    let gaps = style.gap * (children.len() as i32 - 1);
+    let mut free = available_main - mains.iter().sum::<i32>() - gaps;
+
+    // free space goes to the children that grow first, then to justifyContent
+    let grows: Vec<i32> = children
+        .iter()
+        .map(|child| {
+            get_number(&child.borrow().props, "flexGrow")
+                .unwrap_or(0)
+                .max(0)
+        })
+        .collect();
+    let total_grow: i32 = grows.iter().sum();
+    if free > 0 && total_grow > 0 {
+        let mut remaining = free;
+        let last_grower = grows.iter().rposition(|grow| *grow > 0).unwrap();
+        for (i, grow) in grows.iter().enumerate() {
+            let extra = if i == last_grower {
+                remaining
+            } else {
+                free * grow / total_grow
+            };
+            mains[i] += extra;
+            remaining -= extra;
+        }
+        free = 0;
+    }
+
+    let free = free.max(0);
+    let (mut offset, between) = match style.justify {
+        Justify::Start => (0, 0),
+        Justify::Center => (free / 2, 0),
+        Justify::End => (free, 0),
+        Justify::SpaceBetween if children.len() > 1 => (0, free / (children.len() as i32 - 1)),
+        Justify::SpaceBetween => (0, 0),
+    };
+
+    for (i, child) in children.iter().enumerate() {
+        let child_cross = match style.align {
+            Align::Stretch => available_cross,
+            _ => sizes[i].1.min(available_cross),
+        };
+        let cross_offset = match style.align {
+            Align::Stretch | Align::Start => 0,
+            Align::Center => (available_cross - child_cross) / 2,
+            Align::End => available_cross - child_cross,
+        };
+        let child_rect = if style.row {
+            Rect {
+                x: inner.x + offset,
+                y: inner.y + cross_offset,
+                width: mains[i],
+                height: child_cross,
+            }
+        } else {
+            Rect {
+                x: inner.x + cross_offset,
+                y: inner.y + offset,
+                width: child_cross,
+                height: mains[i],
+            }
+        };
+        paint(&child.borrow(), &child_rect, buffer, &inner_clip);
+        offset += mains[i] + style.gap + between;
+    }
+}
+
+// writes the text of node at the cursor, nested <text> add to the style of their parent
+fn paint_text(
+    node: &TuiNode,
+    origin_x: i32,
+    cursor: &mut (i32, i32),
+    style: &Style,
+    buffer: &mut Buffer,
+    clip: &Rect,
+) {
+    match &node.kind {
+        TuiNodeKind::RawText(content) => {
+            for (i, line) in content.split('\n').enumerate() {
+                if i > 0 {
+                    cursor.0 = origin_x;
+                    cursor.1 += 1;
+                }
+                buffer.set_str(cursor.0, cursor.1, line, style, clip);
+                cursor.0 += line.chars().count() as i32;
+            }
+        }
+        _ => {
+            let style = text_style(&node.props, style);
+            for child in visible_children(node) {
+                paint_text(&child.borrow(), origin_x, cursor, &style, buffer, clip);
+            }
+        }
+    }
+}
+
+fn paint(node: &TuiNode, rect: &Rect, buffer: &mut Buffer, clip: &Rect) {
+    if node.hidden {
+        return;
+    }
+    match &node.kind {
+        TuiNodeKind::Root(_) => paint_box(node, &BoxStyle::root(), rect, buffer, clip),
+        TuiNodeKind::Box => paint_box(node, &BoxStyle::from_props(&node.props), rect, buffer, clip),
+        TuiNodeKind::Text | TuiNodeKind::RawText(_) => {
+            let clip = clip.intersect(rect);
+            let mut cursor = (rect.x, rect.y);
+            paint_text(node, rect.x, &mut cursor, &Style::default(), buffer, &clip);
+        }
+    }
+}
+
+// paints the tree of a root and writes what changed since the last paint to its terminal
+pub fn paint_root(root: &Rc<RefCell<TuiNode>>) {
+    let (columns, rows) = match &root.borrow().kind {
+        TuiNodeKind::Root(terminal) => (terminal.columns, terminal.rows),
+        _ => panic!("paint_root expects a root"),
+    };
+    let mut buffer = Buffer::new(columns, rows);
+    let area = buffer.area();
+    paint(&root.borrow(), &area, &mut buffer, &area);
+
+    let mut root = root.borrow_mut();
+    if let TuiNodeKind::Root(terminal) = &mut root.kind {
+        let output = buffer.diff(terminal.front.as_ref());
+        if !output.is_empty() {
+            if let Err(e) = terminal
+                .write
+                .call1(&JsValue::null(), &JsValue::from_str(&output))
+            {
+                shared::error!(Host, "write to terminal failed {:?}", e);
+            }
+        }
+        terminal.front = Some(buffer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+
+    use react_reconciler::react_value::ReactValue;
+
+    use super::*;
+
+    // the props of a node as the reconciler hands them over
+    #[derive(Debug)]
+    struct Props(Vec<(&'static str, Value)>);
+
+    impl ReactValue for Props {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn is_same(&self, other: &Value) -> bool {
+            other
+                .downcast_ref::<Props>()
+                .is_some_and(|other| std::ptr::eq(self, other))
+        }
+
+        fn get(&self, name: &str) -> Value {
+            self.0
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.clone())
+                .unwrap_or(Value::undefined())
+        }
+    }
+
+    fn node(
+        kind: TuiNodeKind,
+        props: Vec<(&'static str, Value)>,
+        children: Vec<Rc<RefCell<TuiNode>>>,
+    ) -> Rc<RefCell<TuiNode>> {
+        let mut node = TuiNode::new(kind);
+        node.props = Value::new(Props(props));
+        node.children = children;
+        Rc::new(RefCell::new(node))
+    }
+
+    fn text(content: &str) -> Rc<RefCell<TuiNode>> {
+        let raw_text = node(TuiNodeKind::RawText(content.to_string()), vec![], vec![]);
+        node(TuiNodeKind::Text, vec![], vec![raw_text])
+    }
+
+    fn number(value: f64) -> Value {
+        Value::from_f64(value)
+    }
+
+    fn string(value: &str) -> Value {
+        Value::from_string(value)
+    }
+
+    // paints node into a columns x rows screen, as lines of text
+    fn paint_lines(node: Rc<RefCell<TuiNode>>, columns: i32, rows: i32) -> Vec<String> {
+        let mut buffer = Buffer::new(columns, rows);
+        let area = buffer.area();
+        let root = BoxStyle::root();
+        let screen = TuiNode {
+            children: vec![node],
+            ..TuiNode::new(TuiNodeKind::Box)
+        };
+        paint_box(&screen, &root, &area, &mut buffer, &area);
+        (0..rows)
+            .map(|y| (0..columns).map(|x| buffer.get(x, y).ch).collect())
+            .collect()
+    }
+
+    #[test]
+    fn lays_out_a_row_with_gap_and_justify() {
+        let row = |props: Vec<(&'static str, Value)>| {
+            node(TuiNodeKind::Box, props, vec![text("ab"), text("cd")])
+        };
+        assert_eq!(
+            paint_lines(row(vec![("gap", number(1.0))]), 10, 1),
+            ["ab cd     "]
+        );
+        assert_eq!(
+            paint_lines(
+                row(vec![("justifyContent", string("space-between"))]),
+                10,
+                1
+            ),
+            ["ab      cd"]
+        );
+        assert_eq!(
+            paint_lines(row(vec![("justifyContent", string("flex-end"))]), 10, 1),
+            ["      abcd"]
+        );
+    }
+
+    #[test]
+    fn gives_the_free_space_to_growing_children() {
+        let grower = node(
+            TuiNodeKind::Box,
+            vec![("flexGrow", number(1.0))],
+            vec![text("a")],
+        );
+        let row = node(TuiNodeKind::Box, vec![], vec![grower, text("z")]);
+        assert_eq!(paint_lines(row, 6, 1), ["a    z"]);
+    }
+
+    #[test]
+    fn lays_out_a_column_inside_border_and_padding() {
+        let column = node(
+            TuiNodeKind::Box,
+            vec![
+                ("flexDirection", string("column")),
+                ("borderStyle", string("single")),
+                ("paddingX", number(1.0)),
+            ],
+            vec![text("hi"), text("yo")],
+        );
+        assert_eq!(
+            paint_lines(column, 6, 4),
+            ["┌────┐", "│ hi │", "│ yo │", "└────┘"]
+        );
+    }
+
+    #[test]
+    fn aligns_children_on_the_cross_axis() {
+        let row = node(
+            TuiNodeKind::Box,
+            vec![("height", number(3.0)), ("alignItems", string("center"))],
+            vec![text("x")],
+        );
+        assert_eq!(paint_lines(row, 3, 3), ["   ", "x  ", "   "]);
+    }
+
+    #[test]
+    fn leaves_hidden_children_out() {
+        let hidden = text("gone");
+        hidden.borrow_mut().hidden = true;
+        let row = node(TuiNodeKind::Box, vec![], vec![hidden, text("kept")]);
+        assert_eq!(paint_lines(row, 6, 1), ["kept  "]);
+    }
+}
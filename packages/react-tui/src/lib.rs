@@ -0,0 +1,44 @@
+use std::{cell::RefCell, rc::Rc};
+
+use host_config::TuiHostConfig;
+use react_reconciler::Reconciler;
+use renderer::Renderer;
+use shared::derive_from_js_value;
+use tui_node::{Terminal, TuiNode, TuiNodeKind};
+use wasm_bindgen::prelude::*;
+use web_sys::js_sys::Function;
+
+mod buffer;
+mod host_config;
+mod input;
+mod layout;
+mod renderer;
+mod tui_node;
+
+// options is { columns, rows, write }, write gets the ANSI output of every commit,
+// e.g. createRoot({ columns: 80, rows: 24, write: (s) => process.stdout.write(s) })
+#[wasm_bindgen(js_name = createRoot)]
+pub fn create_root(options: &JsValue) -> Renderer {
+    set_panic_hook();
+    let columns = derive_from_js_value(options, "columns")
+        .as_f64()
+        .unwrap_or(80.0) as i32;
+    let rows = derive_from_js_value(options, "rows")
+        .as_f64()
+        .unwrap_or(24.0) as i32;
+    let write = match derive_from_js_value(options, "write").dyn_into::<Function>() {
+        Ok(write) => write,
+        Err(_) => panic!("write should be a function"),
+    };
+    let container = Rc::new(RefCell::new(TuiNode::new(TuiNodeKind::Root(Terminal {
+        columns,
+        rows,
+        write,
+        front: None,
+    }))));
+    let reconciler = Reconciler::new(Rc::new(TuiHostConfig));
+    let root = reconciler.create_container(container.clone());
+    Renderer::new(root, reconciler, container)
+}
+
+shared::renderer_exports!(Renderer, react_devtools::connect);
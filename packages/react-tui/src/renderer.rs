@@ -0,0 +1,60 @@
+use std::{cell::RefCell, rc::Rc};
+
+use react_reconciler::{fiber::FiberRootNode, react_value::Value, Reconciler};
+use wasm_bindgen::prelude::*;
+
+use crate::input::dispatch_input;
+use crate::layout::paint_root;
+use crate::tui_node::{TuiNode, TuiNodeKind};
+
+#[wasm_bindgen]
+pub struct Renderer {
+    // getSnapshot of renderer_exports! reads it
+    pub(crate) root: Rc<RefCell<FiberRootNode>>,
+    reconciler: Reconciler,
+    container: Rc<RefCell<TuiNode>>,
+}
+
+impl Renderer {
+    pub fn new(
+        root: Rc<RefCell<FiberRootNode>>,
+        reconciler: Reconciler,
+        container: Rc<RefCell<TuiNode>>,
+    ) -> Self {
+        Self {
+            root,
+            reconciler,
+            container,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Renderer {
+    pub fn render(&self, element: &JsValue) -> JsValue {
+        self.reconciler
+            .update_container(element.into(), self.root.clone())
+            .to_js()
+    }
+
+    pub fn unmount(&self) {
+        self.reconciler
+            .update_container(Value::null(), self.root.clone());
+    }
+
+    // feeds a chunk of stdin (in raw mode) to the onKeyPress handlers
+    #[wasm_bindgen(js_name = dispatchInput)]
+    pub fn dispatch_input(&self, data: &str) -> Result<(), JsValue> {
+        dispatch_input(&self.container, data)
+    }
+
+    // the terminal changed size, the next paint redraws everything
+    pub fn resize(&self, columns: i32, rows: i32) {
+        if let TuiNodeKind::Root(terminal) = &mut self.container.borrow_mut().kind {
+            terminal.columns = columns;
+            terminal.rows = rows;
+            terminal.front = None;
+        }
+        paint_root(&self.container);
+    }
+}
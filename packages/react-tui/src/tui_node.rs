@@ -0,0 +1,78 @@
+use std::{any::Any, cell::RefCell, rc::Rc};
+
+use react_reconciler::react_value::Value;
+use web_sys::js_sys::Function;
+
+use crate::buffer::Buffer;
+
+// the screen a root renders to, the last painted buffer is kept to diff against
+pub struct Terminal {
+    pub columns: i32,
+    pub rows: i32,
+    pub write: Function,
+    pub front: Option<Buffer>,
+}
+
+pub enum TuiNodeKind {
+    Root(Terminal),
+    // <box>, lays out its children in a row or a column
+    Box,
+    // <text>, styles the text nodes inside of it
+    Text,
+    RawText(String),
+}
+
+pub struct TuiNode {
+    pub kind: TuiNodeKind,
+    pub props: Value,
+    pub hidden: bool,
+    pub children: Vec<Rc<RefCell<TuiNode>>>,
+}
+
+impl TuiNode {
+    pub fn new(kind: TuiNodeKind) -> Self {
+        TuiNode {
+            kind,
+            props: Value::null(),
+            hidden: false,
+            children: vec![],
+        }
+    }
+
+    pub fn from_instance(instance: &Rc<dyn Any>) -> Rc<RefCell<TuiNode>> {
+        instance
+            .clone()
+            .downcast::<RefCell<TuiNode>>()
+            .expect("instance should be TuiNode")
+    }
+
+    pub fn append_child(&mut self, child: Rc<RefCell<TuiNode>>) {
+        self.remove_child(&child);
+        self.children.push(child);
+    }
+
+    pub fn insert_before(&mut self, child: Rc<RefCell<TuiNode>>, before: &Rc<RefCell<TuiNode>>) {
+        self.remove_child(&child);
+        match self.children.iter().position(|c| Rc::ptr_eq(c, before)) {
+            Some(index) => self.children.insert(index, child),
+            None => self.children.push(child),
+        }
+    }
+
+    pub fn remove_child(&mut self, child: &Rc<RefCell<TuiNode>>) {
+        self.children.retain(|c| !Rc::ptr_eq(c, child));
+    }
+
+    // the text of a <text> or a text node, children of nested <text> are joined in
+    pub fn text_content(&self) -> String {
+        match &self.kind {
+            TuiNodeKind::RawText(content) => content.clone(),
+            _ => self
+                .children
+                .iter()
+                .filter(|child| !child.borrow().hidden)
+                .map(|child| child.borrow().text_content())
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use std::ptr::addr_of_mut;
+
+use wasm_bindgen::prelude::*;
+
+pub struct CurrentBatchConfig {
+    pub transition: u32,
+}
+
+pub static mut REACT_CURRENT_BATCH_CONFIG: CurrentBatchConfig =
+    CurrentBatchConfig { transition: 0 };
+
+#[wasm_bindgen(js_name = requestCurrentTransition)]
+pub fn request_current_transition() -> bool {
+    unsafe { REACT_CURRENT_BATCH_CONFIG.transition > 0 }
+}
+
+// the updates scheduled while scope runs are transitions
+pub fn with_transition<T>(scope: impl FnOnce() -> T) -> T {
+    let config = addr_of_mut!(REACT_CURRENT_BATCH_CONFIG);
+    unsafe { (*config).transition += 1 };
+    let result = scope();
+    unsafe { (*config).transition -= 1 };
+    result
+}
@@ -1,34 +1,86 @@
-use js_sys::{Function, Reflect};
+use std::ptr::addr_of_mut;
+use std::rc::Rc;
+
+use js_sys::{Object, Reflect};
+use shared::react_value::Value;
 use wasm_bindgen::prelude::*;
 
+// The hooks of the component being rendered, each one a function value. The reconciler sets
+// rust closures when it is linked into the same module, a separate react module gets javascript
+// functions through updateDispatcher.
 #[derive(Debug)]
 pub struct Dispatcher {
-    pub use_state: Function,
+    pub use_state: Value,
+    pub _use: Value,
+    pub use_transition: Value,
+    pub use_deferred_value: Value,
+    pub use_sync_external_store: Value,
+    pub use_id: Value,
+    pub use_imperative_handle: Value,
+    pub use_insertion_effect: Value,
+    pub use_memo: Value,
 }
 
-unsafe impl Send for Dispatcher {}
-
 impl Dispatcher {
-    pub fn new(use_state: Function) -> Self {
-        Self { use_state }
+    fn hooks(&self) -> [(&'static str, &Value); 9] {
+        [
+            ("use_state", &self.use_state),
+            ("use", &self._use),
+            ("use_transition", &self.use_transition),
+            ("use_deferred_value", &self.use_deferred_value),
+            ("use_sync_external_store", &self.use_sync_external_store),
+            ("use_id", &self.use_id),
+            ("use_imperative_handle", &self.use_imperative_handle),
+            ("use_insertion_effect", &self.use_insertion_effect),
+            ("use_memo", &self.use_memo),
+        ]
+    }
+
+    // what updateDispatcher of another react module expects
+    pub fn to_js(&self) -> JsValue {
+        let dispatcher = Object::new();
+        for (name, hook) in self.hooks() {
+            Reflect::set(&dispatcher, &name.into(), &hook.to_js())
+                .expect("a property of a plain object can always be set");
+        }
+        dispatcher.into()
     }
 }
 
 pub struct CurrentDispatcher {
-    pub current: Option<Box<Dispatcher>>,
+    pub current: Option<Rc<Dispatcher>>,
 }
 
 pub static mut CURRENT_DISPATCHER: CurrentDispatcher = CurrentDispatcher { current: None };
 
-fn derive_function_from_js_value(js_value: &JsValue, name: &str) -> Function {
+// None once a component finished rendering
+pub fn set_current_dispatcher(dispatcher: Option<Rc<Dispatcher>>) {
+    unsafe { (*addr_of_mut!(CURRENT_DISPATCHER)).current = dispatcher };
+}
+
+fn derive_hook_from_js_value(js_value: &JsValue, name: &str) -> Value {
     Reflect::get(js_value, &name.into())
-        .unwrap()
-        .dyn_into::<Function>()
-        .unwrap()
+        .unwrap_or(JsValue::undefined())
+        .into()
 }
 
 #[wasm_bindgen(js_name = updateDispatcher)]
-pub unsafe fn update_dispatcher(args: &JsValue) {
-    let use_state = derive_function_from_js_value(args, "use_state");
-    CURRENT_DISPATCHER.current = Some(Box::new(Dispatcher::new(use_state)))
+pub fn update_dispatcher(args: &JsValue) {
+    // null once a component finished rendering
+    if args.is_null() || args.is_undefined() {
+        set_current_dispatcher(None);
+        return;
+    }
+    let dispatcher = Dispatcher {
+        use_state: derive_hook_from_js_value(args, "use_state"),
+        _use: derive_hook_from_js_value(args, "use"),
+        use_transition: derive_hook_from_js_value(args, "use_transition"),
+        use_deferred_value: derive_hook_from_js_value(args, "use_deferred_value"),
+        use_sync_external_store: derive_hook_from_js_value(args, "use_sync_external_store"),
+        use_id: derive_hook_from_js_value(args, "use_id"),
+        use_imperative_handle: derive_hook_from_js_value(args, "use_imperative_handle"),
+        use_insertion_effect: derive_hook_from_js_value(args, "use_insertion_effect"),
+        use_memo: derive_hook_from_js_value(args, "use_memo"),
+    };
+    set_current_dispatcher(Some(Rc::new(dispatcher)));
 }
@@ -0,0 +1,52 @@
+use std::ptr::addr_of;
+use std::rc::Rc;
+
+use js_sys::{Array, Object, Reflect};
+use shared::react_value::Value;
+use shared::{error, __DEV__};
+use wasm_bindgen::prelude::*;
+
+use crate::current_dispatcher::{Dispatcher, CURRENT_DISPATCHER};
+
+// the dispatcher of the component being rendered, there is none outside of a render
+pub fn resolve_dispatcher() -> Result<Rc<Dispatcher>, Value> {
+    match unsafe { (*addr_of!(CURRENT_DISPATCHER.current)).clone() } {
+        Some(dispatcher) => Ok(dispatcher),
+        None => {
+            let message = "Invalid hook call. Hooks can only be called inside of the body of a function component. This could happen for one of the following reasons:\n1. You might have mismatching versions of React and the renderer (such as React DOM)\n2. You might be breaking the Rules of Hooks\n3. You might have more than one copy of React in the same app\nSee https://react.dev/link/invalid-hook-call for tips about how to debug and fix this problem.";
+            if __DEV__ {
+                error!(Hooks, "{}", message);
+            }
+            Err(Value::from_string(message))
+        }
+    }
+}
+
+// _store.validated tells the reconciler whether a missing key was checked already,
+// it is not enumerable so it stays out of the props and snapshots
+pub fn define_element_store(react_element: &Object) {
+    if !__DEV__ {
+        return;
+    }
+    let store = Object::new();
+    let _ = Reflect::set(&store, &"validated".into(), &JsValue::from_bool(false));
+    let descriptor = Object::new();
+    let _ = Reflect::set(&descriptor, &"value".into(), &store);
+    let _ = Reflect::set(&descriptor, &"writable".into(), &JsValue::from_bool(true));
+    Object::define_property(react_element, &"_store".into(), &descriptor);
+}
+
+// children written out in jsx have a fixed position, they need no key
+pub fn validate_static_children(children: &JsValue) {
+    if !__DEV__ {
+        return;
+    }
+    if let Some(children) = children.dyn_ref::<Array>() {
+        for child in children.iter() {
+            let store = Reflect::get(&child, &"_store".into()).unwrap_or(JsValue::undefined());
+            if store.is_object() {
+                let _ = Reflect::set(&store, &"validated".into(), &JsValue::from_bool(true));
+            }
+        }
+    }
+}
@@ -0,0 +1,469 @@
+use std::cell::{OnceCell, RefCell};
+use std::ptr::addr_of_mut;
+
+use js_sys::{Object, Reflect, TypeError};
+use shared::react_value::{ReactValue, Value};
+use shared::{derive_from_js_value, __DEV__, REACT_ELEMENT_TYPE, REACT_RUST_COMPONENT_TYPE};
+use wasm_bindgen::prelude::*;
+
+use crate::jsx_dev;
+
+// a component written in rust, hooks are called like from js, e.g. react::hooks::use_state
+pub type RenderFn = fn(&Props) -> Result<Element, Value>;
+
+// ID tells components apart like the function of a js component, two components must not share one
+pub trait Component: 'static {
+    const ID: &'static str;
+    fn render(props: &Props) -> Result<Element, Value>;
+}
+
+// the element type of a rust component, fibers see the same type each render when the id is the same
+#[derive(Debug)]
+struct RustComponent {
+    id: &'static str,
+    render: RenderFn,
+}
+
+impl ReactValue for RustComponent {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn is_same(&self, other: &Value) -> bool {
+        other
+            .downcast_ref::<RustComponent>()
+            .is_some_and(|other| other.id == self.id)
+    }
+
+    fn get(&self, name: &str) -> Value {
+        match name {
+            "displayName" => Value::from_string(self.id),
+            _ => Value::undefined(),
+        }
+    }
+
+    fn is_function(&self) -> bool {
+        true
+    }
+
+    fn call(&self, args: &[Value]) -> Result<Value, Value> {
+        (self.render)(&Props::from_value(Value::arg(args, 0))).map(Value::from)
+    }
+
+    fn to_js(&self) -> JsValue {
+        component_type(self.id, self.render)
+    }
+}
+
+// the type javascript sees for a rust component, created once per id so js renders it with the
+// same type each time as well
+static mut RUST_COMPONENTS: Vec<(&'static str, RenderFn, JsValue)> = vec![];
+
+pub fn component_type(id: &'static str, render: RenderFn) -> JsValue {
+    let rust_components = unsafe { &mut *addr_of_mut!(RUST_COMPONENTS) };
+    if let Some((_, _, _type)) = rust_components.iter().find(|(other, _, _)| *other == id) {
+        return _type.clone();
+    }
+
+    let _type = Object::new();
+    Reflect::set(
+        &_type,
+        &"$$typeof".into(),
+        &JsValue::from_str(REACT_RUST_COMPONENT_TYPE),
+    )
+    .expect("$$typeof panic");
+    Reflect::set(&_type, &"_id".into(), &JsValue::from_str(id)).expect("_id panic");
+    Reflect::set(&_type, &"displayName".into(), &JsValue::from_str(id)).expect("displayName panic");
+    rust_components.push((id, render, _type.clone().into()));
+    _type.into()
+}
+
+pub fn is_rust_component(_type: &JsValue) -> bool {
+    _type.is_object() && derive_from_js_value(_type, "$$typeof") == REACT_RUST_COMPONENT_TYPE
+}
+
+// called by the reconciler in place of calling a js function component
+pub fn render_rust_component(_type: &JsValue, props: &JsValue) -> Result<JsValue, JsValue> {
+    let id = derive_from_js_value(_type, "_id")
+        .as_string()
+        .ok_or_else(|| TypeError::new("A rust component type has no _id"))?;
+    let rust_components = unsafe { &*addr_of_mut!(RUST_COMPONENTS) };
+    let render = rust_components
+        .iter()
+        .find(|(other, _, _)| *other == id)
+        .map(|(_, render, _)| *render)
+        .ok_or_else(|| TypeError::new(&format!("There is no rust component {}", id)))?;
+    render(&Props::from_js(props.clone()))
+        .map(|element| Value::from(element).to_js())
+        .map_err(|error| error.to_js())
+}
+
+// wraps a rust closure so it can be passed as a prop, e.g. an event handler
+pub fn callback(f: impl Fn(Value) + 'static) -> Value {
+    Value::function(move |args| {
+        f(Value::arg(args, 0));
+        Ok(Value::undefined())
+    })
+}
+
+// the props of an element built in rust and the _store of its dev checks
+#[derive(Debug, Default)]
+struct NativeObject(RefCell<Vec<(String, Value)>>);
+
+impl ReactValue for NativeObject {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn is_same(&self, other: &Value) -> bool {
+        other
+            .downcast_ref::<NativeObject>()
+            .is_some_and(|other| std::ptr::eq(self, other))
+    }
+
+    fn keys(&self) -> Option<Vec<String>> {
+        Some(self.0.borrow().iter().map(|(key, _)| key.clone()).collect())
+    }
+
+    fn get(&self, name: &str) -> Value {
+        self.0
+            .borrow()
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+            .unwrap_or_else(Value::undefined)
+    }
+
+    fn set(&self, name: &str, value: Value) -> Result<(), Value> {
+        let mut properties = self.0.borrow_mut();
+        match properties.iter_mut().find(|(key, _)| key == name) {
+            Some((_, old_value)) => *old_value = value,
+            None => properties.push((name.to_string(), value)),
+        }
+        Ok(())
+    }
+
+    fn to_js(&self) -> JsValue {
+        let object = Object::new();
+        for (key, value) in self.0.borrow().iter() {
+            Reflect::set(&object, &key.into(), &value.to_js()).expect("props panic");
+        }
+        object.into()
+    }
+}
+
+// an element built in rust, the reconciler reads it like the objects of jsx
+#[derive(Debug)]
+struct NativeElement {
+    _type: Value,
+    key: Value,
+    props: Value,
+    store: Value,
+    static_children: bool,
+    // created once, like the props of a fiber javascript sees the same object every time
+    js: OnceCell<JsValue>,
+}
+
+impl ReactValue for NativeElement {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn is_same(&self, other: &Value) -> bool {
+        other
+            .downcast_ref::<NativeElement>()
+            .is_some_and(|other| std::ptr::eq(self, other))
+    }
+
+    fn keys(&self) -> Option<Vec<String>> {
+        Some(
+            ["$$typeof", "type", "key", "ref", "props"]
+                .map(String::from)
+                .to_vec(),
+        )
+    }
+
+    fn get(&self, name: &str) -> Value {
+        match name {
+            "$$typeof" => Value::from_string(REACT_ELEMENT_TYPE),
+            "type" => self._type.clone(),
+            "key" => self.key.clone(),
+            "ref" => Value::null(),
+            "props" => self.props.clone(),
+            "_store" if __DEV__ => self.store.clone(),
+            _ => Value::undefined(),
+        }
+    }
+
+    fn to_js(&self) -> JsValue {
+        self.js
+            .get_or_init(|| {
+                jsx_dev(
+                    &self._type.to_js(),
+                    &self.props.to_js(),
+                    &self.key.to_js(),
+                    &JsValue::from_bool(self.static_children),
+                )
+            })
+            .clone()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Props {
+    inner: Value,
+}
+
+impl Default for Props {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Props {
+    pub fn new() -> Self {
+        Props {
+            inner: Value::new(NativeObject::default()),
+        }
+    }
+
+    pub fn from_value(inner: Value) -> Self {
+        Props { inner }
+    }
+
+    pub fn from_js(inner: JsValue) -> Self {
+        Props {
+            inner: inner.into(),
+        }
+    }
+
+    pub fn as_value(&self) -> &Value {
+        &self.inner
+    }
+
+    pub fn get(&self, key: &str) -> Value {
+        self.inner.get(key)
+    }
+
+    pub fn get_string(&self, key: &str) -> Option<String> {
+        self.get(key).as_string()
+    }
+
+    pub fn get_f64(&self, key: &str) -> Option<f64> {
+        self.get(key).as_f64()
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key).as_bool()
+    }
+
+    pub fn children(&self) -> Element {
+        Element::Node(self.get("children"))
+    }
+}
+
+// a host or component element that takes props, a key and children, see Element::host
+#[derive(Debug)]
+pub struct ElementBuilder {
+    _type: Value,
+    props: NativeObject,
+    key: Value,
+    children: Vec<Element>,
+}
+
+impl ElementBuilder {
+    fn new(_type: Value) -> Self {
+        ElementBuilder {
+            _type,
+            props: NativeObject::default(),
+            key: Value::null(),
+            children: vec![],
+        }
+    }
+
+    pub fn prop(self, key: &str, value: impl Into<Value>) -> Self {
+        let _ = self.props.set(key, value.into());
+        self
+    }
+
+    pub fn on(self, key: &str, handler: impl Fn(Value) + 'static) -> Self {
+        self.prop(key, callback(handler))
+    }
+
+    // like jsx, a number key is the same as its string
+    pub fn key(mut self, key: impl Into<Value>) -> Self {
+        let key = key.into();
+        self.key = match key.as_f64() {
+            Some(number) => Value::from_string(number.to_string()),
+            None => key,
+        };
+        self
+    }
+
+    pub fn child(mut self, child: impl Into<Element>) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
+    pub fn children(self, children: Vec<Element>) -> Self {
+        children
+            .into_iter()
+            .fold(self, |element, child| element.child(child))
+    }
+}
+
+#[derive(Debug)]
+pub enum Element {
+    // a host element or a component, see Element::host and Element::component
+    Typed(ElementBuilder),
+    Text(String),
+    Fragment(Vec<Element>),
+    // anything else that renders, e.g. props.children or a jsx element built on the js side
+    Node(Value),
+    Empty,
+}
+
+impl Element {
+    pub fn host(tag: &str) -> ElementBuilder {
+        ElementBuilder::new(Value::from_string(tag))
+    }
+
+    pub fn component(id: &'static str, render: RenderFn) -> ElementBuilder {
+        ElementBuilder::new(Value::new(RustComponent { id, render }))
+    }
+
+    pub fn of<C: Component>() -> ElementBuilder {
+        Element::component(C::ID, C::render)
+    }
+
+    pub fn js_component(_type: &JsValue) -> ElementBuilder {
+        ElementBuilder::new(_type.into())
+    }
+
+    pub fn text(content: &str) -> Self {
+        Element::Text(content.to_string())
+    }
+}
+
+impl From<ElementBuilder> for Value {
+    fn from(element: ElementBuilder) -> Self {
+        let ElementBuilder {
+            _type,
+            props,
+            key,
+            children,
+        } = element;
+        // like jsx, a single child is not wrapped in a list, the ones added with child have a fixed
+        // position so they need no key
+        let static_children = children.len() > 1;
+        let children = match children.len() {
+            0 => None,
+            1 => children.into_iter().next().map(Value::from),
+            _ => {
+                let children: Vec<Value> = children.into_iter().map(Value::from).collect();
+                if __DEV__ {
+                    for child in &children {
+                        let _ = child.get("_store").set("validated", Value::from_bool(true));
+                    }
+                }
+                Some(Value::from_list(children))
+            }
+        };
+        if let Some(children) = children {
+            let _ = props.set("children", children);
+        }
+        let store = NativeObject::default();
+        let _ = store.set("validated", Value::from_bool(false));
+        Value::new(NativeElement {
+            _type,
+            key,
+            props: Value::new(props),
+            store: Value::new(store),
+            static_children,
+            js: OnceCell::new(),
+        })
+    }
+}
+
+impl From<Element> for Value {
+    fn from(element: Element) -> Self {
+        match element {
+            Element::Typed(element) => element.into(),
+            Element::Text(content) => Value::from_string(content),
+            Element::Fragment(children) => {
+                Value::from_list(children.into_iter().map(Value::from).collect())
+            }
+            Element::Node(value) => value,
+            Element::Empty => Value::null(),
+        }
+    }
+}
+
+// for js, e.g. the result of a rust component rendered by a separate react-dom module
+impl From<Element> for JsValue {
+    fn from(element: Element) -> Self {
+        Value::from(element).to_js()
+    }
+}
+
+impl From<ElementBuilder> for Element {
+    fn from(element: ElementBuilder) -> Self {
+        Element::Typed(element)
+    }
+}
+
+impl From<&str> for Element {
+    fn from(content: &str) -> Self {
+        Element::text(content)
+    }
+}
+
+impl From<String> for Element {
+    fn from(content: String) -> Self {
+        Element::Text(content)
+    }
+}
+
+impl From<Value> for Element {
+    fn from(value: Value) -> Self {
+        Element::Node(value)
+    }
+}
+
+impl From<JsValue> for Element {
+    fn from(value: JsValue) -> Self {
+        Element::Node(value.into())
+    }
+}
+
+impl From<Vec<Element>> for Element {
+    fn from(children: Vec<Element>) -> Self {
+        Element::Fragment(children)
+    }
+}
+
+impl From<Option<Element>> for Element {
+    fn from(element: Option<Element>) -> Self {
+        element.unwrap_or(Element::Empty)
+    }
+}
+
+// rsx!(div { className: "row" } [ "text", rsx!(@counter { initial: 1 }) ])
+// builds an Element, @ marks a rust component whose path is its id, props and children are optional
+#[macro_export]
+macro_rules! rsx {
+    (@$component:path $({ $($prop:ident : $value:expr),* $(,)? })? $([ $($child:expr),* $(,)? ])?) => {
+        $crate::element::Element::from(
+            $crate::element::Element::component(stringify!($component), $component)
+                $($(.prop(stringify!($prop), $value))*)?
+                $($(.child($child))*)?
+        )
+    };
+    ($tag:ident $({ $($prop:ident : $value:expr),* $(,)? })? $([ $($child:expr),* $(,)? ])?) => {
+        $crate::element::Element::from(
+            $crate::element::Element::host(stringify!($tag))
+                $($(.prop(stringify!($prop), $value))*)?
+                $($(.child($child))*)?
+        )
+    };
+}
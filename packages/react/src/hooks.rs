@@ -0,0 +1,74 @@
+// The hooks for components written in rust. They take and return Values like the dispatcher, so
+// they work without javascript, the exports in lib.rs wrap them for js components.
+use shared::react_value::Value;
+
+use crate::current_batch_config::with_transition;
+use crate::dev_warnings::resolve_dispatcher;
+
+// e.g. [state, setState] of useState
+fn pair(list: Value) -> Result<(Value, Value), Value> {
+    match list.as_list().as_deref() {
+        Some([first, second, ..]) => Ok((first.clone(), second.clone())),
+        _ => Err(Value::from_string(format!(
+            "Expected a pair of values, got {:?}",
+            list
+        ))),
+    }
+}
+
+pub fn use_state(initial_state: Value) -> Result<(Value, Value), Value> {
+    pair(resolve_dispatcher()?.use_state.call(&[initial_state])?)
+}
+
+pub fn _use(usable: Value) -> Result<Value, Value> {
+    resolve_dispatcher()?._use.call(&[usable])
+}
+
+pub fn use_transition() -> Result<(Value, Value), Value> {
+    pair(resolve_dispatcher()?.use_transition.call(&[])?)
+}
+
+pub fn use_deferred_value(value: Value, initial_value: Value) -> Result<Value, Value> {
+    resolve_dispatcher()?
+        .use_deferred_value
+        .call(&[value, initial_value])
+}
+
+// get_server_snapshot is optional like in React, the dispatcher gets undefined without one
+pub fn use_sync_external_store(
+    subscribe: Value,
+    get_snapshot: Value,
+    get_server_snapshot: Option<Value>,
+) -> Result<Value, Value> {
+    resolve_dispatcher()?.use_sync_external_store.call(&[
+        subscribe,
+        get_snapshot,
+        get_server_snapshot.unwrap_or_else(Value::undefined),
+    ])
+}
+
+pub fn use_id() -> Result<Value, Value> {
+    resolve_dispatcher()?.use_id.call(&[])
+}
+
+pub fn use_imperative_handle(_ref: Value, create: Value, deps: Value) -> Result<(), Value> {
+    resolve_dispatcher()?
+        .use_imperative_handle
+        .call(&[_ref, create, deps])
+        .map(|_| ())
+}
+
+pub fn use_insertion_effect(create: Value, deps: Value) -> Result<(), Value> {
+    resolve_dispatcher()?
+        .use_insertion_effect
+        .call(&[create, deps])
+        .map(|_| ())
+}
+
+pub fn use_memo(create: Value, deps: Value) -> Result<Value, Value> {
+    resolve_dispatcher()?.use_memo.call(&[create, deps])
+}
+
+pub fn start_transition<T>(scope: impl FnOnce() -> T) -> T {
+    with_transition(scope)
+}
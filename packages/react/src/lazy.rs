@@ -0,0 +1,82 @@
+use js_sys::{Function, Object, Reflect};
+use shared::{derive_from_js_value, REACT_LAZY_TYPE};
+use wasm_bindgen::prelude::*;
+
+const UNINITIALIZED: i32 = -1;
+const PENDING: i32 = 0;
+const RESOLVED: i32 = 1;
+const REJECTED: i32 = 2;
+
+fn set_payload(payload: &JsValue, status: i32, result: &JsValue) {
+    Reflect::set(payload, &"_status".into(), &status.into()).expect("_status panic");
+    Reflect::set(payload, &"_result".into(), result).expect("_result panic");
+}
+
+fn is_settling(payload: &JsValue) -> bool {
+    let status = derive_from_js_value(payload, "_status");
+    status == UNINITIALIZED || status == PENDING
+}
+
+fn lazy_initializer(payload: JsValue) -> Result<JsValue, JsValue> {
+    if derive_from_js_value(&payload, "_status") == UNINITIALIZED {
+        let ctor = derive_from_js_value(&payload, "_result").dyn_into::<Function>()?;
+        let thenable = ctor.call0(&JsValue::null())?;
+
+        let fulfilled_payload = payload.clone();
+        let on_fulfilled = Closure::wrap(Box::new(move |module_object: JsValue| {
+            if is_settling(&fulfilled_payload) {
+                set_payload(&fulfilled_payload, RESOLVED, &module_object);
+            }
+        }) as Box<dyn Fn(JsValue)>);
+        let rejected_payload = payload.clone();
+        let on_rejected = Closure::wrap(Box::new(move |error: JsValue| {
+            if is_settling(&rejected_payload) {
+                set_payload(&rejected_payload, REJECTED, &error);
+            }
+        }) as Box<dyn Fn(JsValue)>);
+        derive_from_js_value(&thenable, "then")
+            .dyn_into::<Function>()?
+            .call2(
+                &thenable,
+                on_fulfilled.as_ref().unchecked_ref::<Function>(),
+                on_rejected.as_ref().unchecked_ref::<Function>(),
+            )?;
+        on_fulfilled.forget();
+        on_rejected.forget();
+
+        if derive_from_js_value(&payload, "_status") == UNINITIALIZED {
+            set_payload(&payload, PENDING, &thenable);
+        }
+    }
+
+    let result = derive_from_js_value(&payload, "_result");
+    if derive_from_js_value(&payload, "_status") == RESOLVED {
+        Ok(derive_from_js_value(&result, "default"))
+    } else {
+        // the pending thenable suspends the render, a rejection is rethrown as is
+        Err(result)
+    }
+}
+
+#[wasm_bindgen]
+pub fn lazy(ctor: &JsValue) -> JsValue {
+    let payload = Object::new();
+    set_payload(&payload, UNINITIALIZED, ctor);
+
+    let closure = Closure::wrap(
+        Box::new(lazy_initializer) as Box<dyn Fn(JsValue) -> Result<JsValue, JsValue>>
+    );
+    let init = closure.as_ref().unchecked_ref::<Function>().clone();
+    closure.forget();
+
+    let lazy_type = Object::new();
+    Reflect::set(
+        &lazy_type,
+        &"$$typeof".into(),
+        &JsValue::from_str(REACT_LAZY_TYPE),
+    )
+    .expect("$$typeof panic");
+    Reflect::set(&lazy_type, &"_payload".into(), &payload).expect("_payload panic");
+    Reflect::set(&lazy_type, &"_init".into(), &init).expect("_init panic");
+    lazy_type.into()
+}
@@ -1,9 +1,15 @@
-use current_dispatcher::CURRENT_DISPATCHER;
-use js_sys::{Object, Reflect, JSON};
-use shared::REACT_ELEMENT_TYPE;
+use dev_warnings::{define_element_store, validate_static_children};
+use js_sys::{Array, Function, Object, Reflect, JSON};
+use shared::react_value::Value;
+use shared::{REACT_CONTEXT_TYPE, REACT_ELEMENT_TYPE, REACT_FORWARD_REF_TYPE, REACT_PROVIDER_TYPE};
 use wasm_bindgen::prelude::*;
 
+pub mod current_batch_config;
 pub mod current_dispatcher;
+pub mod dev_warnings;
+pub mod element;
+pub mod hooks;
+pub mod lazy;
 
 fn resolve_key(value: &JsValue) -> JsValue {
     if value.is_undefined() {
@@ -24,7 +30,12 @@ fn resolve_ref(val: &JsValue) -> JsValue {
 }
 
 #[wasm_bindgen(js_name = jsxDEV)]
-pub fn jsx_dev(_type: &JsValue, config: &JsValue, key: &JsValue) -> JsValue {
+pub fn jsx_dev(
+    _type: &JsValue,
+    config: &JsValue,
+    key: &JsValue,
+    is_static_children: &JsValue,
+) -> JsValue {
     let react_element = Object::new();
     let mut _ref = JsValue::null();
     let mut key = resolve_key(key);
@@ -42,22 +53,20 @@ pub fn jsx_dev(_type: &JsValue, config: &JsValue, key: &JsValue) -> JsValue {
             let val = Reflect::get(conf, &prop);
             match prop.as_string() {
                 None => {}
-                Some(k) => {
-                    if k == "ref" && val.is_ok() {
-                        _ref = resolve_ref(&val.unwrap());
-                    } else if k == "key" && val.is_ok() {
-                        key = resolve_key(&val.unwrap());
-                    } else {
+                Some(k) => match val {
+                    Ok(val) if k == "ref" => _ref = resolve_ref(&val),
+                    Ok(val) if k == "key" => key = resolve_key(&val),
+                    val => {
                         Reflect::set(&props, &JsValue::from(k), &val.unwrap())
                             .expect("props panic");
                     }
-                }
+                },
             }
         }
         Reflect::set(&react_element, &"props".into(), &props).expect("props panic");
     } else {
         if config.is_object() {
-            Reflect::set(&react_element, &"props".into(), &config).expect("props panic");
+            Reflect::set(&react_element, &"props".into(), config).expect("props panic");
         } else {
             Reflect::set(&react_element, &"props".into(), &props).expect("props panic");
         }
@@ -65,20 +74,24 @@ pub fn jsx_dev(_type: &JsValue, config: &JsValue, key: &JsValue) -> JsValue {
 
     Reflect::set(&react_element, &"ref".into(), &_ref).expect("ref panic");
     Reflect::set(&react_element, &"key".into(), &key).expect("key panic");
+    define_element_store(&react_element);
+    if is_static_children.is_truthy() {
+        validate_static_children(&Reflect::get(config, &"children".into()).unwrap_or_default());
+    }
 
     react_element.into()
 }
 
 #[wasm_bindgen(js_name = createElement)]
 pub fn create_element(_type: &JsValue, config: &JsValue, key: &JsValue) -> JsValue {
-    jsx_dev(_type, config, key)
+    jsx_dev(_type, config, key, &JsValue::undefined())
 }
 
 #[wasm_bindgen(js_name = isValidElement)]
 pub fn is_valid_element(object: &JsValue) -> bool {
     object.is_object()
         && !object.is_null()
-        && Reflect::get(&object, &"$$typeof".into())
+        && Reflect::get(object, &"$$typeof".into())
             .unwrap_or("".into())
             .as_string()
             .unwrap_or("".into())
@@ -86,8 +99,125 @@ pub fn is_valid_element(object: &JsValue) -> bool {
             == REACT_ELEMENT_TYPE
 }
 
+// the hooks throw errors, the message of the invalid hook call is a string value
+fn to_js_error(error: Value) -> JsValue {
+    match error.as_string() {
+        Some(message) => js_sys::Error::new(&message).into(),
+        None => error.to_js(),
+    }
+}
+
+fn to_js_pair((first, second): (Value, Value)) -> JsValue {
+    Array::of2(&first.to_js(), &second.to_js()).into()
+}
+
 #[wasm_bindgen(js_name = useState)]
-pub unsafe fn use_state(initial_state: &JsValue) -> Result<JsValue, JsValue> {
-    let use_state = &CURRENT_DISPATCHER.current.as_ref().unwrap().use_state;
-    use_state.call1(&JsValue::null(), initial_state)
+pub fn use_state(initial_state: &JsValue) -> Result<JsValue, JsValue> {
+    hooks::use_state(initial_state.into())
+        .map(to_js_pair)
+        .map_err(to_js_error)
+}
+
+#[wasm_bindgen(js_name = use)]
+pub fn _use(usable: &JsValue) -> Result<JsValue, JsValue> {
+    hooks::_use(usable.into())
+        .map(|value| value.to_js())
+        .map_err(to_js_error)
+}
+
+#[wasm_bindgen(js_name = useTransition)]
+pub fn use_transition() -> Result<JsValue, JsValue> {
+    hooks::use_transition().map(to_js_pair).map_err(to_js_error)
+}
+
+#[wasm_bindgen(js_name = useDeferredValue)]
+pub fn use_deferred_value(value: &JsValue, initial_value: &JsValue) -> Result<JsValue, JsValue> {
+    hooks::use_deferred_value(value.into(), initial_value.into())
+        .map(|value| value.to_js())
+        .map_err(to_js_error)
+}
+
+#[wasm_bindgen(js_name = useSyncExternalStore)]
+pub fn use_sync_external_store(
+    subscribe: &JsValue,
+    get_snapshot: &JsValue,
+    get_server_snapshot: Option<Function>,
+) -> Result<JsValue, JsValue> {
+    hooks::use_sync_external_store(
+        subscribe.into(),
+        get_snapshot.into(),
+        get_server_snapshot.map(|function| JsValue::from(function).into()),
+    )
+    .map(|value| value.to_js())
+    .map_err(to_js_error)
+}
+
+#[wasm_bindgen(js_name = useId)]
+pub fn use_id() -> Result<JsValue, JsValue> {
+    hooks::use_id()
+        .map(|value| value.to_js())
+        .map_err(to_js_error)
+}
+
+#[wasm_bindgen(js_name = useImperativeHandle)]
+pub fn use_imperative_handle(
+    _ref: &JsValue,
+    create: &JsValue,
+    deps: &JsValue,
+) -> Result<(), JsValue> {
+    hooks::use_imperative_handle(_ref.into(), create.into(), deps.into()).map_err(to_js_error)
+}
+
+#[wasm_bindgen(js_name = useInsertionEffect)]
+pub fn use_insertion_effect(create: &JsValue, deps: &JsValue) -> Result<(), JsValue> {
+    hooks::use_insertion_effect(create.into(), deps.into()).map_err(to_js_error)
+}
+
+#[wasm_bindgen(js_name = useMemo)]
+pub fn use_memo(create: &JsValue, deps: &JsValue) -> Result<JsValue, JsValue> {
+    hooks::use_memo(create.into(), deps.into())
+        .map(|value| value.to_js())
+        .map_err(to_js_error)
+}
+
+#[wasm_bindgen(js_name = startTransition)]
+pub fn start_transition(scope: &Function) -> Result<(), JsValue> {
+    hooks::start_transition(|| scope.call0(&JsValue::null())).map(|_| ())
+}
+
+#[wasm_bindgen(js_name = createContext)]
+pub fn create_context(default_value: &JsValue) -> JsValue {
+    let context = Object::new();
+    Reflect::set(
+        &context,
+        &"$$typeof".into(),
+        &JsValue::from_str(REACT_CONTEXT_TYPE),
+    )
+    .expect("$$typeof panic");
+    Reflect::set(&context, &"_currentValue".into(), default_value).expect("_currentValue panic");
+
+    let provider = Object::new();
+    Reflect::set(
+        &provider,
+        &"$$typeof".into(),
+        &JsValue::from_str(REACT_PROVIDER_TYPE),
+    )
+    .expect("$$typeof panic");
+    Reflect::set(&provider, &"_context".into(), &context).expect("_context panic");
+    Reflect::set(&context, &"Provider".into(), &provider).expect("Provider panic");
+
+    context.into()
+}
+
+#[wasm_bindgen(js_name = forwardRef)]
+pub fn forward_ref(render: &JsValue) -> JsValue {
+    let element_type = Object::new();
+    Reflect::set(
+        &element_type,
+        &"$$typeof".into(),
+        &JsValue::from_str(REACT_FORWARD_REF_TYPE),
+    )
+    .expect("$$typeof panic");
+    Reflect::set(&element_type, &"render".into(), render).expect("render panic");
+    element_type.into()
 }
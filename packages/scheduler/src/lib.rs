@@ -1,15 +1,177 @@
-mod utils;
+use std::ptr::{addr_of, addr_of_mut};
 
-use utils::set_panic_hook;
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern "C" {
-    fn alert(s: &str);
+    fn setTimeout(handler: &JsValue, timeout: i32) -> i32;
 }
 
-#[wasm_bindgen]
-pub fn greet() {
-    set_panic_hook();
-    alert("Hello, scheduler!");
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    ImmediatePriority = 1,
+    UserBlockingPriority = 2,
+    NormalPriority = 3,
+    LowPriority = 4,
+    IdlePriority = 5,
+}
+
+// Returns true when the callback has more work and wants to be called again.
+pub type Callback = Box<dyn FnMut(bool) -> bool>;
+
+struct Task {
+    id: u32,
+    callback: Callback,
+    expiration_time: f64,
+}
+
+const FRAME_YIELD_MS: f64 = 5.0;
+
+static mut TASK_QUEUE: Vec<Task> = vec![];
+static mut TASK_ID_COUNTER: u32 = 1;
+static mut CURRENT_TASK_ID: Option<u32> = None;
+static mut IS_CURRENT_TASK_CANCELED: bool = false;
+static mut IS_HOST_CALLBACK_SCHEDULED: bool = false;
+static mut IS_PERFORMING_WORK: bool = false;
+static mut START_TIME: f64 = -1.0;
+
+fn get_priority_timeout(priority_level: Priority) -> f64 {
+    match priority_level {
+        Priority::ImmediatePriority => -1.0,
+        Priority::UserBlockingPriority => 250.0,
+        Priority::NormalPriority => 5000.0,
+        Priority::LowPriority => 10000.0,
+        Priority::IdlePriority => 1073741823.0,
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn unstable_now() -> f64 {
+    js_sys::Date::now()
+}
+
+// outside of javascript, e.g. in native tests
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unstable_now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |duration| duration.as_secs_f64() * 1000.0)
+}
+
+pub fn unstable_should_yield_to_host() -> bool {
+    unsafe { unstable_now() - START_TIME >= FRAME_YIELD_MS }
+}
+
+pub fn unstable_schedule_callback(priority_level: Priority, callback: Callback) -> u32 {
+    let current_time = unstable_now();
+    unsafe {
+        let id = TASK_ID_COUNTER;
+        TASK_ID_COUNTER += 1;
+        (*addr_of_mut!(TASK_QUEUE)).push(Task {
+            id,
+            callback,
+            expiration_time: current_time + get_priority_timeout(priority_level),
+        });
+
+        if !IS_HOST_CALLBACK_SCHEDULED && !IS_PERFORMING_WORK {
+            IS_HOST_CALLBACK_SCHEDULED = true;
+            schedule_perform_work_until_deadline();
+        }
+        id
+    }
+}
+
+pub fn unstable_cancel_callback(id: u32) {
+    unsafe {
+        if CURRENT_TASK_ID == Some(id) {
+            IS_CURRENT_TASK_CANCELED = true;
+        } else {
+            (*addr_of_mut!(TASK_QUEUE)).retain(|task| task.id != id);
+        }
+    }
+}
+
+fn peek_index() -> Option<usize> {
+    let task_queue = unsafe { &*addr_of!(TASK_QUEUE) };
+    let mut index: Option<usize> = None;
+    for (i, task) in task_queue.iter().enumerate() {
+        let is_earlier = match index {
+            None => true,
+            Some(j) => {
+                let other = &task_queue[j];
+                task.expiration_time < other.expiration_time
+                    || (task.expiration_time == other.expiration_time && task.id < other.id)
+            }
+        };
+        if is_earlier {
+            index = Some(i);
+        }
+    }
+    index
+}
+
+fn work_loop(initial_time: f64) -> bool {
+    let mut current_time = initial_time;
+    unsafe {
+        while let Some(index) = peek_index() {
+            let task_queue = &*addr_of!(TASK_QUEUE);
+            if task_queue[index].expiration_time > current_time && unstable_should_yield_to_host() {
+                break;
+            }
+
+            let mut task = (*addr_of_mut!(TASK_QUEUE)).remove(index);
+            CURRENT_TASK_ID = Some(task.id);
+            IS_CURRENT_TASK_CANCELED = false;
+            let did_user_callback_timeout = task.expiration_time <= current_time;
+            let has_continuation = (task.callback)(did_user_callback_timeout);
+            CURRENT_TASK_ID = None;
+            current_time = unstable_now();
+
+            if has_continuation && !IS_CURRENT_TASK_CANCELED {
+                // give the host a chance to paint before continuing
+                (*addr_of_mut!(TASK_QUEUE)).push(task);
+                return true;
+            }
+        }
+        !(*addr_of!(TASK_QUEUE)).is_empty()
+    }
+}
+
+fn flush_work(initial_time: f64) -> bool {
+    unsafe {
+        IS_PERFORMING_WORK = true;
+        let has_more_work = work_loop(initial_time);
+        IS_PERFORMING_WORK = false;
+        has_more_work
+    }
+}
+
+fn perform_work_until_deadline() {
+    unsafe {
+        START_TIME = unstable_now();
+        if flush_work(START_TIME) {
+            schedule_perform_work_until_deadline();
+        } else {
+            IS_HOST_CALLBACK_SCHEDULED = false;
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn schedule_perform_work_until_deadline() {
+    let handler = Closure::once_into_js(perform_work_until_deadline);
+    setTimeout(&handler, 0);
+}
+
+// there is no event loop to post to, unstable_flush_all runs the queue instead
+#[cfg(not(target_arch = "wasm32"))]
+fn schedule_perform_work_until_deadline() {}
+
+// runs every queued task, including the ones they schedule, before returning
+pub fn unstable_flush_all() {
+    while unsafe { IS_HOST_CALLBACK_SCHEDULED } {
+        perform_work_until_deadline();
+    }
 }
@@ -1,21 +1,83 @@
 use web_sys::js_sys::Reflect;
 use web_sys::wasm_bindgen::JsValue;
 
+pub mod logger;
+pub mod react_value;
+
 pub static REACT_ELEMENT_TYPE: &str = "react.element";
+pub static REACT_SUSPENSE_TYPE: &str = "react.suspense";
+pub static REACT_CONTEXT_TYPE: &str = "react.context";
+pub static REACT_PROVIDER_TYPE: &str = "react.provider";
+pub static REACT_LAZY_TYPE: &str = "react.lazy";
+pub static REACT_FORWARD_REF_TYPE: &str = "react.forward_ref";
+pub static REACT_PORTAL_TYPE: &str = "react.portal";
+pub static REACT_STRICT_MODE_TYPE: &str = "react.strict_mode";
+pub static REACT_PROFILER_TYPE: &str = "react.profiler";
+// the type of a component written in rust, see react::element
+pub static REACT_RUST_COMPONENT_TYPE: &str = "react.rust_component";
 
-#[macro_export]
-macro_rules! log {
-    ( $( $t:tt )* ) => {
-        web_sys::console::log_1(&format!( $( $t )* ).into());
-    }
-}
+// development only behaviour, e.g. what StrictMode checks, is left out of release builds
+pub const __DEV__: bool = cfg!(debug_assertions);
 
 pub fn derive_from_js_value(js_value: &JsValue, str: &str) -> JsValue {
-    match Reflect::get(&js_value, &JsValue::from_str(str)) {
+    match Reflect::get(js_value, &JsValue::from_str(str)) {
         Ok(v) => v,
         Err(_) => {
-            log!("derive {} from {:?} error", str, js_value);
+            debug!(Render, "derive {} from {:?} error", str, js_value);
             JsValue::undefined()
         }
     }
 }
+
+// what every renderer exports besides createRoot, expanded in the renderer's crate so the
+// exports end up in its module and console_error_panic_hook is that crate's feature. $renderer is
+// what createRoot returns, its root field is the FiberRootNode, $connect_dev_tools is e.g.
+// react_devtools::connect, shared does not depend on the devtools itself
+#[macro_export]
+macro_rules! renderer_exports {
+    ($renderer:ident, $connect_dev_tools:path) => {
+        #[wasm_bindgen::prelude::wasm_bindgen]
+        impl $renderer {
+            // the committed fiber tree as plain objects, ready for JSON.stringify
+            #[wasm_bindgen(js_name = getSnapshot)]
+            pub fn get_snapshot(&self) -> wasm_bindgen::JsValue {
+                react_reconciler::fiber_snapshot::snapshot_root(&self.root)
+            }
+        }
+
+        pub fn set_panic_hook() {
+            // When the `console_error_panic_hook` feature is enabled, we can call the
+            // `set_panic_hook` function at least once during initialization, and then
+            // we will get better error messages if our code ever panics.
+            //
+            // For more details see
+            // https://github.com/rustwasm/console_error_panic_hook#readme
+            #[cfg(feature = "console_error_panic_hook")]
+            console_error_panic_hook::set_once();
+        }
+
+        // runs the callback with sync priority, the resulting render is committed before returning
+        #[wasm_bindgen::prelude::wasm_bindgen(js_name = flushSync)]
+        pub fn flush_sync(
+            callback: &web_sys::js_sys::Function,
+        ) -> Result<wasm_bindgen::JsValue, wasm_bindgen::JsValue> {
+            react_reconciler::flush_sync(|| callback.call0(&wasm_bindgen::JsValue::null()))
+        }
+
+        // sends the fiber trees to a devtools frontend over transport, { send, listen }
+        #[wasm_bindgen::prelude::wasm_bindgen(js_name = connectDevTools)]
+        pub fn connect_dev_tools(
+            transport: &wasm_bindgen::JsValue,
+        ) -> Result<wasm_bindgen::JsValue, wasm_bindgen::JsValue> {
+            $connect_dev_tools(transport).map(wasm_bindgen::JsValue::from)
+        }
+
+        // what the packages log in development, options is { level, categories, sink }
+        #[wasm_bindgen::prelude::wasm_bindgen(js_name = configureLogging)]
+        pub fn configure_logging(
+            options: &wasm_bindgen::JsValue,
+        ) -> Result<(), wasm_bindgen::JsValue> {
+            $crate::logger::configure_logging(options)
+        }
+    };
+}
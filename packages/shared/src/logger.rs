@@ -0,0 +1,322 @@
+use std::ptr::addr_of;
+
+use web_sys::js_sys::{Array, Function, Object, Reflect};
+use web_sys::wasm_bindgen::{JsCast, JsValue};
+
+// Leveled logging for the packages. trace!, debug! and info! only exist in debug builds with
+// the logging feature, otherwise they expand to nothing, so hot paths can log freely. warn! and
+// error! are kept in every build, they report failures. What passes the level and the
+// categories goes to the sink, the console by default.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    // only as the level of the logger, nothing is written
+    Off,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogCategory {
+    // begin_work and complete_work, the work loop and updates
+    Render,
+    // commit_root, effects and refs
+    Commit,
+    Hooks,
+    // what host configs do with their nodes, e.g. the dom
+    Host,
+}
+
+pub struct LogRecord<'a> {
+    pub level: LogLevel,
+    pub category: LogCategory,
+    pub message: &'a str,
+}
+
+pub type LogSink = Box<dyn Fn(&LogRecord)>;
+
+static mut LEVEL: LogLevel = LogLevel::Warn;
+// a bit per LogCategory
+static mut CATEGORIES: u8 = 0b1111;
+static mut SINK: Option<LogSink> = None;
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+            LogLevel::Off => "off",
+        }
+    }
+
+    pub fn parse(level: &str) -> Option<LogLevel> {
+        match level {
+            "trace" => Some(LogLevel::Trace),
+            "debug" => Some(LogLevel::Debug),
+            "info" => Some(LogLevel::Info),
+            "warn" => Some(LogLevel::Warn),
+            "error" => Some(LogLevel::Error),
+            "off" => Some(LogLevel::Off),
+            _ => None,
+        }
+    }
+}
+
+impl LogCategory {
+    pub const ALL: [LogCategory; 4] = [
+        LogCategory::Render,
+        LogCategory::Commit,
+        LogCategory::Hooks,
+        LogCategory::Host,
+    ];
+
+    fn bit(&self) -> u8 {
+        1 << (*self as u8)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogCategory::Render => "render",
+            LogCategory::Commit => "commit",
+            LogCategory::Hooks => "hooks",
+            LogCategory::Host => "host",
+        }
+    }
+
+    pub fn parse(category: &str) -> Option<LogCategory> {
+        LogCategory::ALL
+            .into_iter()
+            .find(|known| known.as_str() == category)
+    }
+}
+
+// records below level are dropped, warn by default
+pub fn set_log_level(level: LogLevel) {
+    unsafe {
+        LEVEL = level;
+    }
+}
+
+// only records of these categories are written, all of them by default
+pub fn set_log_categories(categories: &[LogCategory]) {
+    unsafe {
+        CATEGORIES = categories
+            .iter()
+            .fold(0, |categories, category| categories | category.bit());
+    }
+}
+
+// None writes to the console again
+pub fn set_log_sink(sink: Option<LogSink>) {
+    unsafe {
+        SINK = sink;
+    }
+}
+
+pub fn enabled(level: LogLevel, category: LogCategory) -> bool {
+    unsafe { level >= LEVEL && LEVEL != LogLevel::Off && CATEGORIES & category.bit() != 0 }
+}
+
+pub fn write(level: LogLevel, category: LogCategory, message: &str) {
+    let record = LogRecord {
+        level,
+        category,
+        message,
+    };
+    match unsafe { (*addr_of!(SINK)).as_ref() } {
+        Some(sink) => sink(&record),
+        None => write_to_console(&record),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_to_console(record: &LogRecord) {
+    let message = JsValue::from_str(&format!(
+        "[{}] {}",
+        record.category.as_str(),
+        record.message
+    ));
+    match record.level {
+        LogLevel::Error => web_sys::console::error_1(&message),
+        LogLevel::Warn => web_sys::console::warn_1(&message),
+        _ => web_sys::console::log_1(&message),
+    }
+}
+
+// there is no console outside of wasm, e.g. in native tests
+#[cfg(not(target_arch = "wasm32"))]
+fn write_to_console(record: &LogRecord) {
+    eprintln!("[{}] {}", record.category.as_str(), record.message);
+}
+
+// options is { level, categories, sink } and every one of them is optional, e.g.
+// { level: 'debug', categories: ['commit', 'hooks'], sink: ({ level, category, message }) => {} },
+// a sink that is null writes to the console again. An unknown level or category is an error
+// and leaves the configuration as it was.
+pub fn configure_logging(options: &JsValue) -> Result<(), JsValue> {
+    let get = |name: &str| Reflect::get(options, &name.into()).unwrap_or(JsValue::undefined());
+
+    let level = match get("level").as_string() {
+        Some(level) => match LogLevel::parse(&level) {
+            Some(level) => Some(level),
+            None => {
+                return Err(JsValue::from_str(&format!("unknown log level {}", level)));
+            }
+        },
+        None => None,
+    };
+
+    let categories = match get("categories").dyn_ref::<Array>() {
+        Some(categories) => {
+            let mut parsed = vec![];
+            for category in categories.iter() {
+                let name = category.as_string().unwrap_or_default();
+                match LogCategory::parse(&name) {
+                    Some(category) => parsed.push(category),
+                    None => {
+                        return Err(JsValue::from_str(&format!(
+                            "unknown log category {:?}",
+                            category
+                        )));
+                    }
+                }
+            }
+            Some(parsed)
+        }
+        None => None,
+    };
+
+    if let Some(level) = level {
+        set_log_level(level);
+    }
+    if let Some(categories) = categories {
+        set_log_categories(&categories);
+    }
+
+    let sink = get("sink");
+    if sink.is_null() {
+        set_log_sink(None);
+    } else if let Some(sink) = sink.dyn_ref::<Function>() {
+        let sink = sink.clone();
+        set_log_sink(Some(Box::new(move |record: &LogRecord| {
+            let object = Object::new();
+            let _ = Reflect::set(&object, &"level".into(), &record.level.as_str().into());
+            let _ = Reflect::set(
+                &object,
+                &"category".into(),
+                &record.category.as_str().into(),
+            );
+            let _ = Reflect::set(&object, &"message".into(), &record.message.into());
+            if sink.call1(&JsValue::null(), &object).is_err() {
+                write_to_console(record);
+            }
+        })));
+    }
+    Ok(())
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_always {
+    ( $level:ident, $category:ident, $( $t:tt )* ) => {
+        if $crate::logger::enabled(
+            $crate::logger::LogLevel::$level,
+            $crate::logger::LogCategory::$category,
+        ) {
+            $crate::logger::write(
+                $crate::logger::LogLevel::$level,
+                $crate::logger::LogCategory::$category,
+                &format!( $( $t )* ),
+            );
+        }
+    };
+}
+
+#[cfg(all(feature = "logging", debug_assertions))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log {
+    ( $( $t:tt )* ) => { $crate::__log_always!($( $t )*) };
+}
+
+// the arguments are still type checked, but never evaluated
+#[cfg(not(all(feature = "logging", debug_assertions)))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log {
+    ( $level:ident, $category:ident, $( $t:tt )* ) => {
+        if false {
+            let _ = format!( $( $t )* );
+        }
+    };
+}
+
+// e.g. debug!(Commit, "commit_root {:?}", root), the category is a LogCategory
+#[macro_export]
+macro_rules! trace {
+    ( $category:ident, $( $t:tt )* ) => { $crate::__log!(Trace, $category, $( $t )*) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ( $category:ident, $( $t:tt )* ) => { $crate::__log!(Debug, $category, $( $t )*) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ( $category:ident, $( $t:tt )* ) => { $crate::__log!(Info, $category, $( $t )*) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ( $category:ident, $( $t:tt )* ) => { $crate::__log_always!(Warn, $category, $( $t )*) };
+}
+
+#[macro_export]
+macro_rules! error {
+    ( $category:ident, $( $t:tt )* ) => { $crate::__log_always!(Error, $category, $( $t )*) };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    #[test]
+    fn writes_records_that_pass_the_level_and_categories() {
+        let records = Rc::new(RefCell::new(vec![]));
+        let records_cloned = records.clone();
+        set_log_sink(Some(Box::new(move |record: &LogRecord| {
+            records_cloned.borrow_mut().push((
+                record.level,
+                record.category,
+                record.message.to_string(),
+            ));
+        })));
+        set_log_level(LogLevel::Warn);
+        set_log_categories(&LogCategory::ALL);
+
+        crate::debug!(Render, "dropped");
+        crate::warn!(Hooks, "kept {}", 1);
+        set_log_categories(&[LogCategory::Render]);
+        crate::error!(Commit, "not in the categories");
+        crate::error!(Render, "kept {}", 2);
+
+        set_log_sink(None);
+        set_log_categories(&LogCategory::ALL);
+        assert_eq!(
+            *records.borrow(),
+            vec![
+                (LogLevel::Warn, LogCategory::Hooks, "kept 1".to_string()),
+                (LogLevel::Error, LogCategory::Render, "kept 2".to_string()),
+            ]
+        );
+    }
+}
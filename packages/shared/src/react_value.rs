@@ -0,0 +1,496 @@
+use std::{
+    any::Any,
+    cell::OnceCell,
+    fmt::{Debug, Formatter},
+    ops::Deref,
+    rc::Rc,
+};
+
+use web_sys::js_sys::{Array, Function, Object, Reflect};
+use web_sys::wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+
+use crate::{REACT_ELEMENT_TYPE, REACT_PORTAL_TYPE};
+
+// Element types, props, keys, children and state are Values. A Value wraps anything that
+// implements ReactValue, a JsValue for the renderers running in javascript or e.g. a plain rust
+// type in native tests, and the reconciler only reads them through the methods below. It is a
+// trait object rather than a type parameter because fibers and their values are kept in the
+// statics of the work loop.
+pub type ElementType = Value;
+pub type Props = Value;
+pub type Key = Value;
+pub type StateValue = Value;
+// what a component returns, e.g. an element, a string or a list of those
+pub type ReactNode = Value;
+
+pub enum ReactNodeKind {
+    Text(String),
+    List(Vec<ReactNode>),
+    Element,
+    Portal,
+    // null, undefined and booleans render nothing
+    Empty,
+    Unsupported,
+}
+
+pub trait ReactValue: Debug {
+    fn as_any(&self) -> &dyn Any;
+    // Object.is for javascript values
+    fn is_same(&self, other: &Value) -> bool;
+    // null or undefined
+    fn is_nullish(&self) -> bool {
+        false
+    }
+    fn as_string(&self) -> Option<String> {
+        None
+    }
+    fn as_f64(&self) -> Option<f64> {
+        None
+    }
+    fn as_bool(&self) -> Option<bool> {
+        None
+    }
+    fn as_list(&self) -> Option<Vec<Value>> {
+        None
+    }
+    // the property names of an object
+    fn keys(&self) -> Option<Vec<String>> {
+        None
+    }
+    // a property of an object, undefined when there is none
+    fn get(&self, _name: &str) -> Value {
+        Value::undefined()
+    }
+    // e.g. the current of a ref object or the _currentValue of a context
+    fn set(&self, name: &str, _value: Value) -> Result<(), Value> {
+        Err(Value::from_string(format!(
+            "Cannot set {} of {:?}",
+            name, self
+        )))
+    }
+    fn is_function(&self) -> bool {
+        false
+    }
+    fn call(&self, _args: &[Value]) -> Result<Value, Value> {
+        Err(Value::from_string(format!("{:?} is not a function", self)))
+    }
+    // calls the function in property name with this value as `this`, e.g. then of a thenable
+    fn call_method(&self, name: &str, args: &[Value]) -> Result<Value, Value> {
+        self.get(name).call(args)
+    }
+    // the value as javascript sees it, e.g. the props handed to the dom
+    fn to_js(&self) -> JsValue {
+        JsValue::undefined()
+    }
+
+    // The roles of a value in the reconciler. The defaults follow the element protocol of
+    // react, { $$typeof, type, key, ref, props }, on top of get.
+
+    // the $$typeof of an element or a special type, e.g. a context provider
+    fn type_of(&self) -> Option<String> {
+        self.get("$$typeof").as_string()
+    }
+    // the tag of a host component, e.g. "div"
+    fn as_host_type(&self) -> Option<String> {
+        self.as_string()
+    }
+    // unkeyed children are matched by their index instead
+    fn as_key_string(&self) -> Option<String> {
+        self.as_string()
+    }
+    // the text of a HostText fiber, its props are the rendered string or number itself
+    fn text_content(&self) -> Option<String> {
+        self.as_string()
+            .or_else(|| self.as_f64().map(|number| number.to_string()))
+    }
+    fn kind(&self) -> ReactNodeKind {
+        if let Some(text) = self.text_content() {
+            return ReactNodeKind::Text(text);
+        }
+        if let Some(list) = self.as_list() {
+            return ReactNodeKind::List(list);
+        }
+        if self.is_nullish() || self.as_bool().is_some() {
+            return ReactNodeKind::Empty;
+        }
+        match self.type_of() {
+            Some(type_of) if type_of == REACT_ELEMENT_TYPE => ReactNodeKind::Element,
+            Some(type_of) if type_of == REACT_PORTAL_TYPE => ReactNodeKind::Portal,
+            _ => ReactNodeKind::Unsupported,
+        }
+    }
+    fn element_key(&self) -> Key {
+        self.get("key")
+    }
+    fn element_type(&self) -> ElementType {
+        self.get("type")
+    }
+    fn element_props(&self) -> Props {
+        self.get("props")
+    }
+    fn element_ref(&self) -> Value {
+        self.get("ref")
+    }
+    fn portal_children(&self) -> ReactNode {
+        self.get("children")
+    }
+    fn portal_container_info(&self) -> Value {
+        self.get("containerInfo")
+    }
+}
+
+#[derive(Clone)]
+pub struct Value(Rc<dyn ReactValue>);
+
+impl Value {
+    pub fn new(value: impl ReactValue + 'static) -> Self {
+        Value(Rc::new(value))
+    }
+
+    pub fn undefined() -> Self {
+        Value::new(Primitive::Undefined)
+    }
+
+    pub fn null() -> Self {
+        Value::new(Primitive::Null)
+    }
+
+    pub fn from_bool(value: bool) -> Self {
+        Value::new(Primitive::Bool(value))
+    }
+
+    pub fn from_f64(value: f64) -> Self {
+        Value::new(Primitive::Number(value))
+    }
+
+    pub fn from_string(value: impl Into<String>) -> Self {
+        Value::new(Primitive::String(value.into()))
+    }
+
+    // e.g. the listener the reconciler attaches to a thenable
+    pub fn function(function: impl Fn(&[Value]) -> Result<Value, Value> + 'static) -> Self {
+        Value::new(NativeFunction {
+            function: Rc::new(function),
+            js: OnceCell::new(),
+        })
+    }
+
+    pub fn from_list(items: Vec<Value>) -> Self {
+        Value::new(List(items))
+    }
+
+    // the argument at index of a call, undefined when the caller passed fewer
+    pub fn arg(args: &[Value], index: usize) -> Self {
+        args.get(index).cloned().unwrap_or_else(Value::undefined)
+    }
+
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        self.0.as_any().downcast_ref::<T>()
+    }
+}
+
+impl Deref for Value {
+    type Target = dyn ReactValue;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl Debug for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_same(other)
+    }
+}
+
+impl From<JsValue> for Value {
+    fn from(value: JsValue) -> Self {
+        Value::new(value)
+    }
+}
+
+impl From<&JsValue> for Value {
+    fn from(value: &JsValue) -> Self {
+        Value::new(value.clone())
+    }
+}
+
+// e.g. a prop written in rust, rsx!(div { title: "a" })
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::from_string(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::from_string(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::from_f64(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::from_f64(value as f64)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::from_bool(value)
+    }
+}
+
+impl From<Value> for JsValue {
+    fn from(value: Value) -> Self {
+        value.to_js()
+    }
+}
+
+// values the reconciler makes itself, e.g. the null ref of a fiber or an error message
+#[derive(Debug, Clone, PartialEq)]
+enum Primitive {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl ReactValue for Primitive {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_same(&self, other: &Value) -> bool {
+        match (
+            other.downcast_ref::<Primitive>(),
+            other.downcast_ref::<JsValue>(),
+        ) {
+            (Some(other), _) => self == other,
+            (_, Some(other)) => Object::is(&self.to_js(), other),
+            _ => false,
+        }
+    }
+
+    fn is_nullish(&self) -> bool {
+        matches!(self, Primitive::Undefined | Primitive::Null)
+    }
+
+    fn as_string(&self) -> Option<String> {
+        match self {
+            Primitive::String(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Primitive::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            Primitive::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn to_js(&self) -> JsValue {
+        match self {
+            Primitive::Undefined => JsValue::undefined(),
+            Primitive::Null => JsValue::null(),
+            Primitive::Bool(value) => JsValue::from_bool(*value),
+            Primitive::Number(value) => JsValue::from_f64(*value),
+            Primitive::String(value) => JsValue::from_str(value),
+        }
+    }
+}
+
+type NativeFn = Rc<dyn Fn(&[Value]) -> Result<Value, Value>>;
+
+struct NativeFunction {
+    function: NativeFn,
+    // created once, javascript sees the same function every time, e.g. a stable setState
+    js: OnceCell<JsValue>,
+}
+
+impl Debug for NativeFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("NativeFunction")
+    }
+}
+
+impl ReactValue for NativeFunction {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_same(&self, other: &Value) -> bool {
+        other
+            .downcast_ref::<NativeFunction>()
+            .is_some_and(|other| Rc::ptr_eq(&self.function, &other.function))
+    }
+
+    fn is_function(&self) -> bool {
+        true
+    }
+
+    fn call(&self, args: &[Value]) -> Result<Value, Value> {
+        (self.function)(args)
+    }
+
+    // javascript passes up to three arguments, the hooks take no more, missing ones are undefined
+    fn to_js(&self) -> JsValue {
+        self.js
+            .get_or_init(|| {
+                let function = self.function.clone();
+                Closure::wrap(Box::new(move |a: JsValue, b: JsValue, c: JsValue| {
+                    function(&[Value::from(a), Value::from(b), Value::from(c)])
+                        .map(|value| value.to_js())
+                        .map_err(|error| error.to_js())
+                })
+                    as Box<dyn Fn(JsValue, JsValue, JsValue) -> Result<JsValue, JsValue>>)
+                .into_js_value()
+            })
+            .clone()
+    }
+}
+
+// e.g. the [state, setState] pair of useState, an array once it reaches javascript
+#[derive(Debug)]
+struct List(Vec<Value>);
+
+impl ReactValue for List {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_same(&self, other: &Value) -> bool {
+        other
+            .downcast_ref::<List>()
+            .is_some_and(|other| std::ptr::eq(self, other))
+    }
+
+    fn as_list(&self) -> Option<Vec<Value>> {
+        Some(self.0.clone())
+    }
+
+    fn to_js(&self) -> JsValue {
+        self.0
+            .iter()
+            .map(|item| item.to_js())
+            .collect::<Array>()
+            .into()
+    }
+}
+
+impl ReactValue for JsValue {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_same(&self, other: &Value) -> bool {
+        match other.downcast_ref::<JsValue>() {
+            Some(other) => Object::is(self, other),
+            None => other.downcast_ref::<Primitive>().is_some() && Object::is(self, &other.to_js()),
+        }
+    }
+
+    fn is_nullish(&self) -> bool {
+        self.is_null() || self.is_undefined()
+    }
+
+    fn as_string(&self) -> Option<String> {
+        JsValue::as_string(self)
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        JsValue::as_f64(self)
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        JsValue::as_bool(self)
+    }
+
+    fn as_list(&self) -> Option<Vec<Value>> {
+        self.dyn_ref::<Array>()
+            .map(|list| list.iter().map(Value::from).collect())
+    }
+
+    fn keys(&self) -> Option<Vec<String>> {
+        let object = self.dyn_ref::<Object>().filter(|_| self.is_object())?;
+        Some(
+            Object::keys(object)
+                .iter()
+                .filter_map(|key| key.as_string())
+                .collect(),
+        )
+    }
+
+    fn get(&self, name: &str) -> Value {
+        if !self.is_object() && !JsValue::is_function(self) {
+            return Value::undefined();
+        }
+        Reflect::get(self, &JsValue::from_str(name))
+            .unwrap_or(JsValue::undefined())
+            .into()
+    }
+
+    fn set(&self, name: &str, value: Value) -> Result<(), Value> {
+        Reflect::set(self, &JsValue::from_str(name), &value.to_js())
+            .map(|_| ())
+            .map_err(Value::from)
+    }
+
+    fn is_function(&self) -> bool {
+        JsValue::is_function(self)
+    }
+
+    fn call(&self, args: &[Value]) -> Result<Value, Value> {
+        let function = self
+            .dyn_ref::<Function>()
+            .ok_or_else(|| Value::from_string(format!("{:?} is not a function", self)))?;
+        let args = args.iter().map(|arg| arg.to_js()).collect::<Array>();
+        function
+            .apply(&JsValue::null(), &args)
+            .map(Value::from)
+            .map_err(Value::from)
+    }
+
+    fn call_method(&self, name: &str, args: &[Value]) -> Result<Value, Value> {
+        let method = Reflect::get(self, &JsValue::from_str(name))
+            .map_err(Value::from)?
+            .dyn_into::<Function>()
+            .map_err(Value::from)?;
+        let args = args.iter().map(|arg| arg.to_js()).collect::<Array>();
+        method
+            .apply(self, &args)
+            .map(Value::from)
+            .map_err(Value::from)
+    }
+
+    fn to_js(&self) -> JsValue {
+        self.clone()
+    }
+
+    fn type_of(&self) -> Option<String> {
+        if !self.is_object() {
+            return None;
+        }
+        self.get("$$typeof").as_string()
+    }
+}
@@ -23,6 +23,36 @@ execSync(
         isTest ? '--target nodejs' : ''
     }`
 )
+execSync(
+    `wasm-pack build packages/react-canvas --out-dir ${cwd}/dist/react-canvas --out-name index ${
+        isTest ? '--target nodejs' : ''
+    }`
+)
+execSync(
+    `wasm-pack build packages/react-tui --out-dir ${cwd}/dist/react-tui --out-name index ${
+        isTest ? '--target nodejs' : ''
+    }`
+)
+
+// element types that are plain values rather than wasm exports
+const reactElementTypes = {
+    Suspense: 'react.suspense',
+    StrictMode: 'react.strict_mode',
+    Profiler: 'react.profiler'
+}
+const reactIndexFilename = isTest
+    ? `${cwd}/dist/react/index.js`
+    : `${cwd}/dist/react/index_bg.js`
+fs.appendFileSync(
+    reactIndexFilename,
+    Object.entries(reactElementTypes)
+        .map(([name, value]) =>
+            isTest
+                ? `module.exports.${name} = '${value}';\n`
+                : `export const ${name} = '${value}';\n`
+        )
+        .join('')
+)
 
 // modify react/package.json
 const packageJsonFilename = `${cwd}/dist/react/package.json`
@@ -37,15 +67,18 @@ packageJson.files.push(
 )
 fs.writeFileSync(packageJsonFilename, JSON.stringify(packageJson))
 
-// modify react-dom/index_bg.js
-const reactDomIndexFilename = isTest
-    ? `${cwd}/dist/react-dom/index.js`
-    : `${cwd}/dist/react-dom/index_bg.js`
+// modify the index_bg.js of every renderer
+for (const renderer of ['react-dom', 'react-canvas', 'react-tui']) {
+    const rendererIndexFilename = isTest
+        ? `${cwd}/dist/${renderer}/index.js`
+        : `${cwd}/dist/${renderer}/index_bg.js`
 
-const reactDomIndexBgData = fs.readFileSync(reactDomIndexFilename)
-fs.writeFileSync(
-    reactDomIndexFilename,
-    (isTest
-        ? 'const { updateDispatcher } = require("react");\n'
-        : 'import { updateDispatcher } from "react";\n') + reactDomIndexBgData
-)
+    const rendererIndexBgData = fs.readFileSync(rendererIndexFilename)
+    fs.writeFileSync(
+        rendererIndexFilename,
+        (isTest
+            ? 'const { updateDispatcher, startTransition, requestCurrentTransition } = require("react");\n'
+            : 'import { updateDispatcher, startTransition, requestCurrentTransition } from "react";\n') +
+            rendererIndexBgData
+    )
+}